@@ -1,11 +1,24 @@
 //! Channel messaging bindings for Rust
-//! Provides functions to list channels, read messages, and send messages by channel name
+//! Provides functions to manage channels, to read, send, edit, delete and search
+//! messages by channel name, and to attach files to messages
 
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{HeaderValue, AUTHORIZATION, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// How long a fetched channel name→id map is trusted before listing channels again
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Limit for a whole request made by a client from `ChannelClient::new`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Error, Debug)]
 pub enum ChannelError {
     #[error("HTTP request failed: {0}")]
@@ -16,6 +29,63 @@ pub enum ChannelError {
     InvalidHeader,
     #[error("Payload too large")]
     PayloadTooLarge,
+    #[error("Unauthorized: check the API token")]
+    Unauthorized,
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Rate limited")]
+    RateLimited {
+        /// Seconds from the `Retry-After` header
+        retry_after: Option<u64>,
+    },
+    #[error("Unexpected HTTP status {0}")]
+    UnexpectedStatus(u16),
+}
+
+/// Builds and sends the client's HTTP requests.
+///
+/// A plain `reqwest` client sends each request once; callers can supply their own
+/// transport to add timeouts, retries or proxies shared with the rest of their program.
+pub trait Transport: Send + Sync {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder;
+
+    fn send(&self, request: RequestBuilder) -> reqwest::Result<Response>;
+}
+
+impl Transport for Client {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        Client::request(self, method, url)
+    }
+
+    fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        request.send()
+    }
+}
+
+/// Map error statuses to typed errors, passing successful responses through
+fn check(response: Response) -> Result<Response, ChannelError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let path = response.url().path().to_string();
+    Err(match status {
+        StatusCode::UNAUTHORIZED => ChannelError::Unauthorized,
+        StatusCode::FORBIDDEN => ChannelError::Forbidden(path),
+        StatusCode::NOT_FOUND => ChannelError::NotFound(path),
+        StatusCode::PAYLOAD_TOO_LARGE => ChannelError::PayloadTooLarge,
+        StatusCode::TOO_MANY_REQUESTS => ChannelError::RateLimited {
+            retry_after: response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok()),
+        },
+        other => ChannelError::UnexpectedStatus(other.as_u16()),
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +147,14 @@ pub struct Message {
     pub extra: serde_json::Value,
 }
 
+/// Search endpoints answer either with a bare list or wrapped in `results`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SearchResponse {
+    Wrapped { results: Vec<Message> },
+    List(Vec<Message>),
+}
+
 #[derive(Debug, Serialize)]
 struct SendMessagePayload {
     content: String,
@@ -87,46 +165,158 @@ struct CreateChannelPayload {
     name: String,
 }
 
+/// Page of messages to read: at most `limit`, older than `before` and/or newer than
+/// `after` (both message IDs)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessageQuery {
+    pub limit: Option<u32>,
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+}
+
+impl MessageQuery {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        if let Some(before) = self.before {
+            params.push(("before", before.to_string()));
+        }
+        if let Some(after) = self.after {
+            params.push(("after", after.to_string()));
+        }
+        params
+    }
+}
+
+/// Channel name→id map and when it was fetched
+struct ChannelCache {
+    ids: HashMap<String, i64>,
+    fetched_at: Instant,
+}
+
 pub struct ChannelClient {
     url: String,
-    client: Client,
+    token: HeaderValue,
+    transport: Box<dyn Transport>,
+    cache: Mutex<Option<ChannelCache>>,
+    cache_ttl: Duration,
+    create_missing: bool,
 }
 
 impl ChannelClient {
+    /// Create a client sending each request once, giving up after `DEFAULT_TIMEOUT`
     pub fn new(url: &str, token: &str) -> Result<Self, ChannelError> {
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            HeaderName::from_static("authorization"),
-            HeaderValue::from_str(token).map_err(|_| ChannelError::InvalidHeader)?,
-        );
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-        let client = Client::builder().default_headers(headers).build()?;
+        Self::with_transport(url, token, Client::builder().timeout(DEFAULT_TIMEOUT).build()?)
+    }
 
+    /// Create a client whose requests go through `transport`
+    pub fn with_transport(
+        url: &str,
+        token: &str,
+        transport: impl Transport + 'static,
+    ) -> Result<Self, ChannelError> {
         Ok(Self {
             url: url.trim_end_matches('/').to_string(),
-            client,
+            token: HeaderValue::from_str(token).map_err(|_| ChannelError::InvalidHeader)?,
+            transport: Box::new(transport),
+            cache: Mutex::new(None),
+            cache_ttl: DEFAULT_CACHE_TTL,
+            create_missing: false,
         })
     }
 
-    /// List all available channels
+    /// Set how long channel IDs are cached; `Duration::ZERO` looks them up every time
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Let `send_message` create channels that don't exist instead of failing
+    pub fn with_create_missing(mut self, create_missing: bool) -> Self {
+        self.create_missing = create_missing;
+        self
+    }
+
+    /// Forget cached channel IDs, so the next lookup lists channels again
+    pub fn invalidate_cache(&self) {
+        *self.cache.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Start an authorized request to a path under the API URL
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.transport
+            .request(method, &format!("{}{}", self.url, path))
+            .header(AUTHORIZATION, self.token.clone())
+    }
+
+    /// Send a request, mapping error statuses to typed errors
+    fn execute(&self, request: RequestBuilder) -> Result<Response, ChannelError> {
+        check(self.transport.send(request)?)
+    }
+
+    /// List all available channels, refreshing the channel ID cache
     pub fn list_channels(&self) -> Result<Vec<Channel>, ChannelError> {
-        let response = self
-            .client
-            .get(format!("{}/channels", self.url))
-            .send()?
-            .error_for_status()?;
+        let response = self.execute(self.request(Method::GET, "/channels"))?;
 
         let wrapper: ChannelsResponse = response.json()?;
+
+        *self.cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(ChannelCache {
+            ids: wrapper.channels.iter().map(|c| (c.name.clone(), c.id)).collect(),
+            fetched_at: Instant::now(),
+        });
+
         Ok(wrapper.channels)
     }
 
-    /// Find a channel ID by its name
+    /// Find a channel ID by its name, from the cache while it is fresh
     pub fn find_channel_id_by_name(&self, name: &str) -> Result<Option<i64>, ChannelError> {
+        if let Some(id) = self.cached_id(name) {
+            return Ok(Some(id));
+        }
+
         let channels = self.list_channels()?;
         Ok(channels.into_iter().find(|c| c.name == name).map(|c| c.id))
     }
 
+    fn cached_id(&self, name: &str) -> Option<i64> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .as_ref()
+            .filter(|c| c.fetched_at.elapsed() < self.cache_ttl)
+            .and_then(|c| c.ids.get(name).copied())
+    }
+
+    fn cache_id(&self, name: &str, id: i64) {
+        if let Some(cache) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            cache.ids.insert(name.to_string(), id);
+        }
+    }
+
+    /// Channel ID for sending, creating the channel only when that was enabled
+    fn resolve_for_send(&self, channel_name: &str) -> Result<i64, ChannelError> {
+        match self.find_channel_id_by_name(channel_name)? {
+            Some(id) => Ok(id),
+            None if self.create_missing => Ok(self.create_channel(channel_name)?.id),
+            None => Err(ChannelError::ChannelNotFound(channel_name.to_string())),
+        }
+    }
+
+    /// Channel ID for a name, or `ChannelNotFound`
+    fn require_channel_id(&self, channel_name: &str) -> Result<i64, ChannelError> {
+        self.find_channel_id_by_name(channel_name)?
+            .ok_or_else(|| ChannelError::ChannelNotFound(channel_name.to_string()))
+    }
+
+    /// Forget cached IDs after a 404, which means a cached channel no longer exists
+    fn invalidate_on_not_found<T>(&self, result: Result<T, ChannelError>) -> Result<T, ChannelError> {
+        if matches!(result, Err(ChannelError::NotFound(_))) {
+            self.invalidate_cache();
+        }
+        result
+    }
+
     /// Read channel details by name
     pub fn read_channel(&self, name: &str) -> Result<Option<Channel>, ChannelError> {
         let channels = self.list_channels()?;
@@ -139,14 +329,40 @@ impl ChannelClient {
             name: name.to_string(),
         };
 
-        let response = self
-            .client
-            .post(format!("{}/channels/", self.url))
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        let response = self.execute(self.request(Method::POST, "/channels/").json(&payload))?;
+
+        let channel: Channel = response.json()?;
+        self.cache_id(&channel.name, channel.id);
+        Ok(channel)
+    }
+
+    /// Rename a channel
+    pub fn rename_channel(&self, name: &str, new_name: &str) -> Result<(), ChannelError> {
+        let channel_id = self.require_channel_id(name)?;
+        let payload = CreateChannelPayload {
+            name: new_name.to_string(),
+        };
+
+        let result = self.execute(
+            self.request(Method::PUT, &format!("/channels/{}", channel_id))
+                .json(&payload),
+        );
+        // The cached name→id map is stale either way
+        self.invalidate_cache();
+        result?;
+
+        Ok(())
+    }
+
+    /// Delete a channel and all its messages
+    pub fn delete_channel(&self, name: &str) -> Result<(), ChannelError> {
+        let channel_id = self.require_channel_id(name)?;
+
+        let result = self.execute(self.request(Method::DELETE, &format!("/channels/{}", channel_id)));
+        self.invalidate_cache();
+        result?;
 
-        Ok(response.json()?)
+        Ok(())
     }
 
     /// Read messages from a channel by name
@@ -155,48 +371,143 @@ impl ChannelClient {
         channel_name: &str,
         limit: Option<u32>,
     ) -> Result<Vec<Message>, ChannelError> {
-        let channel_id = self
-            .find_channel_id_by_name(channel_name)?
-            .ok_or_else(|| ChannelError::ChannelNotFound(channel_name.to_string()))?;
+        self.read_messages_page(
+            channel_name,
+            &MessageQuery {
+                limit,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Read one page of messages; pass the oldest ID seen as `before` to page backwards
+    pub fn read_messages_page(
+        &self,
+        channel_name: &str,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, ChannelError> {
+        let channel_id = self.require_channel_id(channel_name)?;
+
+        let result = self.execute(
+            self.request(Method::GET, &format!("/channels/{}/messages", channel_id))
+                .query(&query.params()),
+        );
+
+        Ok(self.invalidate_on_not_found(result)?.json()?)
+    }
 
-        let mut url = format!("{}/channels/{}/messages", self.url, channel_id);
-        if let Some(limit) = limit {
-            url.push_str(&format!("?limit={}", limit));
+    /// Search message contents, optionally within one channel
+    pub fn search_messages(
+        &self,
+        query: &str,
+        channel_name: Option<&str>,
+    ) -> Result<Vec<Message>, ChannelError> {
+        let mut params = vec![("query", query.to_string())];
+        if let Some(name) = channel_name {
+            params.push(("channelId", self.require_channel_id(name)?.to_string()));
         }
 
-        let response = self.client.get(&url).send()?.error_for_status()?;
+        let response = self.execute(self.request(Method::GET, "/search").query(&params))?;
 
-        Ok(response.json()?)
+        Ok(match response.json()? {
+            SearchResponse::Wrapped { results } => results,
+            SearchResponse::List(messages) => messages,
+        })
     }
 
-    /// Send a message to a channel by name, creating the channel if it doesn't exist
+    /// Send a message to a channel by name.
+    ///
+    /// The channel is only created when it doesn't exist if `with_create_missing(true)`
+    /// was set; otherwise this fails with `ChannelNotFound`.
     pub fn send_message(&self, channel_name: &str, content: &str) -> Result<Message, ChannelError> {
-        let channel_id = match self.find_channel_id_by_name(channel_name)? {
-            Some(id) => id,
-            None => {
-                // Channel doesn't exist, create it
-                let channel = self.create_channel(channel_name)?;
-                channel.id
-            }
+        let payload = SendMessagePayload {
+            content: content.to_string(),
         };
 
+        let channel_id = self.resolve_for_send(channel_name)?;
+        let mut result = self.post_message(channel_id, &payload);
+
+        // A 404 means the cached ID is stale (channel deleted or recreated): look it up again
+        if matches!(result, Err(ChannelError::NotFound(_))) {
+            self.invalidate_cache();
+            let channel_id = self.resolve_for_send(channel_name)?;
+            result = self.post_message(channel_id, &payload);
+        }
+
+        Ok(result?.json()?)
+    }
+
+    fn post_message(
+        &self,
+        channel_id: i64,
+        payload: &SendMessagePayload,
+    ) -> Result<Response, ChannelError> {
+        self.execute(
+            self.request(Method::POST, &format!("/channels/{}/messages", channel_id))
+                .json(payload),
+        )
+    }
+
+    /// Replace the content of an earlier message
+    pub fn update_message(
+        &self,
+        channel_name: &str,
+        message_id: i64,
+        content: &str,
+    ) -> Result<Message, ChannelError> {
+        let channel_id = self.require_channel_id(channel_name)?;
         let payload = SendMessagePayload {
             content: content.to_string(),
         };
 
-        let response = self
-            .client
-            .post(format!("{}/channels/{}/messages", self.url, channel_id))
-            .json(&payload)
-            .send()?;
+        let result = self.execute(
+            self.request(
+                Method::PUT,
+                &format!("/channels/{}/messages/{}", channel_id, message_id),
+            )
+            .json(&payload),
+        );
+
+        Ok(self.invalidate_on_not_found(result)?.json()?)
+    }
 
-        // Check for 413 Payload Too Large specifically
-        if response.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
-            return Err(ChannelError::PayloadTooLarge);
-        }
+    /// Attach a file to an earlier message, sent as the multipart field `file`
+    pub fn upload_file(
+        &self,
+        channel_name: &str,
+        message_id: i64,
+        file_name: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), ChannelError> {
+        let channel_id = self.require_channel_id(channel_name)?;
+        let part = Part::bytes(bytes)
+            .file_name(file_name.to_string())
+            .mime_str(content_type)?;
+
+        let result = self.execute(
+            self.request(
+                Method::POST,
+                &format!("/channels/{}/messages/{}/files", channel_id, message_id),
+            )
+            .multipart(Form::new().part("file", part)),
+        );
 
-        let response = response.error_for_status()?;
-        Ok(response.json()?)
+        self.invalidate_on_not_found(result)?;
+        Ok(())
+    }
+
+    /// Delete a message
+    pub fn delete_message(&self, channel_name: &str, message_id: i64) -> Result<(), ChannelError> {
+        let channel_id = self.require_channel_id(channel_name)?;
+
+        let result = self.execute(self.request(
+            Method::DELETE,
+            &format!("/channels/{}/messages/{}", channel_id, message_id),
+        ));
+
+        self.invalidate_on_not_found(result)?;
+        Ok(())
     }
 }
 
@@ -239,3 +550,374 @@ pub fn send_message(
 ) -> Result<Message, ChannelError> {
     create_client(url, token)?.send_message(channel_name, content)
 }
+
+/// Rename a channel
+pub fn rename_channel(
+    url: &str,
+    token: &str,
+    name: &str,
+    new_name: &str,
+) -> Result<(), ChannelError> {
+    create_client(url, token)?.rename_channel(name, new_name)
+}
+
+/// Delete a channel by name
+pub fn delete_channel(url: &str, token: &str, name: &str) -> Result<(), ChannelError> {
+    create_client(url, token)?.delete_channel(name)
+}
+
+/// Read one page of messages from a channel by name
+pub fn read_messages_page(
+    url: &str,
+    token: &str,
+    channel_name: &str,
+    query: &MessageQuery,
+) -> Result<Vec<Message>, ChannelError> {
+    create_client(url, token)?.read_messages_page(channel_name, query)
+}
+
+/// Search messages, optionally within one channel
+pub fn search_messages(
+    url: &str,
+    token: &str,
+    query: &str,
+    channel_name: Option<&str>,
+) -> Result<Vec<Message>, ChannelError> {
+    create_client(url, token)?.search_messages(query, channel_name)
+}
+
+/// Replace the content of a message
+pub fn update_message(
+    url: &str,
+    token: &str,
+    channel_name: &str,
+    message_id: i64,
+    content: &str,
+) -> Result<Message, ChannelError> {
+    create_client(url, token)?.update_message(channel_name, message_id, content)
+}
+
+/// Delete a message
+pub fn delete_message(
+    url: &str,
+    token: &str,
+    channel_name: &str,
+    message_id: i64,
+) -> Result<(), ChannelError> {
+    create_client(url, token)?.delete_message(channel_name, message_id)
+}
+
+/// Attach a file to a message
+pub fn upload_file(
+    url: &str,
+    token: &str,
+    channel_name: &str,
+    message_id: i64,
+    file_name: &str,
+    bytes: Vec<u8>,
+    content_type: &str,
+) -> Result<(), ChannelError> {
+    create_client(url, token)?.upload_file(channel_name, message_id, file_name, bytes, content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    const CHANNELS: &str = r#"{"channels": [{"id": 7, "name": "feeds"}]}"#;
+    const MESSAGE: &str = r#"{"id": 1, "content": "hi", "channelId": 7}"#;
+
+    fn client(server: &Server) -> ChannelClient {
+        ChannelClient::new(&server.url(), "token").unwrap()
+    }
+
+    #[test]
+    fn test_channel_ids_cached_between_sends() {
+        let mut server = Server::new();
+        let list = server
+            .mock("GET", "/channels")
+            .with_body(CHANNELS)
+            .expect(1)
+            .create();
+        let send = server
+            .mock("POST", "/channels/7/messages")
+            .match_header("authorization", "token")
+            .with_body(MESSAGE)
+            .expect(3)
+            .create();
+
+        let client = client(&server);
+        for _ in 0..3 {
+            client.send_message("feeds", "hi").unwrap();
+        }
+
+        list.assert();
+        send.assert();
+    }
+
+    #[test]
+    fn test_zero_ttl_lists_every_time() {
+        let mut server = Server::new();
+        let list = server
+            .mock("GET", "/channels")
+            .with_body(CHANNELS)
+            .expect(2)
+            .create();
+        server
+            .mock("POST", "/channels/7/messages")
+            .with_body(MESSAGE)
+            .create();
+
+        let client = client(&server).with_cache_ttl(Duration::ZERO);
+        client.send_message("feeds", "hi").unwrap();
+        client.send_message("feeds", "hi").unwrap();
+
+        list.assert();
+    }
+
+    #[test]
+    fn test_not_found_invalidates_and_retries_with_fresh_id() {
+        let mut server = Server::new();
+        let client = client(&server);
+
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        client.list_channels().unwrap();
+
+        // Channel was recreated under a new ID since it was cached
+        server.reset();
+        let relist = server
+            .mock("GET", "/channels")
+            .with_body(r#"{"channels": [{"id": 9, "name": "feeds"}]}"#)
+            .expect(1)
+            .create();
+        server.mock("POST", "/channels/7/messages").with_status(404).create();
+        let send = server
+            .mock("POST", "/channels/9/messages")
+            .with_body(MESSAGE)
+            .create();
+
+        client.send_message("feeds", "hi").unwrap();
+
+        relist.assert();
+        send.assert();
+    }
+
+    #[test]
+    fn test_missing_channel_not_created_by_default() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let create = server.mock("POST", "/channels/").expect(0).create();
+
+        let err = client(&server).send_message("fedes", "hi").unwrap_err();
+
+        assert!(matches!(err, ChannelError::ChannelNotFound(name) if name == "fedes"));
+        create.assert();
+    }
+
+    #[test]
+    fn test_missing_channel_created_when_enabled() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let create = server
+            .mock("POST", "/channels/")
+            .match_body(Matcher::Json(serde_json::json!({"name": "news"})))
+            .with_body(r#"{"id": 8, "name": "news"}"#)
+            .create();
+        server
+            .mock("POST", "/channels/8/messages")
+            .with_body(r#"{"id": 2, "content": "hi", "channelId": 8}"#)
+            .expect(2)
+            .create();
+
+        let client = client(&server).with_create_missing(true);
+        client.send_message("news", "hi").unwrap();
+        // The created channel is cached, so the second send neither lists nor creates
+        client.send_message("news", "hi").unwrap();
+
+        create.assert();
+    }
+
+    #[test]
+    fn test_status_codes_become_typed_errors() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let client = client(&server);
+
+        for (status, check) in [
+            (401, (|e| matches!(e, ChannelError::Unauthorized)) as fn(&ChannelError) -> bool),
+            (403, |e| matches!(e, ChannelError::Forbidden(_))),
+            (413, |e| matches!(e, ChannelError::PayloadTooLarge)),
+            (500, |e| matches!(e, ChannelError::UnexpectedStatus(500))),
+        ] {
+            let mock = server
+                .mock("POST", "/channels/7/messages")
+                .with_status(status)
+                .create();
+
+            let err = client.send_message("feeds", "hi").unwrap_err();
+            assert!(check(&err), "{} gave {:?}", status, err);
+            mock.remove();
+        }
+
+        server
+            .mock("POST", "/channels/7/messages")
+            .with_status(429)
+            .with_header("retry-after", "12")
+            .create();
+        assert!(matches!(
+            client.send_message("feeds", "hi").unwrap_err(),
+            ChannelError::RateLimited { retry_after: Some(12) }
+        ));
+    }
+
+    #[test]
+    fn test_read_messages_page_cursors() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let mock = server
+            .mock("GET", "/channels/7/messages")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("limit".into(), "20".into()),
+                Matcher::UrlEncoded("before".into(), "100".into()),
+            ]))
+            .with_body(format!("[{}]", MESSAGE))
+            .create();
+
+        let messages = client(&server)
+            .read_messages_page(
+                "feeds",
+                &MessageQuery {
+                    limit: Some(20),
+                    before: Some(100),
+                    after: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(messages.len(), 1);
+        mock.assert();
+    }
+
+    #[test]
+    fn test_update_and_delete_message() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let update = server
+            .mock("PUT", "/channels/7/messages/1")
+            .match_body(Matcher::Json(serde_json::json!({"content": "edited"})))
+            .with_body(r#"{"id": 1, "content": "edited", "channelId": 7}"#)
+            .create();
+        let delete = server
+            .mock("DELETE", "/channels/7/messages/1")
+            .with_status(204)
+            .create();
+
+        let client = client(&server);
+        assert_eq!(client.update_message("feeds", 1, "edited").unwrap().content, "edited");
+        client.delete_message("feeds", 1).unwrap();
+
+        update.assert();
+        delete.assert();
+    }
+
+    #[test]
+    fn test_rename_channel_invalidates_cache() {
+        let mut server = Server::new();
+        let list = server
+            .mock("GET", "/channels")
+            .with_body(CHANNELS)
+            .expect(2)
+            .create();
+        let rename = server
+            .mock("PUT", "/channels/7")
+            .match_body(Matcher::Json(serde_json::json!({"name": "news"})))
+            .with_status(200)
+            .with_body("{}")
+            .create();
+
+        let client = client(&server);
+        client.rename_channel("feeds", "news").unwrap();
+        client.find_channel_id_by_name("feeds").unwrap();
+
+        list.assert();
+        rename.assert();
+    }
+
+    #[test]
+    fn test_search_messages_in_channel() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        server
+            .mock("GET", "/search")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("query".into(), "rust release".into()),
+                Matcher::UrlEncoded("channelId".into(), "7".into()),
+            ]))
+            .with_body(format!(r#"{{"results": [{}]}}"#, MESSAGE))
+            .create();
+
+        let results = client(&server)
+            .search_messages("rust release", Some("feeds"))
+            .unwrap();
+
+        assert_eq!(results[0].id, 1);
+    }
+
+    #[test]
+    fn test_upload_file_as_multipart() {
+        let mut server = Server::new();
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        let upload = server
+            .mock("POST", "/channels/7/messages/1/files")
+            .match_header("authorization", "token")
+            .match_header("content-type", Matcher::Regex("^multipart/form-data; boundary=".into()))
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex(r#"name="file"; filename="cover.png""#.into()),
+                Matcher::Regex("Content-Type: image/png".into()),
+            ]))
+            .with_status(201)
+            .with_body("{}")
+            .create();
+
+        client(&server)
+            .upload_file("feeds", 1, "cover.png", b"\x89PNG".to_vec(), "image/png")
+            .unwrap();
+
+        upload.assert();
+    }
+
+    #[test]
+    fn test_requests_go_through_custom_transport() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Counting(Client, Arc<AtomicUsize>);
+
+        impl Transport for Counting {
+            fn request(&self, method: Method, url: &str) -> RequestBuilder {
+                self.0.request(method, url)
+            }
+
+            fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                request.send()
+            }
+        }
+
+        let mut server = Server::new();
+        server
+            .mock("GET", "/channels")
+            .match_header("authorization", "token")
+            .with_body(CHANNELS)
+            .create();
+
+        let sent = Arc::new(AtomicUsize::new(0));
+        let client =
+            ChannelClient::with_transport(&server.url(), "token", Counting(Client::new(), sent.clone()))
+                .unwrap();
+
+        assert_eq!(client.find_channel_id_by_name("feeds").unwrap(), Some(7));
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,59 @@
+#!/usr/bin/env bash
+# Run the test suite with the PostgreSQL backend against a throwaway server.
+#
+# Uses FEEDER_TEST_POSTGRES_URL as is when set. Otherwise starts a temporary
+# instance with the local PostgreSQL binaries (initdb, pg_ctl), or a container
+# when docker is available and they aren't. Extra arguments go to `cargo test`.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+run_tests() {
+    cargo test --workspace --features postgres "$@"
+}
+
+if [[ -n "${FEEDER_TEST_POSTGRES_URL:-}" ]]; then
+    run_tests "$@"
+    exit
+fi
+
+PORT="${FEEDER_TEST_POSTGRES_PORT:-54329}"
+export FEEDER_TEST_POSTGRES_URL="postgresql://feeder@127.0.0.1:${PORT}/feeder"
+
+PG_BIN="$(ls -d /usr/lib/postgresql/*/bin 2>/dev/null | sort -V | tail -n 1 || true)"
+if command -v initdb >/dev/null || [[ -n "$PG_BIN" ]]; then
+    PATH="${PG_BIN:+$PG_BIN:}$PATH"
+    DATA_DIR="$(mktemp -d)"
+
+    # The server refuses to run as root
+    as_owner=()
+    if [[ "$(id -u)" == 0 ]]; then
+        chown postgres "$DATA_DIR"
+        as_owner=(runuser -u postgres --)
+    fi
+
+    cleanup() {
+        "${as_owner[@]}" pg_ctl -D "$DATA_DIR" -m immediate stop >/dev/null 2>&1 || true
+        rm -rf "$DATA_DIR"
+    }
+    trap cleanup EXIT
+
+    "${as_owner[@]}" initdb -D "$DATA_DIR" -U feeder --auth=trust >/dev/null
+    "${as_owner[@]}" pg_ctl -D "$DATA_DIR" -l "$DATA_DIR/server.log" -w \
+        -o "-p $PORT -k $DATA_DIR -c listen_addresses=127.0.0.1 -c fsync=off" start >/dev/null
+    "${as_owner[@]}" createdb -h 127.0.0.1 -p "$PORT" -U feeder feeder
+elif command -v docker >/dev/null; then
+    CONTAINER="$(docker run -d --rm -p "127.0.0.1:${PORT}:5432" \
+        -e POSTGRES_USER=feeder -e POSTGRES_DB=feeder -e POSTGRES_HOST_AUTH_METHOD=trust \
+        postgres:16-alpine)"
+    trap 'docker stop "$CONTAINER" >/dev/null' EXIT
+
+    until docker exec "$CONTAINER" pg_isready -U feeder >/dev/null 2>&1; do
+        sleep 0.5
+    done
+else
+    echo "Needs PostgreSQL binaries (initdb, pg_ctl) or docker, or FEEDER_TEST_POSTGRES_URL" >&2
+    exit 1
+fi
+
+run_tests "$@"
@@ -1,4 +1,8 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use reqwest::header::{HeaderName, HeaderValue};
+
+use crate::domain::{FeedAuth, RequestOptions};
+use crate::errors::{FeederError, FeederResult};
 
 #[derive(Parser)]
 #[command(name = "feeder")]
@@ -15,6 +19,21 @@ pub enum Commands {
     Add {
         /// Feed URL to add
         url: String,
+
+        /// Watch the page itself for content changes instead of looking for a feed
+        #[arg(long)]
+        watch: bool,
+
+        /// CSS selector limiting which part of a watched page is compared
+        #[arg(long, requires = "watch")]
+        selector: Option<String>,
+
+        /// Pick the Nth discovered feed when the page advertises several
+        #[arg(long, conflicts_with = "watch")]
+        pick: Option<usize>,
+
+        #[command(flatten)]
+        request: RequestArgs,
     },
 
     /// Remove a feed (interactive selection)
@@ -23,10 +42,27 @@ pub enum Commands {
     /// List all feeds
     List,
 
+    /// Change the settings of a feed
+    Edit(EditArgs),
+
+    /// Show everything known about a feed
+    Show {
+        /// Feed ID, URL or title
+        feed: String,
+
+        /// Print the details as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Import feeds from OPML file
     Import {
         /// Path to OPML file
         path: String,
+
+        /// Import all feeds or none: if any feed fails, nothing is added
+        #[arg(long)]
+        atomic: bool,
     },
 
     /// Export feeds to OPML format
@@ -46,4 +82,271 @@ pub enum Commands {
         #[arg(long)]
         skip_notify: bool,
     },
+
+    /// Validate feeds again and update their titles, URLs and types
+    Refresh {
+        /// Only refresh this feed (ID, URL or title); all feeds if not given
+        feed: Option<String>,
+    },
+
+    /// Search notified articles by title, author and text
+    Search {
+        /// Words that must all appear; end a word with * to match prefixes
+        query: String,
+
+        /// Only search this feed (ID, URL or title)
+        #[arg(long)]
+        feed: Option<String>,
+
+        /// Only articles notified on or after a date (YYYY-MM-DD) or within N days (e.g. 7d)
+        #[arg(long, value_parser = parse_since)]
+        since: Option<String>,
+
+        /// Maximum number of results
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List recently notified articles
+    History {
+        /// Only list this feed (ID, URL or title)
+        #[arg(long)]
+        feed: Option<String>,
+
+        /// Maximum number of articles
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print articles as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Maintain the database
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbCommand {
+    /// Remove old notification cache entries and compact the database
+    ///
+    /// Limits default to FEEDER_RETENTION_DAYS and FEEDER_RETENTION_KEEP. Entries
+    /// still present in a feed are always kept, so nothing is notified twice.
+    Prune {
+        /// Keep entries notified within this many days
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Keep this many of the most recent entries of each feed
+        #[arg(long)]
+        keep: Option<u32>,
+    },
+
+    /// Copy the database to a file, safe while feeder is running
+    Backup {
+        /// Where to write the backup
+        path: String,
+    },
+
+    /// Replace the database with a backup
+    Restore {
+        /// Backup made with `feeder db backup`
+        path: String,
+    },
+
+    /// Check the database for corruption and inconsistent rows
+    Check,
+}
+
+/// Options of `feeder edit`
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// Feed ID, URL or title
+    pub feed: String,
+
+    /// Title to show in notifications and listings instead of the feed's own
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Go back to the feed's own title
+    #[arg(long, conflicts_with = "title")]
+    pub no_title: bool,
+
+    /// Push priority from 1 (min) to 5 (max) for ntfy and Gotify
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+    pub priority: Option<u8>,
+
+    /// Telegram chat ID for this feed's articles
+    #[arg(long)]
+    pub telegram_chat: Option<String>,
+
+    /// Attach each article's image to its Notebrook message (true or false)
+    #[arg(long)]
+    pub attach_image: Option<bool>,
+
+    #[command(flatten)]
+    pub request: RequestArgs,
+
+    /// Stop sending a header set with --header
+    #[arg(long, value_name = "NAME")]
+    pub remove_header: Vec<String>,
+
+    /// Stop sending credentials
+    #[arg(long, conflicts_with_all = ["basic_auth", "bearer", "cookie"])]
+    pub no_auth: bool,
+}
+
+impl EditArgs {
+    /// Whether any setting was given
+    pub fn has_changes(&self) -> bool {
+        self.title.is_some()
+            || self.no_title
+            || self.priority.is_some()
+            || self.telegram_chat.is_some()
+            || self.attach_image.is_some()
+            || !self.request.is_empty()
+            || !self.remove_header.is_empty()
+            || self.no_auth
+    }
+}
+
+/// Headers and credentials sent when fetching a feed
+#[derive(Args, Debug, Default)]
+pub struct RequestArgs {
+    /// Extra request header, repeatable
+    #[arg(long = "header", value_name = "NAME: VALUE")]
+    pub headers: Vec<String>,
+
+    /// HTTP Basic credentials
+    #[arg(long, value_name = "USER:PASSWORD", conflicts_with_all = ["bearer", "cookie"])]
+    pub basic_auth: Option<String>,
+
+    /// Bearer token sent as `Authorization: Bearer <token>`
+    #[arg(long, value_name = "TOKEN", conflicts_with = "cookie")]
+    pub bearer: Option<String>,
+
+    /// Cookie header value, e.g. a session cookie
+    #[arg(long, value_name = "COOKIE")]
+    pub cookie: Option<String>,
+}
+
+impl RequestArgs {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+            && self.basic_auth.is_none()
+            && self.bearer.is_none()
+            && self.cookie.is_none()
+    }
+
+    /// Add the given headers to `options`, replacing its credentials if new ones were given
+    pub fn apply(&self, options: &mut RequestOptions) -> FeederResult<()> {
+        for header in &self.headers {
+            let (name, value) = parse_header(header)?;
+            options.headers.insert(name, value);
+        }
+
+        if let Some(credentials) = &self.basic_auth {
+            let (username, password) = match credentials.split_once(':') {
+                Some((username, password)) => (username, Some(password.to_string())),
+                None => (credentials.as_str(), None),
+            };
+            options.auth = Some(FeedAuth::Basic {
+                username: username.to_string(),
+                password,
+            });
+        } else if let Some(token) = &self.bearer {
+            options.auth = Some(FeedAuth::Bearer {
+                token: token.clone(),
+            });
+        } else if let Some(cookie) = &self.cookie {
+            options.auth = Some(FeedAuth::Cookie {
+                cookie: cookie.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Split `Name: value`, checking both parts are valid in an HTTP header
+fn parse_header(header: &str) -> FeederResult<(String, String)> {
+    let invalid = || FeederError::InvalidInput(format!("Invalid header '{}', expected 'Name: value'", header));
+
+    let (name, value) = header.split_once(':').ok_or_else(invalid)?;
+    let (name, value) = (name.trim(), value.trim());
+
+    HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+    HeaderValue::from_str(value).map_err(|_| invalid())?;
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Parse `--since` into the `YYYY-MM-DD HH:MM:SS` form dates are stored in
+fn parse_since(value: &str) -> Result<String, String> {
+    let start = if let Some(days) = value.strip_suffix('d') {
+        let days: u32 = days
+            .parse()
+            .map_err(|_| format!("expected YYYY-MM-DD or a number of days like 7d, got '{}'", value))?;
+        chrono::Utc::now().date_naive() - chrono::Duration::days(days.into())
+    } else {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("expected YYYY-MM-DD or a number of days like 7d, got '{}'", value))?
+    };
+
+    Ok(format!("{} 00:00:00", start.format("%Y-%m-%d")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_args_apply() {
+        let args = RequestArgs {
+            headers: vec!["X-Api-Key:  abc ".to_string()],
+            basic_auth: Some("me:pa:ss".to_string()),
+            ..Default::default()
+        };
+
+        let mut options = RequestOptions::default();
+        args.apply(&mut options).unwrap();
+
+        assert_eq!(options.headers["X-Api-Key"], "abc");
+        assert_eq!(
+            options.auth,
+            Some(FeedAuth::Basic {
+                username: "me".to_string(),
+                password: Some("pa:ss".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_header_rejected() {
+        for header in ["no colon", "Bad Name: v", ": empty"] {
+            let args = RequestArgs {
+                headers: vec![header.to_string()],
+                ..Default::default()
+            };
+            assert!(args.apply(&mut RequestOptions::default()).is_err(), "{}", header);
+        }
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2024-03-01").unwrap(), "2024-03-01 00:00:00");
+
+        let today = chrono::Utc::now().date_naive();
+        assert_eq!(parse_since("0d").unwrap(), format!("{} 00:00:00", today));
+
+        assert!(parse_since("yesterday").is_err());
+        assert!(parse_since("2024-13-01").is_err());
+    }
 }
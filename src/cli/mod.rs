@@ -1,3 +1,3 @@
 pub mod commands;
 
-pub use commands::{Cli, Commands};
+pub use commands::{Cli, Commands, DbCommand, EditArgs, RequestArgs};
@@ -1,11 +1,183 @@
+use crate::domain::RetentionPolicy;
 use crate::errors::{FeederError, FeederResult};
+use crate::sinks::DeliveryMode;
+use crate::storage::StorageBackend;
+
+/// Prefix of variables that add a header to webhook requests, e.g.
+/// `FEEDER_WEBHOOK_HEADER_X_API_KEY=secret` sends `X-Api-Key: secret`
+const WEBHOOK_HEADER_PREFIX: &str = "FEEDER_WEBHOOK_HEADER_";
+
+/// Raster formats every client can display; SVG is left out since it can carry scripts
+const DEFAULT_ATTACH_TYPES: &[&str] = &["image/jpeg", "image/png", "image/gif", "image/webp"];
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub notebrook_url: String,
     pub notebrook_token: String,
     pub notebrook_channel: String,
+    /// Create `notebrook_channel` when it doesn't exist instead of failing
+    pub notebrook_create_channel: bool,
+    /// How long channel IDs are cached before listing channels again
+    pub notebrook_cache_ttl_secs: u64,
+    /// Largest article image attached to a Notebrook message
+    pub notebrook_attach_max_bytes: u64,
+    /// Content types of article images that may be attached
+    pub notebrook_attach_types: Vec<String>,
+    /// Backend holding feeds, the notification cache and history
+    pub storage: StorageBackend,
+    /// Location of the store: the SQLite database, the TOML/JSON file or the
+    /// PostgreSQL URL
     pub db_path: String,
+    /// Timeouts and retries for feed and Notebrook requests
+    pub http: HttpConfig,
+    /// Extra attempts for a notification that failed with a retryable error
+    pub notify_retries: u32,
+    /// Limits applied by `feeder db prune`
+    pub retention: RetentionPolicy,
+    pub webhook: Option<WebhookConfig>,
+    pub matrix: Option<MatrixConfig>,
+    pub email: Option<EmailConfig>,
+    pub ntfy: Option<NtfyConfig>,
+    pub gotify: Option<GotifyConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub exec: Option<ExecConfig>,
+    /// Discord incoming webhook URLs
+    pub discord_webhooks: Vec<String>,
+    /// Slack incoming webhook URLs
+    pub slack_webhooks: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub connect_timeout_secs: u64,
+    /// Limit for the whole request, from connecting to reading the body
+    pub timeout_secs: u64,
+    /// Extra attempts after a connection error, 429 or 5xx
+    pub retries: u32,
+    /// First backoff delay, doubled on each attempt and jittered
+    pub backoff_ms: u64,
+    /// Upper bound on any single delay, including a server's `Retry-After`
+    pub max_backoff_secs: u64,
+    pub user_agent: String,
+    /// HTTP or SOCKS proxy URL for all requests, e.g. `socks5h://127.0.0.1:9050`
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            timeout_secs: 30,
+            retries: 2,
+            backoff_ms: 500,
+            max_backoff_secs: 30,
+            user_agent: concat!("feeder/", env!("CARGO_PKG_VERSION")).to_string(),
+            proxy: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// JSON body template, see `WebhookSink` for placeholders
+    pub template: Option<String>,
+    pub headers: Vec<(String, String)>,
+    /// Signs each body with HMAC-SHA256 when set
+    pub secret: Option<String>,
+    pub timeout_secs: u64,
+    pub mode: DeliveryMode,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatrixConfig {
+    pub homeserver: String,
+    pub access_token: String,
+    /// Room ID such as `!abc123:example.org`
+    pub room_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender mailbox, e.g. `Feeder <feeder@example.com>`
+    pub from: String,
+    pub to: Vec<String>,
+    pub mode: DeliveryMode,
+}
+
+#[derive(Debug, Clone)]
+pub struct NtfyConfig {
+    /// Topic URL such as `https://ntfy.sh/my-feeds`
+    pub url: String,
+    pub token: Option<String>,
+    pub tags: Vec<String>,
+    /// Priority (1-5) for feeds without their own
+    pub priority: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct GotifyConfig {
+    pub url: String,
+    /// Application token
+    pub token: String,
+    /// Priority (1-5) for feeds without their own
+    pub priority: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    /// Chat for feeds without their own `telegram_chat` setting
+    pub chat_id: String,
+    pub disable_preview: bool,
+    /// Bot API base URL, overridable for self-hosted API servers
+    pub api_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecConfig {
+    /// Shell command run once per notification
+    pub command: String,
+    pub timeout_secs: u64,
+}
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    StartTls,
+    /// TLS from the first byte (usually port 465)
+    Tls,
+    /// Unencrypted, for local relays and test catchers
+    None,
+}
+
+impl SmtpTls {
+    fn default_port(self) -> u16 {
+        match self {
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        }
+    }
+}
+
+impl std::str::FromStr for SmtpTls {
+    type Err = FeederError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "starttls" => Ok(SmtpTls::StartTls),
+            "tls" | "ssl" => Ok(SmtpTls::Tls),
+            "none" | "plain" => Ok(SmtpTls::None),
+            _ => Err(FeederError::Config(format!("Unknown SMTP TLS mode: {}", s))),
+        }
+    }
 }
 
 impl Config {
@@ -38,18 +210,289 @@ impl Config {
         let notebrook_channel = std::env::var("NOTEBROOK_CHANNEL")
             .unwrap_or_else(|_| "feeds".to_string());
 
+        let storage = parse_var("FEEDER_STORAGE", StorageBackend::default())?;
+
         // Default db_path is relative to executable directory
-        let db_path = std::env::var("FEEDER_DB_PATH").unwrap_or_else(|_| {
-            exe_dir
-                .map(|d| d.join("feeder.db").to_string_lossy().into_owned())
-                .unwrap_or_else(|| "./feeder.db".to_string())
-        });
+        let db_path = match std::env::var("FEEDER_DB_PATH") {
+            Ok(path) => path,
+            Err(_) => {
+                let file_name = storage
+                    .default_file_name()
+                    .ok_or_else(|| FeederError::MissingEnvVar("FEEDER_DB_PATH".to_string()))?;
+                exe_dir
+                    .map(|d| d.join(file_name).to_string_lossy().into_owned())
+                    .unwrap_or_else(|| format!("./{}", file_name))
+            }
+        };
+
+        let notify_retries = parse_var("FEEDER_NOTIFY_RETRIES", 2)?;
 
         Ok(Self {
             notebrook_url,
             notebrook_token,
             notebrook_channel,
+            notebrook_create_channel: parse_var("NOTEBROOK_CREATE_CHANNEL", false)?,
+            notebrook_cache_ttl_secs: parse_var("NOTEBROOK_CACHE_TTL", 300)?,
+            notebrook_attach_max_bytes: parse_var("NOTEBROOK_ATTACH_MAX_BYTES", 5 * 1024 * 1024)?,
+            notebrook_attach_types: attach_types(),
+            storage,
             db_path,
+            http: HttpConfig::from_env()?,
+            notify_retries,
+            retention: RetentionPolicy {
+                days: parse_optional_var("FEEDER_RETENTION_DAYS")?,
+                keep_per_feed: parse_optional_var("FEEDER_RETENTION_KEEP")?,
+            },
+            webhook: WebhookConfig::from_env()?,
+            matrix: MatrixConfig::from_env()?,
+            email: EmailConfig::from_env()?,
+            ntfy: NtfyConfig::from_env()?,
+            gotify: GotifyConfig::from_env()?,
+            telegram: TelegramConfig::from_env()?,
+            exec: ExecConfig::from_env()?,
+            discord_webhooks: list_var("FEEDER_DISCORD_WEBHOOK_URL"),
+            slack_webhooks: list_var("FEEDER_SLACK_WEBHOOK_URL"),
+        })
+    }
+}
+
+impl HttpConfig {
+    fn from_env() -> FeederResult<Self> {
+        let defaults = Self::default();
+
+        Ok(Self {
+            connect_timeout_secs: parse_var(
+                "FEEDER_HTTP_CONNECT_TIMEOUT",
+                defaults.connect_timeout_secs,
+            )?,
+            timeout_secs: parse_var("FEEDER_HTTP_TIMEOUT", defaults.timeout_secs)?,
+            retries: parse_var("FEEDER_HTTP_RETRIES", defaults.retries)?,
+            backoff_ms: parse_var("FEEDER_HTTP_BACKOFF_MS", defaults.backoff_ms)?,
+            max_backoff_secs: parse_var("FEEDER_HTTP_MAX_BACKOFF", defaults.max_backoff_secs)?,
+            user_agent: optional_var("FEEDER_USER_AGENT").unwrap_or(defaults.user_agent),
+            proxy: optional_var("FEEDER_PROXY"),
+        })
+    }
+}
+
+impl WebhookConfig {
+    /// Webhooks are enabled by setting `FEEDER_WEBHOOK_URL` (comma-separated for several)
+    fn from_env() -> FeederResult<Option<Self>> {
+        let urls = match optional_var("FEEDER_WEBHOOK_URL") {
+            Some(urls) => split_list(&urls),
+            None => return Ok(None),
+        };
+
+        let template = match optional_var("FEEDER_WEBHOOK_TEMPLATE_FILE") {
+            Some(path) => Some(std::fs::read_to_string(&path).map_err(|e| {
+                FeederError::Config(format!("Cannot read webhook template {}: {}", path, e))
+            })?),
+            None => optional_var("FEEDER_WEBHOOK_TEMPLATE"),
+        };
+
+        let mut headers: Vec<(String, String)> = std::env::vars()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(WEBHOOK_HEADER_PREFIX)
+                    .map(|name| (header_name_from_var(name), value))
+            })
+            .collect();
+        headers.sort();
+
+        Ok(Some(Self {
+            urls,
+            template,
+            headers,
+            secret: optional_var("FEEDER_WEBHOOK_SECRET"),
+            timeout_secs: parse_var("FEEDER_WEBHOOK_TIMEOUT", 10)?,
+            mode: parse_var("FEEDER_WEBHOOK_MODE", DeliveryMode::Each)?,
+        }))
+    }
+}
+
+impl MatrixConfig {
+    /// Matrix is enabled by setting `FEEDER_MATRIX_HOMESERVER`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let homeserver = match optional_var("FEEDER_MATRIX_HOMESERVER") {
+            Some(homeserver) => homeserver,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            homeserver,
+            access_token: required_var("FEEDER_MATRIX_TOKEN")?,
+            room_id: required_var("FEEDER_MATRIX_ROOM")?,
+        }))
+    }
+}
+
+impl EmailConfig {
+    /// Email is enabled by setting `FEEDER_SMTP_HOST`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let host = match optional_var("FEEDER_SMTP_HOST") {
+            Some(host) => host,
+            None => return Ok(None),
+        };
+
+        let tls = parse_var("FEEDER_SMTP_TLS", SmtpTls::StartTls)?;
+
+        Ok(Some(Self {
+            host,
+            port: parse_var("FEEDER_SMTP_PORT", tls.default_port())?,
+            tls,
+            username: optional_var("FEEDER_SMTP_USERNAME"),
+            password: optional_var("FEEDER_SMTP_PASSWORD"),
+            from: required_var("FEEDER_SMTP_FROM")?,
+            to: split_list(&required_var("FEEDER_SMTP_TO")?),
+            mode: parse_var("FEEDER_SMTP_MODE", DeliveryMode::Each)?,
+        }))
+    }
+}
+
+impl NtfyConfig {
+    /// ntfy is enabled by setting `FEEDER_NTFY_URL`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let url = match optional_var("FEEDER_NTFY_URL") {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            url,
+            token: optional_var("FEEDER_NTFY_TOKEN"),
+            tags: list_var("FEEDER_NTFY_TAGS"),
+            priority: priority_var("FEEDER_NTFY_PRIORITY")?,
+        }))
+    }
+}
+
+impl GotifyConfig {
+    /// Gotify is enabled by setting `FEEDER_GOTIFY_URL`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let url = match optional_var("FEEDER_GOTIFY_URL") {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            url,
+            token: required_var("FEEDER_GOTIFY_TOKEN")?,
+            priority: priority_var("FEEDER_GOTIFY_PRIORITY")?,
+        }))
+    }
+}
+
+impl TelegramConfig {
+    /// Telegram is enabled by setting `FEEDER_TELEGRAM_TOKEN`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let bot_token = match optional_var("FEEDER_TELEGRAM_TOKEN") {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            bot_token,
+            chat_id: required_var("FEEDER_TELEGRAM_CHAT")?,
+            disable_preview: parse_var("FEEDER_TELEGRAM_DISABLE_PREVIEW", false)?,
+            api_url: optional_var("FEEDER_TELEGRAM_API_URL")
+                .unwrap_or_else(|| "https://api.telegram.org".to_string()),
+        }))
+    }
+}
+
+impl ExecConfig {
+    /// The command hook is enabled by setting `FEEDER_EXEC_COMMAND`
+    fn from_env() -> FeederResult<Option<Self>> {
+        let command = match optional_var("FEEDER_EXEC_COMMAND") {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            command,
+            timeout_secs: parse_var("FEEDER_EXEC_TIMEOUT", 60)?,
+        }))
+    }
+}
+
+/// Read a 1-5 push priority, defaulting to 3
+fn priority_var(name: &str) -> FeederResult<u8> {
+    let priority = parse_var(name, 3u8)?;
+    if !(1..=5).contains(&priority) {
+        return Err(FeederError::Config(format!("{} must be between 1 and 5", name)));
+    }
+    Ok(priority)
+}
+
+/// Read a variable that must be set once its feature is enabled
+fn required_var(name: &str) -> FeederResult<String> {
+    optional_var(name).ok_or_else(|| FeederError::MissingEnvVar(name.to_string()))
+}
+
+/// Read a variable, treating empty values as unset
+fn optional_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Read and parse a variable, falling back to `default` when unset
+fn parse_var<T: std::str::FromStr>(name: &str, default: T) -> FeederResult<T> {
+    match optional_var(name) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| FeederError::Config(format!("Invalid value for {}: {}", name, value))),
+        None => Ok(default),
+    }
+}
+
+/// Read and parse a variable that has no default
+fn parse_optional_var<T: std::str::FromStr>(name: &str) -> FeederResult<Option<T>> {
+    optional_var(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| FeederError::Config(format!("Invalid value for {}: {}", name, value)))
         })
+        .transpose()
+}
+
+/// Image types attached to Notebrook messages, from `NOTEBROOK_ATTACH_TYPES`
+fn attach_types() -> Vec<String> {
+    let types = list_var("NOTEBROOK_ATTACH_TYPES");
+    if types.is_empty() {
+        return DEFAULT_ATTACH_TYPES.iter().map(|t| t.to_string()).collect();
     }
+    types.into_iter().map(|t| t.to_ascii_lowercase()).collect()
+}
+
+/// Read a comma-separated variable, empty when unset
+fn list_var(name: &str) -> Vec<String> {
+    optional_var(name)
+        .map(|value| split_list(&value))
+        .unwrap_or_default()
+}
+
+/// Split a comma-separated list, dropping empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// `X_API_KEY` -> `X-Api-Key`
+fn header_name_from_var(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let lower = part.to_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
 }
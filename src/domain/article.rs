@@ -5,8 +5,15 @@ pub struct Article {
     pub id: String,
     pub title: String,
     pub content: Option<String>,
+    /// The entry's text, kept in the history for search but left out of notifications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     pub links: Vec<String>,
     pub published: Option<String>,
+    pub author: Option<String>,
+    /// URL of the entry's image or thumbnail, when the feed names one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
 }
 
 impl Article {
@@ -15,13 +22,21 @@ impl Article {
             id,
             title,
             content: None,
+            summary: None,
             links: Vec::new(),
             published: None,
+            author: None,
+            image: None,
         }
     }
 
     pub fn cache_key(&self, feed_title: &str) -> String {
-        format!("{}:{}", feed_title, self.id)
+        format!("{}{}", Self::cache_key_prefix(feed_title), self.id)
+    }
+
+    /// What the cache keys of a feed's articles start with
+    pub fn cache_key_prefix(feed_title: &str) -> String {
+        format!("{}:", feed_title)
     }
 
     pub fn with_content(mut self, content: Option<String>) -> Self {
@@ -29,6 +44,11 @@ impl Article {
         self
     }
 
+    pub fn with_summary(mut self, summary: Option<String>) -> Self {
+        self.summary = summary;
+        self
+    }
+
     pub fn with_links(mut self, links: Vec<String>) -> Self {
         self.links = links;
         self
@@ -38,4 +58,14 @@ impl Article {
         self.published = published;
         self
     }
+
+    pub fn with_author(mut self, author: Option<String>) -> Self {
+        self.author = author;
+        self
+    }
+
+    pub fn with_image(mut self, image: Option<String>) -> Self {
+        self.image = image;
+        self
+    }
 }
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+use super::FeedSettings;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FeedType {
     Rss,
     Atom,
     Json,
+    Html,
 }
 
 impl FeedType {
@@ -14,6 +17,7 @@ impl FeedType {
             FeedType::Rss => "rss",
             FeedType::Atom => "atom",
             FeedType::Json => "json",
+            FeedType::Html => "html",
         }
     }
 }
@@ -26,6 +30,7 @@ impl std::str::FromStr for FeedType {
             "rss" => Ok(FeedType::Rss),
             "atom" => Ok(FeedType::Atom),
             "json" => Ok(FeedType::Json),
+            "html" => Ok(FeedType::Html),
             _ => Err(format!("Unknown feed type: {}", s)),
         }
     }
@@ -39,6 +44,7 @@ pub enum SourceType {
     Mastodon,
     WordPress,
     Blogger,
+    PageWatch,
 }
 
 impl SourceType {
@@ -49,6 +55,7 @@ impl SourceType {
             SourceType::Mastodon => "mastodon",
             SourceType::WordPress => "wordpress",
             SourceType::Blogger => "blogger",
+            SourceType::PageWatch => "page_watch",
         }
     }
 }
@@ -63,6 +70,7 @@ impl std::str::FromStr for SourceType {
             "mastodon" => Ok(SourceType::Mastodon),
             "wordpress" => Ok(SourceType::WordPress),
             "blogger" => Ok(SourceType::Blogger),
+            "page_watch" | "page" => Ok(SourceType::PageWatch),
             _ => Err(format!("Unknown source type: {}", s)),
         }
     }
@@ -74,7 +82,7 @@ impl std::fmt::Display for SourceType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Feed {
     pub id: Option<i64>,
     pub url: String,
@@ -83,6 +91,8 @@ pub struct Feed {
     pub feed_type: FeedType,
     pub source_type: SourceType,
     pub created_at: Option<String>,
+    #[serde(default)]
+    pub settings: FeedSettings,
 }
 
 impl Feed {
@@ -101,6 +111,17 @@ impl Feed {
             feed_type,
             source_type,
             created_at: None,
+            settings: FeedSettings::default(),
         }
     }
+
+    pub fn with_settings(mut self, settings: FeedSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// The title set with `feeder edit --title`, else the feed's own
+    pub fn display_title(&self) -> &str {
+        self.settings.title.as_deref().unwrap_or(&self.title)
+    }
 }
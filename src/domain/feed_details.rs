@@ -0,0 +1,130 @@
+use serde::Serialize;
+
+use crate::http::redact_url;
+
+use super::{Feed, FeedType, HistoryEntry, SourceType};
+
+/// Everything known about one feed, for `feeder show`
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedDetails {
+    pub id: i64,
+    /// The title shown in notifications, custom or the feed's own
+    pub title: String,
+    /// The feed's own title, when a custom one replaces it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_title: Option<String>,
+    pub url: String,
+    pub feed_url: String,
+    pub source_type: SourceType,
+    pub feed_type: FeedType,
+    pub created_at: Option<String>,
+    pub last_fetched_at: Option<String>,
+    pub status: FeedStatus,
+    /// Failed fetches since the last successful one
+    pub error_count: u32,
+    pub last_error: Option<String>,
+    pub notified_count: usize,
+    /// Most recently notified articles first
+    pub recent: Vec<HistoryEntry>,
+    pub settings: EffectiveSettings,
+}
+
+impl FeedDetails {
+    /// Details of `feed` with what isn't stored with it left empty. Its URLs are
+    /// shown without credentials.
+    pub fn new(id: i64, feed: &Feed, settings: EffectiveSettings) -> Self {
+        Self {
+            id,
+            title: feed.display_title().to_string(),
+            original_title: feed.settings.title.as_ref().map(|_| feed.title.clone()),
+            url: redact_url(&feed.url),
+            feed_url: redact_url(&feed.feed_url),
+            source_type: feed.source_type,
+            feed_type: feed.feed_type,
+            created_at: feed.created_at.clone(),
+            last_fetched_at: None,
+            status: FeedStatus::NeverFetched,
+            error_count: 0,
+            last_error: None,
+            notified_count: 0,
+            recent: Vec::new(),
+            settings,
+        }
+    }
+}
+
+/// How a feed's fetches have been going
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedStatus {
+    Ok,
+    /// The last fetch failed
+    Failing,
+    NeverFetched,
+}
+
+impl FeedStatus {
+    pub fn new(last_fetched_at: Option<&str>, error_count: u32) -> Self {
+        match (last_fetched_at, error_count) {
+            (_, 1..) => FeedStatus::Failing,
+            (Some(_), 0) => FeedStatus::Ok,
+            (None, 0) => FeedStatus::NeverFetched,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedStatus::Ok => "ok",
+            FeedStatus::Failing => "failing",
+            FeedStatus::NeverFetched => "never fetched",
+        }
+    }
+}
+
+/// Where a feed's articles go and how, with configured defaults filled in
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EffectiveSettings {
+    pub notebrook_channel: String,
+    /// Only set when Telegram is configured
+    pub telegram_chat: Option<String>,
+    /// Push priority, or `None` for each push sink's own default
+    pub priority: Option<u8>,
+    pub attach_image: bool,
+    pub selector: Option<String>,
+    /// Header names and credential kind sent with requests, never their values
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub request: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_status() {
+        assert_eq!(FeedStatus::new(None, 0), FeedStatus::NeverFetched);
+        assert_eq!(FeedStatus::new(Some("2024-01-01 00:00:00"), 0), FeedStatus::Ok);
+        assert_eq!(FeedStatus::new(Some("2024-01-01 00:00:00"), 2), FeedStatus::Failing);
+        assert_eq!(FeedStatus::new(None, 1), FeedStatus::Failing);
+    }
+
+    #[test]
+    fn test_original_title_only_when_overridden() {
+        let mut feed = Feed::new(
+            "https://example.com".to_string(),
+            "https://example.com/feed".to_string(),
+            "Example - Latest".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+
+        let details = FeedDetails::new(1, &feed, EffectiveSettings::default());
+        assert_eq!(details.title, "Example - Latest");
+        assert_eq!(details.original_title, None);
+
+        feed.settings.title = Some("Example".to_string());
+        let details = FeedDetails::new(1, &feed, EffectiveSettings::default());
+        assert_eq!(details.title, "Example");
+        assert_eq!(details.original_title.as_deref(), Some("Example - Latest"));
+    }
+}
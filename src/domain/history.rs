@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+/// A notified article kept for `feeder history` and `feeder search`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub feed_id: i64,
+    pub feed_title: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<String>,
+    pub author: Option<String>,
+    /// Start of the article's text, for previews and full-text search
+    pub snippet: Option<String>,
+    pub notified_at: String,
+}
@@ -1,7 +1,15 @@
 pub mod feed;
+pub mod feed_details;
 pub mod article;
 pub mod notification;
+pub mod settings;
+pub mod history;
+pub mod retention;
 
 pub use feed::{Feed, FeedType, SourceType};
+pub use feed_details::{EffectiveSettings, FeedDetails, FeedStatus};
 pub use article::Article;
-pub use notification::Notification;
+pub use notification::{Notification, PendingNotification};
+pub use history::HistoryEntry;
+pub use retention::RetentionPolicy;
+pub use settings::{FeedAuth, FeedSettings, RequestOptions};
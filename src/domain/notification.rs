@@ -1,11 +1,44 @@
+use serde::{Deserialize, Serialize};
+
 use super::{Article, Feed};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Notification {
     pub feed_title: String,
     pub article_title: String,
     pub text: String,
     pub links: Vec<String>,
+    pub published: Option<String>,
+    /// Article image to attach, only set for feeds that opted in
+    pub image: Option<String>,
+}
+
+/// A notification that one sink still has to be sent: one it failed to take, or
+/// one waiting for its digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingNotification {
+    /// `NotificationSink::id` of the sink
+    pub sink: String,
+    pub notification: Notification,
+    /// Not to be sent before this time (`YYYY-MM-DD HH:MM:SS`, UTC), as the sink
+    /// asked to be left alone until then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<String>,
+}
+
+impl PendingNotification {
+    pub fn new(sink: String, notification: Notification) -> Self {
+        Self {
+            sink,
+            notification,
+            not_before: None,
+        }
+    }
+
+    /// Whether it may be sent at `now` (`YYYY-MM-DD HH:MM:SS`, UTC)
+    pub fn is_due(&self, now: &str) -> bool {
+        self.not_before.as_deref().is_none_or(|not_before| not_before <= now)
+    }
 }
 
 impl Notification {
@@ -13,10 +46,12 @@ impl Notification {
         let text = article.content.clone().unwrap_or_default();
 
         Self {
-            feed_title: feed.title.clone(),
+            feed_title: feed.display_title().to_string(),
             article_title: article.title.clone(),
             text,
             links: article.links.clone(),
+            published: article.published.clone(),
+            image: article.image.clone().filter(|_| feed.settings.attach_image),
         }
     }
 
@@ -36,6 +71,19 @@ impl Notification {
 
         message
     }
+
+    /// A plain notification for tests to override fields of
+    #[cfg(test)]
+    pub(crate) fn test_default() -> Self {
+        Self {
+            feed_title: "Rust Blog".to_string(),
+            article_title: "Announcing Rust 1.75.0".to_string(),
+            text: String::new(),
+            links: vec!["https://blog.rust-lang.org/1.75".to_string()],
+            published: None,
+            image: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -50,6 +98,7 @@ mod tests {
             article_title: "New Rust Features".to_string(),
             text: "Rust 1.75 introduces async traits".to_string(),
             links: vec!["https://example.com/post".to_string()],
+            ..Notification::test_default()
         };
 
         let formatted = notification.format();
@@ -66,6 +115,7 @@ mod tests {
             article_title: "Title".to_string(),
             text: "Content".to_string(),
             links: vec![],
+            ..Notification::test_default()
         };
 
         let formatted = notification.format();
@@ -77,8 +127,8 @@ mod tests {
         let notification = Notification {
             feed_title: "Blog".to_string(),
             article_title: "Title".to_string(),
-            text: String::new(),
             links: vec!["https://example.com".to_string()],
+            ..Notification::test_default()
         };
 
         let formatted = notification.format();
@@ -97,7 +147,8 @@ mod tests {
 
         let article = Article::new("123".to_string(), "Test Article".to_string())
             .with_content(Some("Article content".to_string()))
-            .with_links(vec!["https://example.com/article".to_string()]);
+            .with_links(vec!["https://example.com/article".to_string()])
+            .with_published(Some("2024-01-15T12:00:00+00:00".to_string()));
 
         let notification = Notification::from_article(&feed, &article);
 
@@ -105,5 +156,42 @@ mod tests {
         assert_eq!(notification.article_title, "Test Article");
         assert_eq!(notification.text, "Article content");
         assert_eq!(notification.links, vec!["https://example.com/article"]);
+        assert_eq!(notification.published.as_deref(), Some("2024-01-15T12:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_notification_image_only_when_feed_opts_in() {
+        let mut feed = Feed::new(
+            "https://example.com/feed".to_string(),
+            "https://example.com/feed".to_string(),
+            "Photos".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let article = Article::new("1".to_string(), "Sunset".to_string())
+            .with_image(Some("https://example.com/sunset.jpg".to_string()));
+
+        assert_eq!(Notification::from_article(&feed, &article).image, None);
+
+        feed.settings.attach_image = true;
+        assert_eq!(
+            Notification::from_article(&feed, &article).image.as_deref(),
+            Some("https://example.com/sunset.jpg")
+        );
+    }
+
+    #[test]
+    fn test_notification_uses_custom_title() {
+        let mut feed = Feed::new(
+            "https://example.com/feed".to_string(),
+            "https://example.com/feed".to_string(),
+            "example.com - Latest Posts".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        feed.settings.title = Some("Example".to_string());
+        let article = Article::new("1".to_string(), "Hello".to_string());
+
+        assert_eq!(Notification::from_article(&feed, &article).feed_title, "Example");
     }
 }
@@ -0,0 +1,19 @@
+/// How long entries stay in the notification cache.
+///
+/// An entry is kept while it meets either limit, so with both set it must be older
+/// than `days` *and* beyond the newest `keep_per_feed` of its feed to be pruned.
+/// Entries still present in a feed are kept regardless.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Keep entries notified within this many days
+    pub days: Option<u32>,
+    /// Keep this many of the most recently notified entries of each feed
+    pub keep_per_feed: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// Whether no limit is set, so nothing may be pruned
+    pub fn is_unbounded(&self) -> bool {
+        self.days.is_none() && self.keep_per_feed.is_none()
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Per-feed options chosen by the user, persisted alongside the feed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedSettings {
+    /// Title to show in place of the one the feed gives itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// CSS selector narrowing a watched page to the part worth tracking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+
+    /// Push priority from 1 (min) to 5 (max) for sinks that support it, like ntfy and Gotify
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+
+    /// Telegram chat ID to send this feed's articles to instead of the default chat
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram_chat: Option<String>,
+
+    /// Attach each article's image or thumbnail to its Notebrook message
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub attach_image: bool,
+
+    /// Headers and credentials sent whenever this feed is fetched
+    #[serde(skip_serializing_if = "RequestOptions::is_empty")]
+    pub request: RequestOptions,
+}
+
+/// Extra headers and credentials for a feed's HTTP requests.
+///
+/// `Debug` shows header names and the kind of credentials only, so values never end
+/// up in logs or error output.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestOptions {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<FeedAuth>,
+}
+
+impl RequestOptions {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.auth.is_none()
+    }
+
+    /// Header names and the kind of credentials, without any values
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.headers.is_empty() {
+            let names: Vec<&str> = self.headers.keys().map(String::as_str).collect();
+            parts.push(format!("headers {}", names.join(", ")));
+        }
+        if let Some(auth) = &self.auth {
+            parts.push(format!("{} auth", auth.kind()));
+        }
+        parts.join("; ")
+    }
+}
+
+impl fmt::Debug for RequestOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestOptions")
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("auth", &self.auth.as_ref().map(FeedAuth::kind))
+            .finish()
+    }
+}
+
+/// Credentials sent with a feed's requests; `Debug` shows only the kind
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FeedAuth {
+    /// HTTP Basic authentication
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// Raw `Cookie` header, e.g. a session cookie
+    Cookie { cookie: String },
+}
+
+impl fmt::Debug for FeedAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FeedAuth({})", self.kind())
+    }
+}
+
+impl FeedAuth {
+    /// Name of the scheme, safe to display
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FeedAuth::Basic { .. } => "basic",
+            FeedAuth::Bearer { .. } => "bearer",
+            FeedAuth::Cookie { .. } => "cookie",
+        }
+    }
+}
+
+impl FeedSettings {
+    /// Serialize for storage; unknown or missing fields fall back to defaults on load
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Load stored settings. Corrupt JSON is an error rather than silently losing
+    /// the feed's settings.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let settings = FeedSettings {
+            title: Some("Status".to_string()),
+            selector: Some("#status".to_string()),
+            priority: Some(5),
+            telegram_chat: Some("-100123".to_string()),
+            attach_image: true,
+            request: RequestOptions {
+                headers: BTreeMap::from([("X-Api-Key".to_string(), "k".to_string())]),
+                auth: Some(FeedAuth::Basic {
+                    username: "me".to_string(),
+                    password: Some("secret".to_string()),
+                }),
+            },
+        };
+
+        let json = settings.to_json();
+        assert_eq!(FeedSettings::from_json(&json).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_request_options_debug_hides_secrets() {
+        let request = RequestOptions {
+            headers: BTreeMap::from([("X-Api-Key".to_string(), "hunter2".to_string())]),
+            auth: Some(FeedAuth::Bearer {
+                token: "t0ken".to_string(),
+            }),
+        };
+
+        let debug = format!("{:?}", request);
+        assert!(debug.contains("X-Api-Key") && debug.contains("bearer"));
+        assert!(!debug.contains("hunter2") && !debug.contains("t0ken"));
+
+        assert_eq!(request.summary(), "headers X-Api-Key; bearer auth");
+    }
+
+    #[test]
+    fn test_settings_invalid_json_rejected() {
+        assert!(FeedSettings::from_json("not json").is_err());
+        assert_eq!(FeedSettings::from_json("{}").unwrap(), FeedSettings::default());
+    }
+}
@@ -40,6 +40,19 @@ pub enum FeederError {
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[cfg(feature = "postgres")]
+    #[error("Database error: {0}")]
+    Postgres(#[from] postgres::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Database check failed: {0}")]
+    DatabaseCheck(String),
+
+    #[error("Another feeder run is in progress: {0}")]
+    RunInProgress(String),
+
     // Notification errors
     #[error("Notification failed: {0}")]
     Notification(String),
@@ -58,15 +71,92 @@ pub enum FeederError {
 
     #[error("Payload too large")]
     PayloadTooLarge,
+
+    // Non-2xx responses from notification sinks
+    #[error("{sink} returned HTTP {status}")]
+    SinkStatus {
+        sink: String,
+        status: u16,
+        /// Seconds the server asked us to wait before retrying
+        retry_after: Option<u64>,
+    },
+
+    // Temporary failures from non-HTTP sinks (SMTP 4xx, timeouts)
+    #[error("{sink} temporarily failed: {message}")]
+    SinkTransient { sink: String, message: String },
+
+    // Sinks that delivered the message but not all of it, like an image attachment
+    #[error("{sink} sent the message, but {message}")]
+    PartlyDelivered { sink: String, message: String },
+}
+
+impl FeederError {
+    /// Whether sending again later might succeed (rate limits, server errors, timeouts)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FeederError::SinkStatus { status, .. } => *status == 429 || *status >= 500,
+            FeederError::SinkTransient { .. } => true,
+            FeederError::Http(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Delay requested by the server, if any
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            FeederError::SinkStatus { retry_after, .. } => {
+                retry_after.map(std::time::Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl From<channels::ChannelError> for FeederError {
     fn from(err: channels::ChannelError) -> Self {
+        use channels::ChannelError;
+
+        let status = |status: u16, retry_after: Option<u64>| FeederError::SinkStatus {
+            sink: "notebrook".to_string(),
+            status,
+            retry_after,
+        };
+
         match err {
-            channels::ChannelError::PayloadTooLarge => FeederError::PayloadTooLarge,
+            ChannelError::PayloadTooLarge => FeederError::PayloadTooLarge,
+            ChannelError::RateLimited { retry_after } => status(429, retry_after),
+            ChannelError::UnexpectedStatus(code) => status(code, None),
+            ChannelError::Unauthorized => status(401, None),
+            ChannelError::Forbidden(_) => status(403, None),
+            ChannelError::RequestError(e) if e.is_timeout() || e.is_connect() => {
+                FeederError::SinkTransient {
+                    sink: "notebrook".to_string(),
+                    message: e.to_string(),
+                }
+            }
             _ => FeederError::Channel(err.to_string()),
         }
     }
 }
 
 pub type FeederResult<T> = Result<T, FeederError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use channels::ChannelError;
+
+    #[test]
+    fn test_channel_errors_keep_retry_information() {
+        let err = FeederError::from(ChannelError::RateLimited { retry_after: Some(5) });
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(5)));
+
+        assert!(FeederError::from(ChannelError::UnexpectedStatus(503)).is_retryable());
+        assert!(!FeederError::from(ChannelError::Unauthorized).is_retryable());
+        assert!(matches!(
+            FeederError::from(ChannelError::PayloadTooLarge),
+            FeederError::PayloadTooLarge
+        ));
+    }
+}
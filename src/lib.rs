@@ -2,6 +2,8 @@ pub mod cli;
 pub mod config;
 pub mod domain;
 pub mod errors;
+pub mod http;
 pub mod services;
+pub mod sinks;
 pub mod sources;
 pub mod storage;
@@ -3,15 +3,32 @@ use std::fs;
 
 use clap::Parser;
 
-use feeder::cli::{Cli, Commands};
+use feeder::cli::{Cli, Commands, DbCommand, EditArgs, RequestArgs};
 use feeder::config::Config;
+use feeder::domain::{
+    EffectiveSettings, Feed, FeedDetails, FeedStatus, HistoryEntry, Notification,
+    PendingNotification, RequestOptions, RetentionPolicy,
+};
 use feeder::errors::{FeederError, FeederResult};
-use feeder::services::{FeedService, FetchService, ImportExportService, NotificationService};
+use feeder::http::{redact_url, HttpClient};
+use feeder::services::{
+    FeedService, FetchResult, FetchService, ImportExportService, ImportMode, NotificationService,
+    SinkFailure,
+};
+use feeder::sinks::{DeliveryMode, Route};
 use feeder::sources::SourceRegistry;
-use feeder::storage::sqlite::{
-    SqliteArticleCacheRepository, SqliteFeedRepository, SqliteStorage,
+use feeder::storage::traits::{ERROR_COUNT_KEY, FETCHED_AT_KEY, LAST_ERROR_KEY};
+use feeder::storage::{
+    self, ArticleCacheRepository, ArticleHistoryRepository, FeedRepository, FeedStateRepository,
+    Storage,
 };
 
+type BoxedFetchService = FetchService<
+    Box<dyn FeedRepository>,
+    Box<dyn ArticleCacheRepository>,
+    Box<dyn FeedStateRepository>,
+>;
+
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {}", e);
@@ -26,35 +43,162 @@ fn run() -> FeederResult<()> {
     let config = Config::from_env()?;
 
     // Initialize storage
-    let storage = SqliteStorage::new(&config.db_path)?;
-    let feed_repo = SqliteFeedRepository::new(storage.clone());
-    let cache_repo = SqliteArticleCacheRepository::new(storage);
+    let storage = storage::open(config.storage, &config.db_path)?;
+    let feed_repo = storage.feeds();
+    let cache_repo = storage.article_cache();
+    let state_repo = storage.feed_state();
+    let history_repo = storage.history();
 
     // Initialize source registry
-    let source_registry = SourceRegistry::new();
+    let source_registry = SourceRegistry::with_http(HttpClient::new(&config.http)?);
 
     match cli.command {
-        Commands::Add { url } => cmd_add(&url, feed_repo, source_registry),
+        Commands::Add {
+            url,
+            watch,
+            selector,
+            pick,
+            request,
+        } => cmd_add(&url, watch, selector, pick, &request, feed_repo, source_registry),
         Commands::Remove => cmd_remove(feed_repo),
         Commands::List => cmd_list(feed_repo),
-        Commands::Import { path } => cmd_import(&path, feed_repo, source_registry),
+        Commands::Edit(args) => cmd_edit(args, feed_repo),
+        Commands::Show { feed, json } => {
+            cmd_show(&feed, json, &config, feed_repo, &state_repo, &history_repo)
+        }
+        Commands::Import { path, atomic } => {
+            let mode = if atomic { ImportMode::Atomic } else { ImportMode::BestEffort };
+            cmd_import(&path, mode, feed_repo, source_registry)
+        }
         Commands::Export { output } => cmd_export(feed_repo, source_registry, output),
         Commands::Run { dry_run, skip_notify } => {
-            cmd_run(feed_repo, cache_repo, source_registry, &config, dry_run, skip_notify)
+            let _lock = storage.lock_run()?;
+            let fetch_service =
+                FetchService::new(feed_repo, cache_repo, state_repo, source_registry)
+                    .with_dry_run(dry_run);
+            cmd_run(fetch_service, &config, dry_run, skip_notify)
+        }
+        Commands::Refresh { feed } => {
+            let _lock = storage.lock_run()?;
+            let feeds = match feed {
+                Some(selector) => {
+                    let service = FeedService::new(storage.feeds(), SourceRegistry::new());
+                    vec![service.find(&selector)?]
+                }
+                None => feed_repo.get_all()?,
+            };
+            let fetch_service =
+                FetchService::new(feed_repo, cache_repo, state_repo, source_registry);
+            cmd_refresh(fetch_service, feeds)
+        }
+        Commands::Search {
+            query,
+            feed,
+            since,
+            limit,
+            json,
+        } => {
+            let feed_id = find_feed_id(feed.as_deref(), feed_repo)?;
+            let entries = history_repo.search(&query, feed_id, since, limit)?;
+            print_history(&entries, json, "No matching articles.")
         }
+        Commands::History { feed, limit, json } => {
+            let feed_id = find_feed_id(feed.as_deref(), feed_repo)?;
+            let entries = history_repo.recent(feed_id, limit)?;
+            print_history(&entries, json, "No articles notified yet.")
+        }
+        Commands::Db { command } => match command {
+            DbCommand::Prune { days, keep } => {
+                let limits = RetentionPolicy {
+                    days,
+                    keep_per_feed: keep,
+                };
+                let policy = if limits.is_unbounded() { config.retention } else { limits };
+                let _lock = storage.lock_run()?;
+                cmd_db_prune(&policy, &cache_repo, storage.as_ref())
+            }
+            DbCommand::Backup { path } => {
+                storage.backup(path.as_ref())?;
+                println!("Backed up {} to {}", config.db_path, path);
+                Ok(())
+            }
+            DbCommand::Restore { path } => {
+                let _lock = storage.lock_run()?;
+                let version = storage.restore(path.as_ref())?;
+                println!("Restored {} from {} (schema version {})", config.db_path, path, version);
+                Ok(())
+            }
+            DbCommand::Check => cmd_db_check(storage.as_ref()),
+        },
     }
 }
 
 fn cmd_add(
     url: &str,
-    feed_repo: SqliteFeedRepository,
+    watch: bool,
+    selector: Option<String>,
+    pick: Option<usize>,
+    request_args: &RequestArgs,
+    feed_repo: Box<dyn FeedRepository>,
     source_registry: SourceRegistry,
 ) -> FeederResult<()> {
     let service = FeedService::new(feed_repo, source_registry);
+    let mut request = RequestOptions::default();
+    request_args.apply(&mut request)?;
 
-    println!("Validating feed: {}", url);
+    let result = if watch {
+        println!("Validating page: {}", url);
+        service.add_page_watch(url, selector, request)
+    } else {
+        println!("Validating feed: {}", url);
+
+        // Nothing to choose from means the source maps the URL to one feed itself
+        let candidates = service.discover(url, &request)?;
+
+        match candidates.len() {
+            0 if pick.is_some() => {
+                return Err(FeederError::InvalidInput(format!(
+                    "No feeds found at {} to pick from",
+                    url
+                )))
+            }
+            0 => service.add(url, request),
+            1 if pick.is_none() => service.add_discovered(url, &candidates[0], request),
+            _ => {
+                let index = match pick {
+                    Some(index) => index,
+                    None => {
+                        println!("Found {} feeds:\n", candidates.len());
+                        for (i, candidate) in candidates.iter().enumerate() {
+                            println!(
+                                "  {}. {} [{}] ({})",
+                                i + 1,
+                                candidate.title.as_deref().unwrap_or("Untitled"),
+                                candidate.feed_type.as_str(),
+                                candidate.url
+                            );
+                        }
+                        println!();
 
-    match service.add(url) {
+                        match prompt_selection("Enter number to add")? {
+                            Some(index) => index,
+                            None => return Ok(()),
+                        }
+                    }
+                };
+
+                if index == 0 || index > candidates.len() {
+                    return Err(FeederError::InvalidInput(
+                        "Number out of range".to_string(),
+                    ));
+                }
+
+                service.add_discovered(url, &candidates[index - 1], request)
+            }
+        }
+    };
+
+    match result {
         Ok(feed) => {
             println!("Feed added successfully!");
             println!("  Title: {}", feed.title);
@@ -70,7 +214,7 @@ fn cmd_add(
     }
 }
 
-fn cmd_remove(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
+fn cmd_remove(feed_repo: Box<dyn FeedRepository>) -> FeederResult<()> {
     let service = FeedService::new(feed_repo, SourceRegistry::new());
     let feeds = service.list()?;
 
@@ -85,29 +229,17 @@ fn cmd_remove(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
         println!(
             "  {}. {} [{}] ({})",
             i + 1,
-            feed.title,
+            feed.display_title(),
             feed.source_type,
             feed.url
         );
     }
     println!();
 
-    // Read user input
-    print!("Enter number (or 'q' to cancel): ");
-    io::stdout().flush()?;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let input = input.trim();
-
-    if input.eq_ignore_ascii_case("q") {
-        println!("Cancelled.");
-        return Ok(());
-    }
-
-    let index: usize = input
-        .parse()
-        .map_err(|_| FeederError::InvalidInput("Invalid number".to_string()))?;
+    let index = match prompt_selection("Enter number")? {
+        Some(index) => index,
+        None => return Ok(()),
+    };
 
     if index == 0 || index > feeds.len() {
         return Err(FeederError::InvalidInput(
@@ -121,12 +253,32 @@ fn cmd_remove(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
     })?;
 
     service.remove(feed_id)?;
-    println!("Removed: {}", feed.title);
+    println!("Removed: {}", feed.display_title());
 
     Ok(())
 }
 
-fn cmd_list(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
+/// Ask for a 1-based number from a list shown above; `None` when the user cancels
+fn prompt_selection(prompt: &str) -> FeederResult<Option<usize>> {
+    print!("{} (or 'q' to cancel): ", prompt);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+
+    if input.eq_ignore_ascii_case("q") {
+        println!("Cancelled.");
+        return Ok(None);
+    }
+
+    input
+        .parse()
+        .map(Some)
+        .map_err(|_| FeederError::InvalidInput("Invalid number".to_string()))
+}
+
+fn cmd_list(feed_repo: Box<dyn FeedRepository>) -> FeederResult<()> {
     let service = FeedService::new(feed_repo, SourceRegistry::new());
     let feeds = service.list()?;
 
@@ -137,10 +289,17 @@ fn cmd_list(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
 
     println!("Configured feeds:\n");
     for feed in feeds {
-        println!("  {} [{}]", feed.title, feed.source_type);
-        println!("    URL: {}", feed.url);
+        println!("  {} [{}]", feed.display_title(), feed.source_type);
+        if feed.settings.title.is_some() {
+            println!("    Original title: {}", feed.title);
+        }
+        println!("    URL: {}", redact_url(&feed.url));
         if feed.url != feed.feed_url {
-            println!("    Feed: {}", feed.feed_url);
+            println!("    Feed: {}", redact_url(&feed.feed_url));
+        }
+        // Names only: header values and credentials are secrets
+        if !feed.settings.request.is_empty() {
+            println!("    Request: {}", feed.settings.request.summary());
         }
         println!();
     }
@@ -148,9 +307,285 @@ fn cmd_list(feed_repo: SqliteFeedRepository) -> FeederResult<()> {
     Ok(())
 }
 
+fn cmd_edit(args: EditArgs, feed_repo: Box<dyn FeedRepository>) -> FeederResult<()> {
+    let service = FeedService::new(feed_repo, SourceRegistry::new());
+    let mut feed = service.find(&args.feed)?;
+
+    if !args.has_changes() {
+        return Err(FeederError::InvalidInput(
+            "Nothing to change, see 'feeder edit --help'".to_string(),
+        ));
+    }
+
+    if let Some(title) = args.title {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(FeederError::InvalidInput(
+                "Title can't be empty, use --no-title to show the feed's own".to_string(),
+            ));
+        }
+        println!("Title: {}", title);
+        feed.settings.title = Some(title.to_string());
+    }
+
+    if args.no_title {
+        feed.settings.title = None;
+        println!("Title: {} (from the feed)", feed.title);
+    }
+
+    if let Some(priority) = args.priority {
+        feed.settings.priority = Some(priority);
+        println!("Priority: {}", priority);
+    }
+
+    if let Some(chat) = args.telegram_chat {
+        println!("Telegram chat: {}", chat);
+        feed.settings.telegram_chat = Some(chat);
+    }
+
+    if let Some(attach) = args.attach_image {
+        feed.settings.attach_image = attach;
+        println!("Attach images: {}", if attach { "yes" } else { "no" });
+    }
+
+    let request = &mut feed.settings.request;
+    for name in &args.remove_header {
+        let before = request.headers.len();
+        request.headers.retain(|header, _| !header.eq_ignore_ascii_case(name));
+        if request.headers.len() == before {
+            return Err(FeederError::InvalidInput(format!("No header named '{}'", name)));
+        }
+    }
+
+    if args.no_auth {
+        request.auth = None;
+    }
+
+    if !args.request.is_empty() || !args.remove_header.is_empty() || args.no_auth {
+        args.request.apply(request)?;
+        // Names only: header values and credentials are secrets
+        let summary = request.summary();
+        println!("Request: {}", if summary.is_empty() { "defaults" } else { &summary });
+    }
+
+    service.update(&feed)?;
+    println!("Updated: {}", feed.display_title());
+
+    Ok(())
+}
+
+fn cmd_show(
+    selector: &str,
+    json: bool,
+    config: &Config,
+    feed_repo: Box<dyn FeedRepository>,
+    state_repo: &dyn FeedStateRepository,
+    history_repo: &dyn ArticleHistoryRepository,
+) -> FeederResult<()> {
+    let service = FeedService::new(feed_repo, SourceRegistry::new());
+    let feed = service.find(selector)?;
+    let feed_id = feed
+        .id
+        .ok_or_else(|| FeederError::FeedNotFound("Feed has no ID".to_string()))?;
+
+    let settings = EffectiveSettings {
+        notebrook_channel: config.notebrook_channel.clone(),
+        telegram_chat: config.telegram.as_ref().map(|telegram| {
+            feed.settings
+                .telegram_chat
+                .clone()
+                .unwrap_or_else(|| telegram.chat_id.clone())
+        }),
+        priority: feed.settings.priority,
+        attach_image: feed.settings.attach_image,
+        selector: feed.settings.selector.clone(),
+        request: feed.settings.request.summary(),
+    };
+
+    let mut details = FeedDetails::new(feed_id, &feed, settings);
+    details.last_fetched_at = state_repo.get(feed_id, FETCHED_AT_KEY)?;
+    details.error_count = state_repo
+        .get(feed_id, ERROR_COUNT_KEY)?
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+    details.status = FeedStatus::new(details.last_fetched_at.as_deref(), details.error_count);
+    details.last_error = state_repo
+        .get(feed_id, LAST_ERROR_KEY)?
+        .filter(|error| !error.is_empty());
+    details.notified_count = history_repo.count(Some(feed_id))?;
+    details.recent = history_repo.recent(Some(feed_id), 10)?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&details)
+            .map_err(|e| FeederError::InvalidInput(e.to_string()))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!("{}", details.title);
+    println!("  ID: {}", details.id);
+    if let Some(title) = &details.original_title {
+        println!("  Original title: {}", title);
+    }
+    println!("  URL: {}", redact_url(&details.url));
+    println!("  Feed: {}", redact_url(&details.feed_url));
+    println!("  Source: {}", details.source_type);
+    println!("  Type: {}", details.feed_type.as_str());
+    println!("  Added: {}", details.created_at.as_deref().unwrap_or("unknown"));
+    println!(
+        "  Last fetched: {}",
+        details.last_fetched_at.as_deref().unwrap_or("never")
+    );
+    match details.status {
+        FeedStatus::Failing => println!(
+            "  Status: failing, {} errors since the last success",
+            details.error_count
+        ),
+        status => println!("  Status: {}", status.as_str()),
+    }
+    if let Some(error) = &details.last_error {
+        println!("  Last error: {}", error);
+    }
+    println!("  Notified articles: {}", details.notified_count);
+
+    let settings = &details.settings;
+    println!("\nSettings:");
+    println!("  Notebrook channel: {}", settings.notebrook_channel);
+    if let Some(chat) = &settings.telegram_chat {
+        println!("  Telegram chat: {}", chat);
+    }
+    match settings.priority {
+        Some(priority) => println!("  Priority: {}", priority),
+        None => println!("  Priority: sink default"),
+    }
+    println!("  Attach images: {}", if settings.attach_image { "yes" } else { "no" });
+    if let Some(selector) = &settings.selector {
+        println!("  Selector: {}", selector);
+    }
+    // Names only: header values and credentials are secrets
+    if !settings.request.is_empty() {
+        println!("  Request: {}", settings.request);
+    }
+
+    println!("\nRecent articles:");
+    if details.recent.is_empty() {
+        println!("  None notified yet.");
+    }
+    for entry in &details.recent {
+        println!("  {}  {}", entry.notified_at, entry.title);
+    }
+
+    Ok(())
+}
+
+/// ID of the feed picked by `--feed`, if one was given
+fn find_feed_id(selector: Option<&str>, feed_repo: Box<dyn FeedRepository>) -> FeederResult<Option<i64>> {
+    let Some(selector) = selector else {
+        return Ok(None);
+    };
+
+    let service = FeedService::new(feed_repo, SourceRegistry::new());
+    Ok(service.find(selector)?.id)
+}
+
+fn print_history(entries: &[HistoryEntry], json: bool, empty_message: &str) -> FeederResult<()> {
+    if json {
+        let output = serde_json::to_string_pretty(entries)
+            .map_err(|e| FeederError::InvalidInput(e.to_string()))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{}", empty_message);
+        return Ok(());
+    }
+
+    for entry in entries {
+        println!("{}  {}: {}", entry.notified_at, entry.feed_title, entry.title);
+        if let Some(link) = &entry.link {
+            println!("    {}", link);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_db_prune(
+    policy: &RetentionPolicy,
+    cache_repo: &dyn ArticleCacheRepository,
+    storage: &dyn Storage,
+) -> FeederResult<()> {
+    if policy.is_unbounded() {
+        return Err(FeederError::InvalidInput(
+            "No retention limit, pass --days or --keep or set FEEDER_RETENTION_DAYS or FEEDER_RETENTION_KEEP"
+                .to_string(),
+        ));
+    }
+
+    let removed = cache_repo.prune(policy)?;
+    println!("Removed {} cache entries.", removed);
+
+    let reclaimed = storage.compact()?;
+    println!("Reclaimed {}.", format_bytes(reclaimed));
+
+    Ok(())
+}
+
+fn cmd_db_check(storage: &dyn Storage) -> FeederResult<()> {
+    let report = storage.check()?;
+
+    if report.integrity_errors.is_empty() {
+        println!("Integrity: ok");
+    } else {
+        println!("Integrity: {} problems", report.integrity_errors.len());
+        for message in &report.integrity_errors {
+            println!("  {}", message);
+        }
+    }
+
+    if report.foreign_key_violations.is_empty() {
+        println!("Foreign keys: ok");
+    } else {
+        println!("Foreign keys: {} violations", report.foreign_key_violations.len());
+        for violation in &report.foreign_key_violations {
+            let row = violation.rowid.map(|id| id.to_string()).unwrap_or_else(|| "?".to_string());
+            println!("  {} row {} references a missing {} row", violation.table, row, violation.parent);
+        }
+    }
+
+    println!("Orphaned cache entries: {}", report.orphaned_notifications);
+
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(FeederError::DatabaseCheck(
+            "problems found, see above; 'feeder db restore' replaces the database with a backup"
+                .to_string(),
+        ))
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB"];
+
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 fn cmd_import(
     path: &str,
-    feed_repo: SqliteFeedRepository,
+    mode: ImportMode,
+    feed_repo: Box<dyn FeedRepository>,
     source_registry: SourceRegistry,
 ) -> FeederResult<()> {
     let content = fs::read_to_string(path)?;
@@ -158,7 +593,7 @@ fn cmd_import(
 
     println!("Importing feeds from {}...\n", path);
 
-    let result = service.import_opml(&content)?;
+    let result = service.import_opml(&content, mode)?;
 
     if !result.added.is_empty() {
         println!("Added {} feeds:", result.added.len());
@@ -182,6 +617,13 @@ fn cmd_import(
             println!("  ! {}: {}", url, error);
         }
         println!();
+
+        if mode == ImportMode::Atomic {
+            return Err(FeederError::InvalidInput(format!(
+                "{} feeds failed, nothing was imported",
+                result.invalid.len()
+            )));
+        }
     }
 
     println!(
@@ -195,7 +637,7 @@ fn cmd_import(
 }
 
 fn cmd_export(
-    feed_repo: SqliteFeedRepository,
+    feed_repo: Box<dyn FeedRepository>,
     source_registry: SourceRegistry,
     output: Option<String>,
 ) -> FeederResult<()> {
@@ -215,16 +657,44 @@ fn cmd_export(
     Ok(())
 }
 
+fn cmd_refresh(fetch_service: BoxedFetchService, feeds: Vec<Feed>) -> FeederResult<()> {
+    if feeds.is_empty() {
+        println!("No feeds configured.");
+        return Ok(());
+    }
+
+    let mut error_count = 0;
+    for (feed, result) in fetch_service.refresh(feeds) {
+        match result {
+            Ok(update) if update.is_empty() => println!("  {}: up to date", feed.display_title()),
+            Ok(update) => {
+                for change in update.describe() {
+                    println!("  {}: {}", feed.display_title(), change);
+                }
+            }
+            Err(e) => {
+                println!("  {}: error: {}", feed.display_title(), e);
+                error_count += 1;
+            }
+        }
+    }
+
+    if error_count > 0 {
+        return Err(FeederError::FeedValidation(format!(
+            "{} feeds could not be refreshed",
+            error_count
+        )));
+    }
+
+    Ok(())
+}
+
 fn cmd_run(
-    feed_repo: SqliteFeedRepository,
-    cache_repo: SqliteArticleCacheRepository,
-    source_registry: SourceRegistry,
+    fetch_service: BoxedFetchService,
     config: &Config,
     dry_run: bool,
     skip_notify: bool,
 ) -> FeederResult<()> {
-    let fetch_service = FetchService::new(feed_repo, cache_repo, source_registry);
-
     if skip_notify {
         println!("Fetching feeds (skip-notify mode)...\n");
     } else {
@@ -244,17 +714,21 @@ fn cmd_run(
     let mut feeds_with_new = 0;
 
     for result in &results {
+        for change in result.update.describe() {
+            println!("  {}: {}", result.feed.display_title(), change);
+        }
+
         if result.is_error() {
             println!(
                 "  {}: error: {}",
-                result.feed.title,
+                result.feed.display_title(),
                 result.error.as_ref().unwrap()
             );
             error_count += 1;
         } else if result.has_new_articles() {
             println!(
                 "  {}: fetched {} articles, {} new",
-                result.feed.title,
+                result.feed.display_title(),
                 result.total_articles,
                 result.new_articles.len()
             );
@@ -263,13 +737,20 @@ fn cmd_run(
         } else {
             println!(
                 "  {}: fetched {} articles, 0 new",
-                result.feed.title, result.total_articles
+                result.feed.display_title(),
+                result.total_articles
             );
         }
     }
 
     println!();
 
+    let notification_service = if !dry_run && !skip_notify {
+        Some(NotificationService::new(config)?)
+    } else {
+        None
+    };
+
     // Summary line
     if error_count > 0 {
         println!(
@@ -280,15 +761,27 @@ fn cmd_run(
         println!("Found {} new articles from {} feeds.\n", total_new, feeds_with_new);
     } else {
         println!("No new articles to notify.");
+
+        // Notifications kept pending by earlier runs go out all the same
+        if let Some(service) = &notification_service {
+            retry_pending(&fetch_service, service, &results)?;
+            send_digests(&fetch_service, service, &results)?;
+        }
         return Ok(());
     }
 
-    // Process notifications
-    let notification_service = if !dry_run && !skip_notify {
-        Some(NotificationService::new(config)?)
-    } else {
-        None
-    };
+    // Process notifications. Sinks that failed to take notifications in earlier runs
+    // get them first.
+    if let Some(service) = &notification_service {
+        retry_pending(&fetch_service, service, &results)?;
+    }
+
+    // Digest sinks get one message at the end. Articles are kept pending for them as
+    // they are marked, so a failed digest is retried without sending them one by one again.
+    let digest_sinks = notification_service
+        .as_ref()
+        .map(|s| s.sink_ids(DeliveryMode::Digest))
+        .unwrap_or_default();
 
     let mut total_notified = 0;
 
@@ -299,14 +792,16 @@ fn cmd_run(
 
         let feed = &result.feed;
         let articles = &result.new_articles;
+        let route = Route::for_feed(feed);
 
-        println!("{} ({} new articles):", feed.title, articles.len());
+        println!("{} ({} new articles):", feed.display_title(), articles.len());
 
-        // Track which articles were successfully notified
+        // Track which articles were successfully notified, and what sinks still need
         let mut notified_articles = Vec::new();
+        let mut pending = Vec::new();
 
         for article in articles {
-            let notification = feeder::domain::Notification::from_article(feed, article);
+            let notification = Notification::from_article(feed, article);
 
             if dry_run {
                 println!("  [DRY RUN] {}", notification.format());
@@ -315,18 +810,42 @@ fn cmd_run(
                 total_notified += 1;
                 notified_articles.push(article.clone());
             } else {
+                // Claimed before sending, so no other run can send it too
+                if !fetch_service.claim(feed, article)? {
+                    println!("  [SENT ELSEWHERE] {}", notification.article_title);
+                    continue;
+                }
+
                 print!("  Sending: {}... ", notification.article_title);
                 io::stdout().flush()?;
 
-                match notification_service.as_ref().unwrap().send(&notification) {
-                    Ok(()) => {
+                match notification_service.as_ref().unwrap().send(&notification, &route) {
+                    Ok(failed) => {
                         println!("OK");
+                        for failure in failed {
+                            match failure.pending(&notification) {
+                                Some(kept) => {
+                                    println!(
+                                        "    not delivered: {} (retried next run)",
+                                        failure.error
+                                    );
+                                    pending.push(kept);
+                                }
+                                None if failure.is_partial() => println!("    {}", failure.error),
+                                None => println!("    not delivered: {}", failure.error),
+                            }
+                        }
+                        for sink in &digest_sinks {
+                            let queued = notification.clone();
+                            pending.push(PendingNotification::new(sink.clone(), queued));
+                        }
                         total_notified += 1;
                         notified_articles.push(article.clone());
                     }
                     Err(e) => {
                         println!("FAILED: {}", e);
                         // Don't add to notified_articles - will retry next run
+                        fetch_service.release(feed, std::slice::from_ref(article))?;
                     }
                 }
             }
@@ -334,12 +853,17 @@ fn cmd_run(
 
         // Mark articles as notified (skip_notify marks without sending, normal marks after sending)
         if !dry_run && !notified_articles.is_empty() {
+            fetch_service.keep_pending(feed, &pending)?;
             fetch_service.mark_notified(feed, &notified_articles)?;
         }
 
         println!();
     }
 
+    if let Some(service) = &notification_service {
+        send_digests(&fetch_service, service, &results)?;
+    }
+
     if dry_run {
         println!("Dry run complete. Would notify {} articles.", total_new);
     } else if skip_notify {
@@ -350,3 +874,161 @@ fn cmd_run(
 
     Ok(())
 }
+
+/// Each feed of the run with the notifications sinks still have to be sent, and
+/// whether those changed
+type PendingByFeed<'a> = Vec<(&'a Feed, Vec<PendingNotification>, bool)>;
+
+fn load_pending<'a>(
+    fetch_service: &BoxedFetchService,
+    results: &'a [FetchResult],
+) -> FeederResult<PendingByFeed<'a>> {
+    let mut feeds = Vec::new();
+    for result in results {
+        let pending = fetch_service.pending(&result.feed)?;
+        if !pending.is_empty() {
+            feeds.push((&result.feed, pending, false));
+        }
+    }
+    Ok(feeds)
+}
+
+fn save_pending(fetch_service: &BoxedFetchService, feeds: PendingByFeed) -> FeederResult<()> {
+    for (feed, pending, changed) in feeds {
+        if changed {
+            fetch_service.set_pending(feed, &pending)?;
+        }
+    }
+    Ok(())
+}
+
+/// Now, as the notification service sees it, for comparing with `not_before`
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Send notifications again to the sinks that failed to take them in earlier runs,
+/// once the time a sink asked to wait is up. Ones that fail again with a retryable
+/// error stay pending; ones for sinks no longer configured are dropped.
+fn retry_pending(
+    fetch_service: &BoxedFetchService,
+    notification_service: &NotificationService,
+    results: &[FetchResult],
+) -> FeederResult<()> {
+    let sinks = notification_service.sink_ids(DeliveryMode::Each);
+    let digest_sinks = notification_service.sink_ids(DeliveryMode::Digest);
+    let now = now();
+
+    let mut feeds = load_pending(fetch_service, results)?;
+    for (feed, pending, changed) in &mut feeds {
+        let route = Route::for_feed(feed);
+        let mut kept = Vec::new();
+
+        for entry in std::mem::take(pending) {
+            if digest_sinks.contains(&entry.sink) || !entry.is_due(&now) {
+                kept.push(entry);
+                continue;
+            }
+
+            *changed = true;
+            print!("  Sending to {}: {}... ", entry.sink, entry.notification.article_title);
+            io::stdout().flush()?;
+
+            let sent = notification_service.send_to(&entry.sink, &entry.notification, &route);
+            let failure = match sent {
+                Ok(()) => {
+                    println!("OK");
+                    continue;
+                }
+                Err(error @ FeederError::PartlyDelivered { .. }) => {
+                    println!("{}", error);
+                    continue;
+                }
+                Err(error) if !sinks.contains(&entry.sink) => {
+                    println!("dropped: {}", error);
+                    continue;
+                }
+                Err(error) => SinkFailure {
+                    sink: entry.sink,
+                    error,
+                },
+            };
+            match failure.pending(&entry.notification) {
+                Some(again) => {
+                    println!("FAILED: {} (retried next run)", failure.error);
+                    kept.push(again);
+                }
+                None => println!("FAILED: {}", failure.error),
+            }
+        }
+
+        *pending = kept;
+    }
+
+    save_pending(fetch_service, feeds)
+}
+
+/// Send each digest sink one digest of the notifications kept pending for it,
+/// including ones a failed digest left. They stay pending until a digest with them
+/// was sent, or it failed for good.
+fn send_digests(
+    fetch_service: &BoxedFetchService,
+    notification_service: &NotificationService,
+    results: &[FetchResult],
+) -> FeederResult<()> {
+    let now = now();
+    let mut feeds = load_pending(fetch_service, results)?;
+
+    for sink in notification_service.sink_ids(DeliveryMode::Digest) {
+        let is_due = |entry: &PendingNotification| entry.sink == sink && entry.is_due(&now);
+        let notifications: Vec<Notification> = feeds
+            .iter()
+            .flat_map(|(_, pending, _)| pending.iter().filter(|entry| is_due(entry)))
+            .map(|entry| entry.notification.clone())
+            .collect();
+        if notifications.is_empty() {
+            continue;
+        }
+
+        print!("Sending digest of {} articles to {}... ", notifications.len(), sink);
+        io::stdout().flush()?;
+
+        // Kept, not before the time the sink asked for, when sending later might work
+        let again = match notification_service.send_digest_to(&sink, &notifications) {
+            Ok(()) => {
+                println!("OK\n");
+                None
+            }
+            Err(error) => {
+                let failure = SinkFailure {
+                    sink: sink.clone(),
+                    error,
+                };
+                if failure.error.is_retryable() {
+                    println!("FAILED: {} (kept for the next digest)\n", failure.error);
+                    Some(failure.not_before())
+                } else {
+                    println!("FAILED: {}\n", failure.error);
+                    None
+                }
+            }
+        };
+        for (_, pending, changed) in &mut feeds {
+            let mut kept = Vec::new();
+            for mut entry in std::mem::take(pending) {
+                if !is_due(&entry) {
+                    kept.push(entry);
+                    continue;
+                }
+                *changed = true;
+                if let Some(not_before) = &again {
+                    entry.not_before = not_before.clone();
+                    kept.push(entry);
+                }
+            }
+            *pending = kept;
+        }
+    }
+
+    save_pending(fetch_service, feeds)
+}
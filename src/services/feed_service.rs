@@ -1,6 +1,6 @@
-use crate::domain::Feed;
+use crate::domain::{Feed, FeedSettings, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::SourceRegistry;
+use crate::sources::{FeedCandidate, FeedMetadata, SourceRegistry};
 use crate::storage::traits::FeedRepository;
 
 pub struct FeedService<R: FeedRepository> {
@@ -17,15 +17,64 @@ impl<R: FeedRepository> FeedService<R> {
     }
 
     /// Add a new feed by URL
-    /// Validates the feed and stores it in the database
-    pub fn add(&self, url: &str) -> FeederResult<Feed> {
+    /// Validates the feed and stores it in the database, along with the headers and
+    /// credentials its requests need
+    pub fn add(&self, url: &str, request: RequestOptions) -> FeederResult<Feed> {
+        self.add_validated(url, FeedSettings::default(), request, |request| {
+            self.source_registry.validate(url, request)
+        })
+    }
+
+    /// List the feeds a URL offers, best first (empty when there is nothing to choose)
+    pub fn discover(&self, url: &str, request: &RequestOptions) -> FeederResult<Vec<FeedCandidate>> {
+        self.source_registry.discover(url, request)
+    }
+
+    /// Add one of the feeds returned by `discover`, keeping `url` as the feed's page
+    pub fn add_discovered(
+        &self,
+        url: &str,
+        candidate: &FeedCandidate,
+        request: RequestOptions,
+    ) -> FeederResult<Feed> {
+        self.add_validated(url, FeedSettings::default(), request, |request| {
+            self.source_registry.validate_candidate(url, candidate, request)
+        })
+    }
+
+    /// Add a web page to watch for changes, optionally narrowed to a CSS selector
+    pub fn add_page_watch(
+        &self,
+        url: &str,
+        selector: Option<String>,
+        request: RequestOptions,
+    ) -> FeederResult<Feed> {
+        let settings = FeedSettings {
+            selector,
+            ..Default::default()
+        };
+
+        self.add_validated(url, settings, request, |request| {
+            self.source_registry.validate_as(SourceType::PageWatch, url, request)
+        })
+    }
+
+    /// Store the feed `validate` describes under `url`, unless `url` was added before.
+    /// The request options are validated with and kept in the feed's settings.
+    fn add_validated(
+        &self,
+        url: &str,
+        settings: FeedSettings,
+        request: RequestOptions,
+        validate: impl FnOnce(&RequestOptions) -> FeederResult<FeedMetadata>,
+    ) -> FeederResult<Feed> {
         // Check if already exists
         if self.repository.exists(url)? {
             return Err(FeederError::FeedAlreadyExists(url.to_string()));
         }
 
         // Validate and get metadata
-        let metadata = self.source_registry.validate(url)?;
+        let metadata = validate(&request)?;
 
         // Create feed entity
         let feed = Feed::new(
@@ -34,7 +83,8 @@ impl<R: FeedRepository> FeedService<R> {
             metadata.title,
             metadata.feed_type,
             metadata.source_type,
-        );
+        )
+        .with_settings(FeedSettings { request, ..settings });
 
         // Store in database
         let id = self.repository.add(&feed)?;
@@ -60,6 +110,46 @@ impl<R: FeedRepository> FeedService<R> {
         self.repository.get_by_id(id)
     }
 
+    /// Find a feed by ID, URL, feed URL or (case-insensitive) title
+    pub fn find(&self, selector: &str) -> FeederResult<Feed> {
+        let selector = selector.trim();
+
+        if let Ok(id) = selector.parse::<i64>() {
+            if let Some(feed) = self.repository.get_by_id(id)? {
+                return Ok(feed);
+            }
+        }
+
+        if let Some(feed) = self.repository.get_by_url(selector)? {
+            return Ok(feed);
+        }
+
+        let mut matches: Vec<Feed> = self
+            .repository
+            .get_all()?
+            .into_iter()
+            .filter(|f| {
+                f.feed_url == selector
+                    || f.title.eq_ignore_ascii_case(selector)
+                    || f.display_title().eq_ignore_ascii_case(selector)
+            })
+            .collect();
+
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => Err(FeederError::FeedNotFound(selector.to_string())),
+            n => Err(FeederError::InvalidInput(format!(
+                "'{}' matches {} feeds, use the ID or URL instead",
+                selector, n
+            ))),
+        }
+    }
+
+    /// Save changes to an existing feed
+    pub fn update(&self, feed: &Feed) -> FeederResult<()> {
+        self.repository.update(feed)
+    }
+
     /// Check if a feed URL already exists
     pub fn exists(&self, url: &str) -> FeederResult<bool> {
         self.repository.exists(url)
@@ -69,7 +159,6 @@ impl<R: FeedRepository> FeedService<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{FeedType, SourceType};
     use crate::storage::sqlite::{SqliteFeedRepository, SqliteStorage};
 
     fn setup() -> FeedService<SqliteFeedRepository> {
@@ -86,6 +175,25 @@ mod tests {
         assert!(feeds.is_empty());
     }
 
+    #[test]
+    fn test_find_by_id_url_and_title() {
+        let service = setup();
+        let feed = Feed::new(
+            "https://example.com".to_string(),
+            "https://example.com/feed.xml".to_string(),
+            "Example Blog".to_string(),
+            crate::domain::FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let id = service.repository.add(&feed).unwrap();
+
+        assert_eq!(service.find(&id.to_string()).unwrap().id, Some(id));
+        assert_eq!(service.find("https://example.com").unwrap().id, Some(id));
+        assert_eq!(service.find("https://example.com/feed.xml").unwrap().id, Some(id));
+        assert_eq!(service.find("example blog").unwrap().id, Some(id));
+        assert!(matches!(service.find("missing"), Err(FeederError::FeedNotFound(_))));
+    }
+
     #[test]
     fn test_exists_false() {
         let service = setup();
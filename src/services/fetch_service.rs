@@ -1,13 +1,67 @@
-use crate::domain::{Article, Feed, Notification};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::domain::{Article, Feed, FeedType, Notification, PendingNotification, SourceType};
 use crate::errors::FeederResult;
+use crate::http::{redact_url, redact_urls, same_origin};
+use crate::sources::rss_atom::UNTITLED_FEED;
 use crate::sources::SourceRegistry;
-use crate::storage::traits::{ArticleCacheRepository, FeedRepository};
+use crate::storage::traits::{
+    ArticleCacheRepository, FeedRepository, FeedStateRepository, PENDING_NOTIFICATIONS_KEY,
+    ERROR_COUNT_KEY, FETCHED_AT_KEY, LAST_ERROR_KEY,
+};
+
+/// What changed when a feed's stored metadata was brought up to date
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedUpdate {
+    /// The title before and after the feed was renamed
+    pub renamed: Option<(String, String)>,
+    /// The feed URL before and after the feed moved
+    pub moved: Option<(String, String)>,
+    /// The feed type before and after it changed
+    pub retyped: Option<(FeedType, FeedType)>,
+    /// Where the feed permanently redirects to on another site. That isn't stored
+    /// by a run, as the feed's credentials would then go there.
+    pub moved_elsewhere: Option<String>,
+}
+
+impl FeedUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_none()
+            && self.moved.is_none()
+            && self.retyped.is_none()
+            && self.moved_elsewhere.is_none()
+    }
+
+    /// One line per change
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some((from, to)) = &self.renamed {
+            lines.push(format!("renamed from '{}' to '{}'", from, to));
+        }
+        if let Some((from, to)) = &self.moved {
+            lines.push(format!("moved from {} to {}", redact_url(from), redact_url(to)));
+        }
+        if let Some((from, to)) = &self.retyped {
+            lines.push(format!("type changed from {} to {}", from.as_str(), to.as_str()));
+        }
+        if let Some(feed_url) = &self.moved_elsewhere {
+            lines.push(format!(
+                "moved to {} on another site; check it and run `feeder refresh` to follow it",
+                redact_url(feed_url)
+            ));
+        }
+        lines
+    }
+}
 
 /// Result of fetching a single feed
 pub struct FetchResult {
     pub feed: Feed,
     pub total_articles: usize,
     pub new_articles: Vec<Article>,
+    /// Changes to the feed's title or URL found while fetching it
+    pub update: FeedUpdate,
     pub error: Option<String>,
 }
 
@@ -17,6 +71,7 @@ impl FetchResult {
             feed,
             total_articles,
             new_articles,
+            update: FeedUpdate::default(),
             error: None,
         }
     }
@@ -26,10 +81,16 @@ impl FetchResult {
             feed,
             total_articles: 0,
             new_articles: Vec::new(),
+            update: FeedUpdate::default(),
             error: Some(error),
         }
     }
 
+    pub fn with_update(mut self, update: FeedUpdate) -> Self {
+        self.update = update;
+        self
+    }
+
     pub fn has_new_articles(&self) -> bool {
         !self.new_articles.is_empty()
     }
@@ -39,28 +100,106 @@ impl FetchResult {
     }
 }
 
-pub struct FetchService<F: FeedRepository, C: ArticleCacheRepository> {
+/// Feed state as a dry run sees it: writes stay in memory on top of the store,
+/// so sources behave as they would in a real run without changing anything
+struct DryRunState<'a> {
+    store: &'a dyn FeedStateRepository,
+    written: Mutex<HashMap<(i64, String), String>>,
+}
+
+impl<'a> DryRunState<'a> {
+    fn new(store: &'a dyn FeedStateRepository) -> Self {
+        Self {
+            store,
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl FeedStateRepository for DryRunState<'_> {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>> {
+        let written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        match written.get(&(feed_id, key.to_string())) {
+            Some(value) => Ok(Some(value.clone())),
+            None => self.store.get(feed_id, key),
+        }
+    }
+
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()> {
+        self.written
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((feed_id, key.to_string()), value.to_string());
+        Ok(())
+    }
+}
+
+pub struct FetchService<F: FeedRepository, C: ArticleCacheRepository, S: FeedStateRepository> {
     feed_repository: F,
     cache_repository: C,
+    state_repository: S,
     source_registry: SourceRegistry,
+    /// Fetch without changing anything stored
+    dry_run: bool,
 }
 
-impl<F: FeedRepository, C: ArticleCacheRepository> FetchService<F, C> {
+impl<F, C, S> FetchService<F, C, S>
+where
+    F: FeedRepository,
+    C: ArticleCacheRepository,
+    S: FeedStateRepository,
+{
     pub fn new(
         feed_repository: F,
         cache_repository: C,
+        state_repository: S,
         source_registry: SourceRegistry,
     ) -> Self {
         Self {
             feed_repository,
             cache_repository,
+            state_repository,
             source_registry,
+            dry_run: false,
         }
     }
 
-    /// Fetch articles from a single feed and return (total_count, unnotified_articles)
-    pub fn fetch_unnotified(&self, feed: &Feed) -> FeederResult<(usize, Vec<Article>)> {
-        let articles = self.source_registry.fetch_articles(feed)?;
+    /// Fetch without storing anything, as `feeder run --dry-run` does
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Fetch articles from a single feed and return (total_count, unnotified_articles).
+    ///
+    /// A feed that now has another title, or has permanently moved within its site,
+    /// is updated first, so the articles are keyed by its current title. A move to
+    /// another site is only reported.
+    pub fn fetch_unnotified(&self, feed: &mut Feed) -> FeederResult<(usize, Vec<Article>, FeedUpdate)> {
+        let fetched = if self.dry_run {
+            let state = DryRunState::new(&self.state_repository);
+            self.source_registry.fetch_articles_with_state(feed, &state)?
+        } else {
+            self.source_registry
+                .fetch_articles_with_state(feed, &self.state_repository)?
+        };
+
+        let mut updated = feed.clone();
+        if let Some(title) = &fetched.title {
+            updated.title = title.clone();
+        }
+        let mut moved_elsewhere = None;
+        match fetched.moved_to() {
+            Some(feed_url) if same_origin(feed_url, &feed.feed_url) => {
+                updated.feed_url = feed_url.to_string()
+            }
+            Some(feed_url) => moved_elsewhere = Some(feed_url.to_string()),
+            None => {}
+        }
+        let mut update = self.update_metadata(feed, updated)?;
+        update.moved_elsewhere = moved_elsewhere;
+
+        let articles = fetched.articles;
         let total_count = articles.len();
 
         // Generate cache keys for all articles
@@ -72,41 +211,202 @@ impl<F: FeedRepository, C: ArticleCacheRepository> FetchService<F, C> {
         // Get unnotified cache keys
         let unnotified_keys = self.cache_repository.get_unnotified(&cache_keys)?;
 
+        // Entries still in the feed are kept by `prune`, or they'd be notified again.
+        // An empty response is more likely a hiccup than a feed emptied for good, so it
+        // doesn't move `FETCHED_AT_KEY` on and leave the entries it missed prunable.
+        if let Some(feed_id) = feed.id.filter(|_| !self.dry_run) {
+            if !cache_keys.is_empty() {
+                let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+                self.cache_repository.mark_seen(&cache_keys, &now)?;
+                self.state_repository.set(feed_id, FETCHED_AT_KEY, &now)?;
+            }
+            self.state_repository.set(feed_id, ERROR_COUNT_KEY, "0")?;
+            self.state_repository.set(feed_id, LAST_ERROR_KEY, "")?;
+        }
+
         // Filter articles to only unnotified ones
         let unnotified_articles: Vec<Article> = articles
             .into_iter()
             .filter(|a| unnotified_keys.contains(&a.cache_key(&feed.title)))
             .collect();
 
-        Ok((total_count, unnotified_articles))
+        Ok((total_count, unnotified_articles, update))
     }
 
-    /// Mark articles as notified
-    pub fn mark_notified(&self, feed: &Feed, articles: &[Article]) -> FeederResult<()> {
+    /// Validate feeds again and store the title, feed URL and type they have now.
+    /// Failures are reported per feed rather than stopping the others.
+    pub fn refresh(&self, feeds: Vec<Feed>) -> Vec<(Feed, FeederResult<FeedUpdate>)> {
+        feeds
+            .into_iter()
+            .map(|mut feed| {
+                let result = self.refresh_feed(&mut feed);
+                (feed, result)
+            })
+            .collect()
+    }
+
+    fn refresh_feed(&self, feed: &mut Feed) -> FeederResult<FeedUpdate> {
+        // Feeds and watched pages are validated where they are fetched from; the
+        // other sources work the feed URL out from the page they were added with
+        let url = match feed.source_type {
+            SourceType::RssAtom | SourceType::PageWatch => &feed.feed_url,
+            _ => &feed.url,
+        };
+        let metadata =
+            self.source_registry
+                .validate_as(feed.source_type, url, &feed.settings.request)?;
+
+        let mut updated = feed.clone();
+        // Sources make up a title for feeds that have none, which is no reason to
+        // rename one. A watched page keeps the title it was added with.
+        if feed.source_type != SourceType::PageWatch
+            && metadata.title != UNTITLED_FEED
+            && metadata.title != *url
+        {
+            updated.title = metadata.title;
+        }
+        updated.feed_url = metadata.feed_url;
+        updated.feed_type = metadata.feed_type;
+        self.update_metadata(feed, updated)
+    }
+
+    /// Store `updated` in place of `feed` if its title, feed URL or type differ.
+    /// A dry run only reports the differences.
+    ///
+    /// Renaming moves the feed's cache entries to keys under the new title, so
+    /// articles already sent aren't sent again.
+    fn update_metadata(&self, feed: &mut Feed, updated: Feed) -> FeederResult<FeedUpdate> {
+        let mut update = FeedUpdate::default();
+        if !updated.title.trim().is_empty() && updated.title != feed.title {
+            update.renamed = Some((feed.title.clone(), updated.title.clone()));
+        }
+        if updated.feed_url != feed.feed_url {
+            update.moved = Some((feed.feed_url.clone(), updated.feed_url.clone()));
+        }
+        if updated.feed_type != feed.feed_type {
+            update.retyped = Some((feed.feed_type, updated.feed_type));
+        }
+
+        if update.is_empty() || self.dry_run {
+            return Ok(update);
+        }
+
+        let Some(feed_id) = feed.id else {
+            return Ok(FeedUpdate::default());
+        };
+
+        if update.renamed.is_some() {
+            self.cache_repository.rekey(
+                feed_id,
+                &Article::cache_key_prefix(&feed.title),
+                &Article::cache_key_prefix(&updated.title),
+            )?;
+            feed.title = updated.title;
+        }
+        feed.feed_url = updated.feed_url;
+        feed.feed_type = updated.feed_type;
+        self.feed_repository.update(feed)?;
+
+        Ok(update)
+    }
+
+    /// Reserve an article before sending it; false means another run has it
+    pub fn claim(&self, feed: &Feed, article: &Article) -> FeederResult<bool> {
         let feed_id = feed.id.ok_or_else(|| {
             crate::errors::FeederError::FeedNotFound("Feed has no ID".to_string())
         })?;
 
+        self.cache_repository
+            .claim(&article.cache_key(&feed.title), feed_id, &article.title)
+    }
+
+    /// Release claims on articles that couldn't be sent, so they are retried next run
+    pub fn release(&self, feed: &Feed, articles: &[Article]) -> FeederResult<()> {
         for article in articles {
-            let cache_key = article.cache_key(&feed.title);
             self.cache_repository
-                .mark_notified(&cache_key, feed_id, &article.title)?;
+                .release(&article.cache_key(&feed.title))?;
         }
 
         Ok(())
     }
 
-    /// Fetch all feeds and return detailed results for each
+    /// Mark articles as notified and keep them in the article history.
+    ///
+    /// Both happen in one transaction, so a crash can't leave part of a feed's
+    /// articles unmarked to be sent again, or marked without their history.
+    pub fn mark_notified(&self, feed: &Feed, articles: &[Article]) -> FeederResult<()> {
+        let feed_id = feed.id.ok_or_else(|| {
+            crate::errors::FeederError::FeedNotFound("Feed has no ID".to_string())
+        })?;
+
+        let entries: Vec<(String, &Article)> = articles
+            .iter()
+            .map(|article| (article.cache_key(&feed.title), article))
+            .collect();
+
+        self.cache_repository.mark_notified_batch(feed_id, &entries)
+    }
+
+    /// Keep notifications that sinks still have to be sent. They are stored with the
+    /// feed, so a run that fails to send them, or crashes, leaves them for the next.
+    pub fn keep_pending(&self, feed: &Feed, pending: &[PendingNotification]) -> FeederResult<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut kept = self.pending(feed)?;
+        kept.extend_from_slice(pending);
+        self.set_pending(feed, &kept)
+    }
+
+    /// Notifications of a feed that sinks still have to be sent
+    pub fn pending(&self, feed: &Feed) -> FeederResult<Vec<PendingNotification>> {
+        let Some(feed_id) = feed.id else {
+            return Ok(Vec::new());
+        };
+
+        match self.state_repository.get(feed_id, PENDING_NOTIFICATIONS_KEY)? {
+            Some(json) if !json.is_empty() => serde_json::from_str(&json)
+                .map_err(|e| crate::errors::FeederError::Storage(e.to_string())),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Replace a feed's pending notifications, as when some of them were sent
+    pub fn set_pending(&self, feed: &Feed, pending: &[PendingNotification]) -> FeederResult<()> {
+        let feed_id = feed.id.ok_or_else(|| {
+            crate::errors::FeederError::FeedNotFound("Feed has no ID".to_string())
+        })?;
+
+        let json = if pending.is_empty() {
+            String::new()
+        } else {
+            serde_json::to_string(pending)
+                .map_err(|e| crate::errors::FeederError::Storage(e.to_string()))?
+        };
+        self.state_repository.set(feed_id, PENDING_NOTIFICATIONS_KEY, &json)
+    }
+
+    /// Fetch all feeds and return detailed results for each. Feeds another worker
+    /// is fetching at the same time are left to it.
     pub fn fetch_all_unnotified(&self) -> FeederResult<Vec<FetchResult>> {
-        let feeds = self.feed_repository.get_all()?;
+        let mut taken = Vec::new();
         let mut results = Vec::new();
 
-        for feed in feeds {
-            match self.fetch_unnotified(&feed) {
-                Ok((total, articles)) => {
-                    results.push(FetchResult::success(feed, total, articles));
+        while let Some(mut feed) = self.feed_repository.lease_next(&taken)? {
+            let Some(feed_id) = feed.id else {
+                break;
+            };
+            taken.push(feed_id);
+
+            match self.fetch_unnotified(&mut feed) {
+                Ok((total, articles, update)) => {
+                    results.push(FetchResult::success(feed, total, articles).with_update(update));
                 }
                 Err(e) => {
+                    if !self.dry_run {
+                        self.record_failure(feed_id, &e.to_string())?;
+                    }
                     results.push(FetchResult::error(feed, e.to_string()));
                 }
             }
@@ -115,6 +415,20 @@ impl<F: FeedRepository, C: ArticleCacheRepository> FetchService<F, C> {
         Ok(results)
     }
 
+    /// Count a failed fetch towards the feed's errors since it last succeeded. URLs
+    /// in the error are kept without their credentials, as `feeder show` prints it.
+    fn record_failure(&self, feed_id: i64, error: &str) -> FeederResult<()> {
+        let errors = self
+            .state_repository
+            .get(feed_id, ERROR_COUNT_KEY)?
+            .and_then(|count| count.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        self.state_repository
+            .set(feed_id, ERROR_COUNT_KEY, &(errors + 1).to_string())?;
+        self.state_repository.set(feed_id, LAST_ERROR_KEY, &redact_urls(error))
+    }
+
     /// Create notifications from articles
     pub fn create_notifications(feed: &Feed, articles: &[Article]) -> Vec<Notification> {
         articles
@@ -127,17 +441,27 @@ impl<F: FeedRepository, C: ArticleCacheRepository> FetchService<F, C> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{FeedType, SourceType};
+    use crate::domain::{FeedAuth, FeedType, SourceType};
+    use crate::storage::traits::ArticleHistoryRepository;
     use crate::storage::sqlite::{
-        SqliteArticleCacheRepository, SqliteFeedRepository, SqliteStorage,
+        SqliteArticleCacheRepository, SqliteArticleHistoryRepository, SqliteFeedRepository,
+        SqliteFeedStateRepository, SqliteStorage,
     };
 
-    fn setup() -> FetchService<SqliteFeedRepository, SqliteArticleCacheRepository> {
+    type SqliteFetchService =
+        FetchService<SqliteFeedRepository, SqliteArticleCacheRepository, SqliteFeedStateRepository>;
+
+    fn setup() -> SqliteFetchService {
+        setup_with_storage().1
+    }
+
+    fn setup_with_storage() -> (SqliteStorage, SqliteFetchService) {
         let storage = SqliteStorage::in_memory().unwrap();
         let feed_repo = SqliteFeedRepository::new(storage.clone());
-        let cache_repo = SqliteArticleCacheRepository::new(storage);
+        let cache_repo = SqliteArticleCacheRepository::new(storage.clone());
+        let state_repo = SqliteFeedStateRepository::new(storage.clone());
         let registry = SourceRegistry::new();
-        FetchService::new(feed_repo, cache_repo, registry)
+        (storage, FetchService::new(feed_repo, cache_repo, state_repo, registry))
     }
 
     #[test]
@@ -164,10 +488,305 @@ mod tests {
                 .with_content(Some("Content 2".to_string())),
         ];
 
-        let notifications = FetchService::<SqliteFeedRepository, SqliteArticleCacheRepository>::create_notifications(&feed, &articles);
+        let notifications = SqliteFetchService::create_notifications(&feed, &articles);
 
         assert_eq!(notifications.len(), 2);
         assert_eq!(notifications[0].feed_title, "Test Feed");
         assert_eq!(notifications[0].article_title, "Article 1");
     }
+
+    #[test]
+    fn test_mark_notified_keeps_history() {
+        let (storage, service) = setup_with_storage();
+
+        let mut feed = Feed::new(
+            "https://example.com/feed".to_string(),
+            "https://example.com/feed".to_string(),
+            "Test Feed".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        feed.id = Some(service.feed_repository.add(&feed).unwrap());
+
+        let article = Article::new("1".to_string(), "Article 1".to_string())
+            .with_links(vec!["https://example.com/1".to_string()]);
+        service.mark_notified(&feed, std::slice::from_ref(&article)).unwrap();
+
+        assert!(service.cache_repository.is_notified(&article.cache_key("Test Feed")).unwrap());
+        let history = SqliteArticleHistoryRepository::new(storage).recent(None, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].link.as_deref(), Some("https://example.com/1"));
+    }
+
+    #[test]
+    fn test_pending_kept_until_replaced() {
+        let service = setup();
+
+        let mut feed = Feed::new(
+            "https://example.com/feed".to_string(),
+            "https://example.com/feed".to_string(),
+            "Test Feed".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        feed.id = Some(service.feed_repository.add(&feed).unwrap());
+
+        let first = Article::new("1".to_string(), "Article 1".to_string());
+        let second = Article::new("2".to_string(), "Article 2".to_string());
+        for (sink, article) in [("email", &first), ("exec", &second)] {
+            let notification = Notification::from_article(&feed, article);
+            let pending = PendingNotification::new(sink.to_string(), notification);
+            service.keep_pending(&feed, &[pending]).unwrap();
+        }
+
+        let pending = service.pending(&feed).unwrap();
+        let sent: Vec<_> = pending
+            .iter()
+            .map(|p| (p.sink.as_str(), p.notification.article_title.as_str()))
+            .collect();
+        assert_eq!(sent, [("email", "Article 1"), ("exec", "Article 2")]);
+
+        service.set_pending(&feed, &pending[1..]).unwrap();
+        assert_eq!(service.pending(&feed).unwrap().len(), 1);
+        service.set_pending(&feed, &[]).unwrap();
+        assert!(service.pending(&feed).unwrap().is_empty());
+    }
+
+    const RENAMED_FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>New Title</title>
+  <item><guid>1</guid><title>Already sent</title></item>
+  <item><guid>2</guid><title>Fresh</title></item>
+</channel></rss>"#;
+
+    #[test]
+    fn test_fetch_follows_moved_and_renamed_feed() {
+        let mut server = mockito::Server::new();
+        let _moved = server
+            .mock("GET", "/old.xml")
+            .with_status(301)
+            .with_header("location", "/new.xml")
+            .create();
+        let _feed = server
+            .mock("GET", "/new.xml")
+            .with_header("content-type", "application/rss+xml")
+            .with_body(RENAMED_FEED)
+            .create();
+
+        let service = setup();
+        let mut feed = Feed::new(
+            server.url(),
+            format!("{}/old.xml", server.url()),
+            "Old Title".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let feed_id = service.feed_repository.add(&feed).unwrap();
+        feed.id = Some(feed_id);
+        service
+            .cache_repository
+            .mark_notified("Old Title:1", feed_id, "Already sent")
+            .unwrap();
+
+        let (total, new_articles, update) = service.fetch_unnotified(&mut feed).unwrap();
+
+        assert_eq!(total, 2);
+        let ids: Vec<&str> = new_articles.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["2"]);
+        assert_eq!(update.renamed, Some(("Old Title".to_string(), "New Title".to_string())));
+        assert_eq!(update.moved.unwrap().0, format!("{}/old.xml", server.url()));
+
+        let stored = service.feed_repository.get_by_id(feed_id).unwrap().unwrap();
+        assert_eq!(stored.title, "New Title");
+        assert_eq!(stored.feed_url, format!("{}/new.xml", server.url()));
+        assert!(service.cache_repository.is_notified("New Title:1").unwrap());
+    }
+
+    #[test]
+    fn test_move_to_another_site_only_reported() {
+        let mut elsewhere = mockito::Server::new();
+        let _feed = elsewhere
+            .mock("GET", "/feed.xml")
+            .with_body(RENAMED_FEED)
+            .create();
+        let mut server = mockito::Server::new();
+        let _moved = server
+            .mock("GET", "/feed.xml")
+            .with_status(301)
+            .with_header("location", &format!("{}/feed.xml", elsewhere.url()))
+            .create();
+
+        let service = setup();
+        let mut feed = Feed::new(
+            server.url(),
+            format!("{}/feed.xml", server.url()),
+            "New Title".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        feed.settings.request.auth = Some(FeedAuth::Bearer {
+            token: "secret".to_string(),
+        });
+        feed.id = Some(service.feed_repository.add(&feed).unwrap());
+
+        let (_, _, update) = service.fetch_unnotified(&mut feed).unwrap();
+
+        assert_eq!(update.moved, None);
+        assert_eq!(update.moved_elsewhere, Some(format!("{}/feed.xml", elsewhere.url())));
+        let stored = service.feed_repository.get_by_id(feed.id.unwrap()).unwrap().unwrap();
+        assert_eq!(stored.feed_url, format!("{}/feed.xml", server.url()));
+    }
+
+    #[test]
+    fn test_dry_run_reports_rename_without_storing_it() {
+        let mut server = mockito::Server::new();
+        let _feed = server.mock("GET", "/feed.xml").with_body(RENAMED_FEED).create();
+
+        let service = setup().with_dry_run(true);
+        let mut feed = Feed::new(
+            server.url(),
+            format!("{}/feed.xml", server.url()),
+            "Old Title".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let feed_id = service.feed_repository.add(&feed).unwrap();
+        feed.id = Some(feed_id);
+        service
+            .cache_repository
+            .mark_notified("Old Title:1", feed_id, "Already sent")
+            .unwrap();
+
+        let (_, new_articles, update) = service.fetch_unnotified(&mut feed).unwrap();
+
+        assert_eq!(update.renamed, Some(("Old Title".to_string(), "New Title".to_string())));
+        assert_eq!(new_articles.len(), 1);
+        assert_eq!(service.feed_repository.get_by_id(feed_id).unwrap().unwrap().title, "Old Title");
+        assert!(service.cache_repository.is_notified("Old Title:1").unwrap());
+    }
+
+    #[test]
+    fn test_refresh_keeps_title_of_untitled_feed() {
+        let mut server = mockito::Server::new();
+        let _feed = server
+            .mock("GET", "/feed.xml")
+            .with_header("content-type", "application/rss+xml")
+            .with_body(r#"<?xml version="1.0"?><rss version="2.0"><channel></channel></rss>"#)
+            .create();
+
+        let service = setup();
+        let mut feed = Feed::new(
+            server.url(),
+            format!("{}/feed.xml", server.url()),
+            "My Name For It".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        feed.id = Some(service.feed_repository.add(&feed).unwrap());
+
+        let (feed, result) = service.refresh(vec![feed]).pop().unwrap();
+
+        assert!(result.unwrap().is_empty());
+        assert_eq!(feed.title, "My Name For It");
+    }
+
+    #[test]
+    fn test_dry_run_leaves_page_watch_state_alone() {
+        let mut server = mockito::Server::new();
+        let _page = server
+            .mock("GET", "/")
+            .with_body(
+                "<html><head><title>Status 12:00</title></head>\
+                 <body><p>All systems operational</p></body></html>",
+            )
+            .create();
+
+        let service = setup().with_dry_run(true);
+        let mut feed = Feed::new(
+            server.url(),
+            server.url(),
+            "Status".to_string(),
+            FeedType::Html,
+            SourceType::PageWatch,
+        );
+        let feed_id = service.feed_repository.add(&feed).unwrap();
+        feed.id = Some(feed_id);
+        service.state_repository.set(feed_id, "page_watch.hash", "old").unwrap();
+        service
+            .state_repository
+            .set(feed_id, "page_watch.snapshot", "All systems down")
+            .unwrap();
+
+        let (_, articles, update) = service.fetch_unnotified(&mut feed).unwrap();
+
+        assert_eq!(articles.len(), 1);
+        // A watched page keeps the title it was added with
+        assert!(update.renamed.is_none());
+        assert_eq!(
+            service.state_repository.get(feed_id, "page_watch.hash").unwrap().as_deref(),
+            Some("old")
+        );
+        assert_eq!(service.state_repository.get(feed_id, FETCHED_AT_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn test_empty_fetch_keeps_fetched_at() {
+        let mut server = mockito::Server::new();
+        let _empty = server
+            .mock("GET", "/feed.xml")
+            .with_body(r#"<?xml version="1.0"?><rss version="2.0"><channel><title>New Title</title></channel></rss>"#)
+            .create();
+
+        let service = setup();
+        let mut feed = Feed::new(
+            server.url(),
+            format!("{}/feed.xml", server.url()),
+            "New Title".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let feed_id = service.feed_repository.add(&feed).unwrap();
+        feed.id = Some(feed_id);
+        service
+            .state_repository
+            .set(feed_id, FETCHED_AT_KEY, "2024-01-01 00:00:00")
+            .unwrap();
+
+        let (total, _, _) = service.fetch_unnotified(&mut feed).unwrap();
+
+        assert_eq!(total, 0);
+        assert_eq!(
+            service.state_repository.get(feed_id, FETCHED_AT_KEY).unwrap().as_deref(),
+            Some("2024-01-01 00:00:00")
+        );
+    }
+
+    #[test]
+    fn test_failed_fetches_counted_until_success() {
+        let mut server = mockito::Server::new();
+        let broken = server.mock("GET", "/feed.xml").with_body("not a feed").create();
+
+        let service = setup();
+        let feed = Feed::new(
+            server.url(),
+            format!("{}/feed.xml", server.url()),
+            "New Title".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let feed_id = service.feed_repository.add(&feed).unwrap();
+        let errors = || service.state_repository.get(feed_id, ERROR_COUNT_KEY).unwrap();
+
+        assert!(service.fetch_all_unnotified().unwrap()[0].is_error());
+        service.fetch_all_unnotified().unwrap();
+        assert_eq!(errors().as_deref(), Some("2"));
+        assert!(service.state_repository.get(feed_id, LAST_ERROR_KEY).unwrap().is_some());
+
+        broken.remove();
+        server.mock("GET", "/feed.xml").with_body(RENAMED_FEED).create();
+        assert!(!service.fetch_all_unnotified().unwrap()[0].is_error());
+        assert_eq!(errors().as_deref(), Some("0"));
+        let last_error = service.state_repository.get(feed_id, LAST_ERROR_KEY).unwrap();
+        assert_eq!(last_error.as_deref(), Some(""));
+    }
 }
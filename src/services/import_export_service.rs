@@ -1,7 +1,10 @@
+use std::collections::HashSet;
+
 use opml::{Outline, OPML};
 use regex::Regex;
 
-use crate::domain::Feed;
+use crate::domain::{Feed, RequestOptions, SourceType};
+use crate::http::redact_url;
 use crate::errors::{FeederError, FeederResult};
 use crate::sources::SourceRegistry;
 use crate::storage::traits::FeedRepository;
@@ -12,6 +15,16 @@ pub struct ImportResult {
     pub duplicates: Vec<String>,
 }
 
+/// What `import_opml` does when some feeds fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Add every feed that validates and report the rest
+    #[default]
+    BestEffort,
+    /// Add all feeds in one transaction, or none if any fails
+    Atomic,
+}
+
 pub struct ImportExportService<R: FeedRepository> {
     repository: R,
     source_registry: SourceRegistry,
@@ -25,8 +38,12 @@ impl<R: FeedRepository> ImportExportService<R> {
         }
     }
 
-    /// Import feeds from OPML content
-    pub fn import_opml(&self, content: &str) -> FeederResult<ImportResult> {
+    /// Import feeds from OPML content.
+    ///
+    /// Feeds already subscribed to, or listed twice, are skipped as duplicates. In
+    /// `Atomic` mode nothing is added when any feed fails: `added` is empty and
+    /// `invalid` lists the failures.
+    pub fn import_opml(&self, content: &str, mode: ImportMode) -> FeederResult<ImportResult> {
         let opml = OPML::from_str(content)
             .map_err(|e| FeederError::OpmlParse(e.to_string()))?;
 
@@ -38,25 +55,34 @@ impl<R: FeedRepository> ImportExportService<R> {
 
         // Extract all feed URLs from outlines
         let urls = self.extract_feed_urls(&opml.body.outlines);
+        let mut seen = HashSet::new();
+        let mut validated = Vec::new();
 
         for url in urls {
             // Check for duplicate
-            if self.repository.exists(&url)? {
+            if !seen.insert(url.clone()) || self.repository.exists(&url)? {
                 result.duplicates.push(url);
                 continue;
             }
 
             // Validate feed
-            match self.source_registry.validate(&url) {
-                Ok(metadata) => {
-                    let feed = Feed::new(
-                        url.clone(),
-                        metadata.feed_url,
-                        metadata.title,
-                        metadata.feed_type,
-                        metadata.source_type,
-                    );
+            match self.source_registry.validate(&url, &RequestOptions::default()) {
+                Ok(metadata) => validated.push(Feed::new(
+                    url,
+                    metadata.feed_url,
+                    metadata.title,
+                    metadata.feed_type,
+                    metadata.source_type,
+                )),
+                Err(e) => {
+                    result.invalid.push((url, e.to_string()));
+                }
+            }
+        }
 
+        match mode {
+            ImportMode::BestEffort => {
+                for feed in validated {
                     match self.repository.add(&feed) {
                         Ok(id) => {
                             result.added.push(Feed {
@@ -65,14 +91,23 @@ impl<R: FeedRepository> ImportExportService<R> {
                             });
                         }
                         Err(e) => {
-                            result.invalid.push((url, e.to_string()));
+                            result.invalid.push((feed.url, e.to_string()));
                         }
                     }
                 }
-                Err(e) => {
-                    result.invalid.push((url, e.to_string()));
-                }
             }
+            ImportMode::Atomic if result.invalid.is_empty() => {
+                let ids = self.repository.add_batch(&validated)?;
+                result.added = validated
+                    .into_iter()
+                    .zip(ids)
+                    .map(|(feed, id)| Feed {
+                        id: Some(id),
+                        ..feed
+                    })
+                    .collect();
+            }
+            ImportMode::Atomic => {}
         }
 
         Ok(result)
@@ -122,12 +157,14 @@ impl<R: FeedRepository> ImportExportService<R> {
             ..Default::default()
         });
 
-        for feed in feeds {
+        // Watched pages aren't feeds, and other readers can't subscribe to them
+        for feed in feeds.into_iter().filter(|f| f.source_type != SourceType::PageWatch) {
             let outline = Outline {
                 text: feed.title.clone(),
-                r#type: Some("rss".to_string()),
-                xml_url: Some(feed.feed_url.clone()),
-                html_url: Some(feed.url.clone()),
+                r#type: Some(feed.feed_type.as_str().to_string()),
+                // Credentials live in the feed's settings, never in the export
+                xml_url: Some(redact_url(&feed.feed_url)),
+                html_url: Some(redact_url(&feed.url)),
                 title: Some(feed.title),
                 ..Default::default()
             };
@@ -142,9 +179,8 @@ impl<R: FeedRepository> ImportExportService<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{FeedType, SourceType};
+    use crate::domain::FeedType;
     use crate::storage::sqlite::{SqliteFeedRepository, SqliteStorage};
-    use crate::storage::traits::FeedRepository as _;
 
     fn setup() -> ImportExportService<SqliteFeedRepository> {
         let storage = SqliteStorage::in_memory().unwrap();
@@ -162,6 +198,63 @@ mod tests {
         assert!(opml.contains("<opml"));
     }
 
+    #[test]
+    fn test_export_writes_feed_type() {
+        let service = setup();
+
+        let feed = Feed::new(
+            "https://example.org".to_string(),
+            "https://example.org/feed.json".to_string(),
+            "JSON Blog".to_string(),
+            FeedType::Json,
+            SourceType::RssAtom,
+        );
+        service.repository.add(&feed).unwrap();
+
+        let opml = service.export_opml().unwrap();
+        assert!(opml.contains(r#"type="json""#));
+        assert!(!opml.contains(r#"type="rss""#));
+    }
+
+    #[test]
+    fn test_export_leaves_out_watched_pages() {
+        let service = setup();
+
+        let page = Feed::new(
+            "https://status.example.com".to_string(),
+            "https://status.example.com".to_string(),
+            "Status".to_string(),
+            FeedType::Html,
+            SourceType::PageWatch,
+        );
+        service.repository.add(&page).unwrap();
+
+        let opml = service.export_opml().unwrap();
+        assert!(!opml.contains("status.example.com"));
+        assert!(!opml.contains(r#"type="html""#));
+    }
+
+    #[test]
+    fn test_export_leaves_out_credentials() {
+        let service = setup();
+
+        let mut feed = Feed::new(
+            "https://me:pw@git.example.com/me".to_string(),
+            "https://me:pw@git.example.com/me.atom".to_string(),
+            "Gitea".to_string(),
+            FeedType::Atom,
+            SourceType::RssAtom,
+        );
+        feed.settings.request.auth = Some(crate::domain::FeedAuth::Bearer {
+            token: "t0ken".to_string(),
+        });
+        service.repository.add(&feed).unwrap();
+
+        let opml = service.export_opml().unwrap();
+        assert!(opml.contains("https://git.example.com/me.atom"));
+        assert!(!opml.contains("pw@") && !opml.contains("t0ken"));
+    }
+
     #[test]
     fn test_extract_feed_urls() {
         let service = setup();
@@ -189,4 +282,39 @@ mod tests {
         assert!(urls.contains(&"https://example1.com/feed".to_string()));
         assert!(urls.contains(&"https://example2.com/feed".to_string()));
     }
+
+    #[test]
+    fn test_atomic_import_adds_nothing_when_a_feed_fails() {
+        let service = setup();
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/good.xml")
+            .with_header("content-type", "application/rss+xml")
+            .with_body(
+                r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Good</title>
+                <link>https://example.com</link><description>d</description></channel></rss>"#,
+            )
+            .create();
+        server.mock("GET", "/gone.xml").with_status(404).create();
+
+        let opml = format!(
+            r#"<?xml version="1.0"?><opml version="2.0"><head><title>t</title></head><body>
+            <outline text="Good" xmlUrl="{0}/good.xml"/>
+            <outline text="Good again" xmlUrl="{0}/good.xml"/>
+            <outline text="Gone" xmlUrl="{0}/gone.xml"/>
+            </body></opml>"#,
+            server.url()
+        );
+
+        let result = service.import_opml(&opml, ImportMode::Atomic).unwrap();
+        assert!(result.added.is_empty());
+        assert_eq!(result.invalid.len(), 1);
+        assert_eq!(result.duplicates.len(), 1);
+        assert!(service.repository.get_all().unwrap().is_empty());
+
+        let result = service.import_opml(&opml, ImportMode::BestEffort).unwrap();
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].title, "Good");
+        assert_eq!(result.invalid.len(), 1);
+    }
 }
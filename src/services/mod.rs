@@ -4,6 +4,6 @@ pub mod notification_service;
 pub mod import_export_service;
 
 pub use feed_service::FeedService;
-pub use fetch_service::{FetchResult, FetchService};
-pub use notification_service::NotificationService;
-pub use import_export_service::ImportExportService;
+pub use fetch_service::{FeedUpdate, FetchResult, FetchService};
+pub use notification_service::{NotificationService, SinkFailure};
+pub use import_export_service::{ImportExportService, ImportMode};
@@ -1,59 +1,181 @@
-use channels::ChannelClient;
+use std::time::Duration;
 
 use crate::config::Config;
-use crate::domain::Notification;
+use crate::domain::{Notification, PendingNotification};
 use crate::errors::{FeederError, FeederResult};
+use crate::sinks::{
+    DeliveryMode, DiscordSink, EmailSink, ExecSink, GotifySink, MatrixSink, NotebrookSink, NotificationSink,
+    NtfySink, Route, SlackSink, TelegramSink, WebhookSink,
+};
+
+/// Delay before the first retry when the server didn't ask for a specific one
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait before a retry within a run; a server asking to wait longer is sent to again
+/// by a later run
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A sink that failed to take a notification, and why
+#[derive(Debug)]
+pub struct SinkFailure {
+    /// `NotificationSink::id` of the sink
+    pub sink: String,
+    pub error: FeederError,
+}
+
+impl SinkFailure {
+    /// The notification to keep for this sink, for a later run to send it again, when
+    /// sending later might succeed
+    pub fn pending(&self, notification: &Notification) -> Option<PendingNotification> {
+        if !self.error.is_retryable() {
+            return None;
+        }
+
+        let mut pending = PendingNotification::new(self.sink.clone(), notification.clone());
+        pending.not_before = self.not_before();
+        Some(pending)
+    }
+
+    /// Whether the sink delivered the notification after all, only not all of it
+    pub fn is_partial(&self) -> bool {
+        matches!(self.error, FeederError::PartlyDelivered { .. })
+    }
+
+    /// Until when the sink asked to be left alone, if it did
+    pub fn not_before(&self) -> Option<String> {
+        self.error
+            .retry_after()
+            .map(|delay| (chrono::Utc::now() + delay).format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+}
 
 pub struct NotificationService {
-    client: ChannelClient,
-    channel: String,
+    sinks: Vec<Box<dyn NotificationSink>>,
+    retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
 }
 
 impl NotificationService {
     pub fn new(config: &Config) -> FeederResult<Self> {
-        let client = ChannelClient::new(&config.notebrook_url, &config.notebrook_token)?;
+        let mut sinks: Vec<Box<dyn NotificationSink>> =
+            vec![Box::new(NotebrookSink::new(config)?)];
 
-        Ok(Self {
-            client,
-            channel: config.notebrook_channel.clone(),
-        })
-    }
+        if let Some(webhook) = &config.webhook {
+            for url in &webhook.urls {
+                sinks.push(Box::new(WebhookSink::new(webhook, url, &config.http)?));
+            }
+        }
 
-    /// Send a notification to notebrook, truncating text if too large
-    pub fn send(&self, notification: &Notification) -> FeederResult<()> {
-        // Try with full message first
-        let message = notification.format();
-        match self.client.send_message(&self.channel, &message) {
-            Ok(_) => return Ok(()),
-            Err(channels::ChannelError::PayloadTooLarge) => {}
-            Err(e) => return Err(e.into()),
+        if let Some(matrix) = &config.matrix {
+            sinks.push(Box::new(MatrixSink::new(matrix, &config.http)?));
         }
 
-        // Message too large, try truncating the text
-        let mut truncated = notification.clone();
+        if let Some(email) = &config.email {
+            sinks.push(Box::new(EmailSink::new(email)?));
+        }
 
-        // Binary search for max text length that fits
-        let mut high = truncated.text.len();
+        if let Some(ntfy) = &config.ntfy {
+            sinks.push(Box::new(NtfySink::new(ntfy, &config.http)?));
+        }
 
-        while high > 0 {
-            let mid = high / 2;
-            truncated.text = truncate_to_char_boundary(&notification.text, mid);
+        if let Some(gotify) = &config.gotify {
+            sinks.push(Box::new(GotifySink::new(gotify, &config.http)?));
+        }
 
-            let message = truncated.format();
-            match self.client.send_message(&self.channel, &message) {
-                Ok(_) => return Ok(()),
-                Err(channels::ChannelError::PayloadTooLarge) => {
-                    high = mid;
+        if let Some(telegram) = &config.telegram {
+            sinks.push(Box::new(TelegramSink::new(telegram, &config.http)?));
+        }
+
+        if let Some(exec) = &config.exec {
+            sinks.push(Box::new(ExecSink::new(exec)));
+        }
+
+        for url in &config.discord_webhooks {
+            sinks.push(Box::new(DiscordSink::new(url, &config.http)?));
+        }
+
+        for url in &config.slack_webhooks {
+            sinks.push(Box::new(SlackSink::new(url, &config.http)?));
+        }
+
+        let mut service = Self::with_sinks(sinks, config.notify_retries);
+        service.max_delay = Duration::from_secs(config.http.max_backoff_secs);
+        Ok(service)
+    }
+
+    pub fn with_sinks(sinks: Vec<Box<dyn NotificationSink>>, retries: u32) -> Self {
+        Self {
+            sinks,
+            retries,
+            base_delay: BASE_RETRY_DELAY,
+            max_delay: MAX_RETRY_DELAY,
+        }
+    }
+
+    /// IDs of the sinks delivering in `mode`
+    pub fn sink_ids(&self, mode: DeliveryMode) -> Vec<String> {
+        self.sinks
+            .iter()
+            .filter(|s| s.mode() == mode)
+            .map(|s| s.id())
+            .collect()
+    }
+
+    /// Send a notification to every per-notification sink.
+    ///
+    /// Each sink is retried on retryable errors. Once any sink delivered it, the sinks that
+    /// still failed are returned rather than failing the send, so the article is marked
+    /// and the notification kept for just those sinks (see [`SinkFailure::pending`]).
+    /// Sinks that delivered it only in part count as delivered and are returned too.
+    /// Only when every sink failed is the first failure returned, leaving the article
+    /// unnotified.
+    pub fn send(
+        &self,
+        notification: &Notification,
+        route: &Route,
+    ) -> FeederResult<Vec<SinkFailure>> {
+        let mut delivered = false;
+        let mut failed = Vec::new();
+
+        for sink in self.sinks.iter().filter(|s| s.mode() == DeliveryMode::Each) {
+            match self.with_retries(|| sink.send(notification, route)) {
+                Ok(()) => delivered = true,
+                Err(error) => {
+                    let failure = SinkFailure { sink: sink.id(), error };
+                    delivered |= failure.is_partial();
+                    failed.push(failure);
                 }
-                Err(e) => return Err(e.into()),
             }
         }
 
-        // Try with no text at all
-        truncated.text = String::new();
-        let message = truncated.format();
-        self.client.send_message(&self.channel, &message)?;
-        Ok(())
+        if !delivered && !failed.is_empty() {
+            return Err(failed.remove(0).error);
+        }
+
+        Ok(failed)
+    }
+
+    /// Send a notification to the sink with ID `sink` only, as when it failed to take
+    /// it before
+    pub fn send_to(
+        &self,
+        sink: &str,
+        notification: &Notification,
+        route: &Route,
+    ) -> FeederResult<()> {
+        let sink = self.sink(sink)?;
+        self.with_retries(|| sink.send(notification, route))
+    }
+
+    /// Send one digest of `notifications` to the sink with ID `sink`
+    pub fn send_digest_to(&self, sink: &str, notifications: &[Notification]) -> FeederResult<()> {
+        if notifications.is_empty() {
+            return Ok(());
+        }
+
+        let sink = self.sink(sink)?;
+        self.with_retries(|| sink.send_digest(notifications))
     }
 
     /// Send multiple notifications
@@ -61,16 +183,239 @@ impl NotificationService {
         let mut errors = Vec::new();
 
         for notification in notifications {
-            if let Err(e) = self.send(notification) {
-                errors.push(e);
+            match self.send(notification, &Route::default()) {
+                Ok(failed) => errors.extend(failed.into_iter().map(|failure| failure.error)),
+                Err(e) => errors.push(e),
             }
         }
 
         Ok(errors)
     }
+
+    fn sink(&self, id: &str) -> FeederResult<&dyn NotificationSink> {
+        self.sinks
+            .iter()
+            .find(|s| s.id() == id)
+            .map(|s| s.as_ref())
+            .ok_or_else(|| FeederError::Notification(format!("{} is no longer configured", id)))
+    }
+
+    /// Run `send`, retrying retryable errors with exponential backoff, capped at
+    /// `max_delay`, or after the server's `Retry-After`. A server asking to wait longer
+    /// than `max_delay` gets the error back at once, to be kept pending past the run
+    /// (see [`SinkFailure::pending`]) rather than sent again too early and dropped.
+    fn with_retries<F>(&self, send: F) -> FeederResult<()>
+    where
+        F: Fn() -> FeederResult<()>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            match send() {
+                Ok(()) => return Ok(()),
+                Err(e) if e.is_retryable() && attempt < self.retries => {
+                    let delay = match e.retry_after() {
+                        Some(delay) if delay > self.max_delay => return Err(e),
+                        Some(delay) => delay,
+                        None => (self.base_delay * 2u32.pow(attempt)).min(self.max_delay),
+                    };
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
-/// Truncate string to at most `max_chars` characters, respecting char boundaries
-fn truncate_to_char_boundary(s: &str, max_chars: usize) -> String {
-    s.chars().take(max_chars).collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Sink failing with a given status a fixed number of times before succeeding
+    struct FlakySink {
+        name: &'static str,
+        mode: DeliveryMode,
+        status: u16,
+        failures: u32,
+        /// `Retry-After` seconds sent with each failure
+        retry_after: Option<u64>,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl NotificationSink for FlakySink {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn mode(&self) -> DeliveryMode {
+            self.mode
+        }
+
+        fn send(&self, _notification: &Notification, _route: &Route) -> FeederResult<()> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                return Err(FeederError::SinkStatus {
+                    sink: "flaky".to_string(),
+                    status: self.status,
+                    retry_after: self.retry_after,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    fn service(sink: FlakySink, retries: u32) -> NotificationService {
+        let mut service = NotificationService::with_sinks(vec![Box::new(sink)], retries);
+        service.base_delay = Duration::from_millis(1);
+        service.max_delay = Duration::from_millis(10);
+        service
+    }
+
+    fn flaky(mode: DeliveryMode, status: u16, failures: u32) -> (FlakySink, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let sink = FlakySink {
+            name: "flaky",
+            mode,
+            status,
+            failures,
+            retry_after: None,
+            calls: calls.clone(),
+        };
+        (sink, calls)
+    }
+
+    fn notification() -> Notification {
+        Notification {
+            feed_title: "Blog".to_string(),
+            article_title: "Title".to_string(),
+            links: vec![],
+            ..Notification::test_default()
+        }
+    }
+
+    #[test]
+    fn test_retryable_errors_are_retried() {
+        let (sink, calls) = flaky(DeliveryMode::Each, 503, 2);
+        let service = service(sink, 2);
+
+        service.send(&notification(), &Route::default()).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_retries_are_bounded() {
+        let (sink, calls) = flaky(DeliveryMode::Each, 429, 10);
+        let service = service(sink, 2);
+
+        let result = service.send(&notification(), &Route::default());
+        assert!(matches!(result, Err(FeederError::SinkStatus { status: 429, .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_client_errors_not_retried() {
+        let (sink, calls) = flaky(DeliveryMode::Each, 400, 1);
+        let service = service(sink, 2);
+
+        assert!(service.send(&notification(), &Route::default()).is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_long_retry_after_left_for_a_later_run() {
+        let (mut sink, calls) = flaky(DeliveryMode::Each, 429, 1);
+        sink.retry_after = Some(3600);
+        let service = service(sink, 1);
+
+        let started = std::time::Instant::now();
+        let error = service.send(&notification(), &Route::default()).unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Kept for the sink, not to be sent again before the hour is up
+        let failure = SinkFailure {
+            sink: "flaky".to_string(),
+            error,
+        };
+        let pending = failure.pending(&notification()).unwrap();
+        let soon = (chrono::Utc::now() + Duration::from_secs(3000)).format("%Y-%m-%d %H:%M:%S");
+        assert!(!pending.is_due(&soon.to_string()));
+    }
+
+    #[test]
+    fn test_one_failing_sink_does_not_fail_the_send() {
+        let (broken, broken_calls) = flaky(DeliveryMode::Each, 400, u32::MAX);
+        let (working, working_calls) = flaky(DeliveryMode::Each, 200, 0);
+        let service = NotificationService::with_sinks(vec![Box::new(broken), Box::new(working)], 2);
+
+        let failed = service.send(&notification(), &Route::default()).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].sink, "flaky");
+        assert!(matches!(failed[0].error, FeederError::SinkStatus { status: 400, .. }));
+        // A client error would fail again, so it isn't kept to be sent again
+        assert!(failed[0].pending(&notification()).is_none());
+        assert_eq!(broken_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(working_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_digest_sinks_skipped_by_send() {
+        let (sink, calls) = flaky(DeliveryMode::Digest, 200, 0);
+        let service = service(sink, 0);
+
+        assert_eq!(service.sink_ids(DeliveryMode::Digest), ["flaky"]);
+        service.send(&notification(), &Route::default()).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        service.send_digest_to("flaky", &[notification(), notification()]).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_failed_sink_kept_pending_alone() {
+        let (mut broken, broken_calls) = flaky(DeliveryMode::Each, 503, u32::MAX);
+        broken.name = "broken";
+        let (working, working_calls) = flaky(DeliveryMode::Each, 200, 0);
+        let sinks: Vec<Box<dyn NotificationSink>> = vec![Box::new(working), Box::new(broken)];
+        let mut service = NotificationService::with_sinks(sinks, 1);
+        service.base_delay = Duration::from_millis(1);
+
+        let failed = service.send(&notification(), &Route::default()).unwrap();
+        let pending = failed[0].pending(&notification()).unwrap();
+        assert_eq!(pending.sink, "broken");
+        assert_eq!(working_calls.load(Ordering::SeqCst), 1);
+
+        // Sending it again only goes to the sink it is pending for
+        assert!(service.send_to(&pending.sink, &pending.notification, &Route::default()).is_err());
+        assert_eq!(broken_calls.load(Ordering::SeqCst), 4);
+        assert!(service.send_to("gone", &pending.notification, &Route::default()).is_err());
+    }
+
+    #[test]
+    fn test_partly_delivered_counts_as_delivered() {
+        struct WithoutImage;
+
+        impl NotificationSink for WithoutImage {
+            fn name(&self) -> &str {
+                "partial"
+            }
+
+            fn send(&self, _notification: &Notification, _route: &Route) -> FeederResult<()> {
+                Err(FeederError::PartlyDelivered {
+                    sink: "partial".to_string(),
+                    message: "not its image".to_string(),
+                })
+            }
+        }
+
+        let service = NotificationService::with_sinks(vec![Box::new(WithoutImage)], 3);
+
+        let failed = service.send(&notification(), &Route::default()).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].is_partial());
+        assert!(failed[0].pending(&notification()).is_none());
+    }
 }
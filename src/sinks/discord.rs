@@ -0,0 +1,203 @@
+use chrono::DateTime;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::HttpConfig;
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::format::{snippet, truncate_to_char_boundary};
+use crate::sinks::response::{check_status, retry_after_header};
+use crate::sinks::traits::{target_id, NotificationSink, Route};
+
+/// Discord's limit for embed titles
+const MAX_TITLE_CHARS: usize = 256;
+/// Descriptions may hold 4096 characters, but a notification only shows a snippet
+const MAX_DESCRIPTION_CHARS: usize = 500;
+
+/// Body of Discord 429 responses; `retry_after` is in (fractional) seconds
+#[derive(Debug, Deserialize)]
+struct RateLimited {
+    retry_after: Option<f64>,
+}
+
+/// Posts notifications to a Discord incoming webhook as rich embeds
+pub struct DiscordSink {
+    client: Client,
+    url: String,
+}
+
+impl DiscordSink {
+    pub fn new(url: &str, http: &HttpConfig) -> FeederResult<Self> {
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+
+    fn body(notification: &Notification) -> Value {
+        let mut embed = json!({
+            "author": { "name": truncate_to_char_boundary(&notification.feed_title, MAX_TITLE_CHARS) },
+            "title": truncate_to_char_boundary(&notification.article_title, MAX_TITLE_CHARS),
+        });
+
+        if let Some(link) = notification.links.first() {
+            embed["url"] = json!(link);
+        }
+
+        if !notification.text.is_empty() {
+            embed["description"] = json!(snippet(&notification.text, MAX_DESCRIPTION_CHARS));
+        }
+
+        if let Some(timestamp) = notification
+            .published
+            .as_deref()
+            .and_then(|p| DateTime::parse_from_rfc3339(p).ok())
+        {
+            embed["timestamp"] = json!(timestamp.to_rfc3339());
+        }
+
+        json!({ "embeds": [embed] })
+    }
+}
+
+impl NotificationSink for DiscordSink {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    fn id(&self) -> String {
+        target_id(self.name(), &self.url)
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        // The webhook URL is the secret, so keep it out of the error
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&Self::body(notification))
+            .send()
+            .map_err(|e| FeederError::Http(e.without_url()))?;
+
+        // The body's retry_after is more precise than the header and also covers global limits
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let header = retry_after_header(&response);
+            let retry_after = response
+                .json::<RateLimited>()
+                .ok()
+                .and_then(|r| r.retry_after)
+                .map(|secs| secs.ceil() as u64)
+                .or(header);
+
+            return Err(FeederError::SinkStatus {
+                sink: "discord".to_string(),
+                status: 429,
+                retry_after,
+            });
+        }
+
+        check_status("discord", response)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification() -> Notification {
+        Notification {
+            text: "The Rust team is happy to announce a new version".to_string(),
+            published: Some("2023-12-28T00:00:00+00:00".to_string()),
+            ..Notification::test_default()
+        }
+    }
+
+    #[test]
+    fn test_embed_body() {
+        assert_eq!(
+            DiscordSink::body(&notification()),
+            json!({
+                "embeds": [{
+                    "author": { "name": "Rust Blog" },
+                    "title": "Announcing Rust 1.75.0",
+                    "url": "https://blog.rust-lang.org/1.75",
+                    "description": "The Rust team is happy to announce a new version",
+                    "timestamp": "2023-12-28T00:00:00+00:00",
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_embed_skips_missing_fields() {
+        let mut notification = notification();
+        notification.text = String::new();
+        notification.links.clear();
+        notification.published = Some("yesterday".to_string());
+
+        let body = DiscordSink::body(&notification);
+        let embed = body["embeds"][0].as_object().unwrap();
+
+        assert!(!embed.contains_key("url"));
+        assert!(!embed.contains_key("description"));
+        assert!(!embed.contains_key("timestamp"));
+    }
+
+    #[test]
+    fn test_send_to_mock_webhook() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/api/webhooks/1/abc")
+            .match_body(Matcher::PartialJson(json!({
+                "embeds": [{ "title": "Announcing Rust 1.75.0" }]
+            })))
+            .with_status(204)
+            .create();
+
+        let sink = DiscordSink::new(
+            &format!("{}/api/webhooks/1/abc", server.url()),
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        sink.send(&notification(), &Route::default()).unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_rate_limit_from_body() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", Matcher::Any)
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .with_body(r#"{"message": "You are being rate limited.", "retry_after": 2.4, "global": false}"#)
+            .create();
+
+        let err = DiscordSink::new(&server.url(), &HttpConfig::default())
+            .unwrap()
+            .send(&notification(), &Route::default())
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            FeederError::SinkStatus { status: 429, retry_after: Some(3), .. }
+        ));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_connection_error_hides_url() {
+        let err = DiscordSink::new("http://127.0.0.1:1/secret-token", &HttpConfig::default())
+            .unwrap()
+            .send(&notification(), &Route::default())
+            .unwrap_err();
+
+        assert!(!err.to_string().contains("secret-token"));
+    }
+}
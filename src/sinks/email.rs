@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::config::{EmailConfig, SmtpTls};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::sinks::format::{escape_html, to_html};
+use crate::sinks::traits::{DeliveryMode, NotificationSink, Route};
+
+/// Emails notifications, or one digest per run, to a list of recipients over SMTP
+pub struct EmailSink {
+    transport: SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    mode: DeliveryMode,
+}
+
+impl EmailSink {
+    pub fn new(config: &EmailConfig) -> FeederResult<Self> {
+        let builder = match config.tls {
+            SmtpTls::StartTls => SmtpTransport::starttls_relay(&config.host),
+            SmtpTls::Tls => SmtpTransport::relay(&config.host),
+            SmtpTls::None => Ok(SmtpTransport::builder_dangerous(&config.host)),
+        }
+        .map_err(|e| FeederError::Config(format!("Invalid SMTP settings: {}", e)))?;
+
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(30)));
+
+        if let Some(username) = &config.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        let from = parse_mailbox(&config.from)?;
+        let to = config
+            .to
+            .iter()
+            .map(|address| parse_mailbox(address))
+            .collect::<FeederResult<Vec<_>>>()?;
+
+        if to.is_empty() {
+            return Err(FeederError::Config("No email recipients configured".to_string()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            mode: config.mode,
+        })
+    }
+
+    fn build_message(&self, subject: &str, plain: String, html: String) -> FeederResult<Message> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for recipient in &self.to {
+            builder = builder.to(recipient.clone());
+        }
+
+        builder
+            .multipart(MultiPart::alternative_plain_html(plain, html))
+            .map_err(|e| FeederError::Notification(format!("Could not build email: {}", e)))
+    }
+
+    fn single_message(&self, notification: &Notification) -> FeederResult<Message> {
+        let subject = format!("{}: {}", notification.feed_title, notification.article_title);
+        let html = format!("<p>{}</p>", to_html(notification));
+
+        self.build_message(&subject, notification.format(), html)
+    }
+
+    fn digest_message(&self, notifications: &[Notification]) -> FeederResult<Message> {
+        let subject = match notifications.len() {
+            1 => "1 new article".to_string(),
+            n => format!("{} new articles", n),
+        };
+
+        let plain = notifications
+            .iter()
+            .map(|n| format!("- {}", n.format()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let items = notifications
+            .iter()
+            .map(|n| format!("<li>{}</li>", to_html(n)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let html = format!("<h1>{}</h1>\n<ul>\n{}\n</ul>", escape_html(&subject), items);
+
+        self.build_message(&subject, plain, html)
+    }
+
+    fn deliver(&self, message: &Message) -> FeederResult<()> {
+        self.transport.send(message).map(|_| ()).map_err(|e| {
+            if e.is_transient() || e.is_timeout() {
+                FeederError::SinkTransient {
+                    sink: "email".to_string(),
+                    message: e.to_string(),
+                }
+            } else {
+                FeederError::Notification(format!("email: {}", e))
+            }
+        })
+    }
+}
+
+impl NotificationSink for EmailSink {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn mode(&self) -> DeliveryMode {
+        self.mode
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        self.deliver(&self.single_message(notification)?)
+    }
+
+    fn send_digest(&self, notifications: &[Notification]) -> FeederResult<()> {
+        if notifications.is_empty() {
+            return Ok(());
+        }
+
+        self.deliver(&self.digest_message(notifications)?)
+    }
+}
+
+fn parse_mailbox(address: &str) -> FeederResult<Mailbox> {
+    address
+        .parse()
+        .map_err(|e| FeederError::Config(format!("Invalid email address '{}': {}", address, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Accept one SMTP session on a local port and hand back the message data
+    fn smtp_catcher() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut data = String::new();
+            let mut in_data = false;
+
+            writer.write_all(b"220 localhost ESMTP catcher\r\n").unwrap();
+
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        writer.write_all(b"250 OK queued\r\n").unwrap();
+                    } else {
+                        data.push_str(&line);
+                    }
+                } else {
+                    let command = line.to_uppercase();
+                    if command.starts_with("EHLO") || command.starts_with("HELO") {
+                        writer.write_all(b"250-localhost\r\n250 8BITMIME\r\n").unwrap();
+                    } else if command.starts_with("DATA") {
+                        in_data = true;
+                        writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").unwrap();
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").unwrap();
+                        break;
+                    } else {
+                        writer.write_all(b"250 OK\r\n").unwrap();
+                    }
+                }
+                line.clear();
+            }
+
+            tx.send(data).ok();
+        });
+
+        (port, rx)
+    }
+
+    fn config(port: u16, mode: DeliveryMode) -> EmailConfig {
+        EmailConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: SmtpTls::None,
+            username: None,
+            password: None,
+            from: "Feeder <feeder@example.com>".to_string(),
+            to: vec!["team@example.com".to_string(), "archive@example.com".to_string()],
+            mode,
+        }
+    }
+
+    fn notification(title: &str) -> Notification {
+        Notification {
+            article_title: title.to_string(),
+            links: vec!["https://blog.rust-lang.org/post".to_string()],
+            ..Notification::test_default()
+        }
+    }
+
+    #[test]
+    fn test_single_message_is_multipart() {
+        let sink = EmailSink::new(&config(25, DeliveryMode::Each)).unwrap();
+        let message = sink.single_message(&notification("Hello")).unwrap();
+        let message = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(message.contains("Subject: Rust Blog: Hello"));
+        assert!(message.contains("To: team@example.com, archive@example.com"));
+        assert!(message.contains("multipart/alternative"));
+        assert!(message.contains("text/plain"));
+        assert!(message.contains("text/html"));
+        assert!(message.contains("<a href=\"https://blog.rust-lang.org/post\">Hello</a>"));
+    }
+
+    #[test]
+    fn test_digest_lists_every_article() {
+        let sink = EmailSink::new(&config(25, DeliveryMode::Digest)).unwrap();
+        let message = sink
+            .digest_message(&[notification("First"), notification("Second")])
+            .unwrap();
+        let message = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(message.contains("Subject: 2 new articles"));
+        assert!(message.contains("- Rust Blog First https://blog.rust-lang.org/post"));
+        assert!(message.contains("- Rust Blog Second https://blog.rust-lang.org/post"));
+    }
+
+    #[test]
+    fn test_invalid_address_rejected() {
+        let mut config = config(25, DeliveryMode::Each);
+        config.to = vec!["not an address".to_string()];
+
+        assert!(matches!(EmailSink::new(&config), Err(FeederError::Config(_))));
+    }
+
+    #[test]
+    fn test_send_to_local_smtp_catcher() {
+        let (port, received) = smtp_catcher();
+        let sink = EmailSink::new(&config(port, DeliveryMode::Each)).unwrap();
+
+        sink.send(&notification("Delivered"), &Route::default()).unwrap();
+
+        let data = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(data.contains("Subject: Rust Blog: Delivered"));
+        assert!(data.contains("Rust Blog Delivered https://blog.rust-lang.org/post"));
+    }
+}
@@ -0,0 +1,325 @@
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::ExecConfig;
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::sinks::format::snippet;
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Captured output kept in error messages
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// Version of the stdin JSON, bumped whenever a field changes incompatibly
+const PAYLOAD_VERSION: u32 = 1;
+
+/// The JSON a command reads on stdin, kept apart from `Notification` so internal
+/// changes don't break scripts
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    version: u32,
+    feed_title: &'a str,
+    article_title: &'a str,
+    text: &'a str,
+    links: &'a [String],
+    published: Option<&'a str>,
+}
+
+impl<'a> Payload<'a> {
+    fn new(notification: &'a Notification) -> Self {
+        Self {
+            version: PAYLOAD_VERSION,
+            feed_title: &notification.feed_title,
+            article_title: &notification.article_title,
+            text: &notification.text,
+            links: &notification.links,
+            published: notification.published.as_deref(),
+        }
+    }
+}
+
+/// Runs a shell command for each notification.
+///
+/// The notification is written to stdin as a versioned JSON [`Payload`] and exposed as
+/// `FEEDER_*` environment variables. Exit code 0 means delivered; anything else is a
+/// retryable failure carrying the captured output. A command running past the timeout
+/// is killed along with everything it started, and not retried.
+pub struct ExecSink {
+    command: String,
+    timeout: Duration,
+}
+
+impl ExecSink {
+    pub fn new(config: &ExecConfig) -> Self {
+        Self {
+            command: config.command.clone(),
+            timeout: Duration::from_secs(config.timeout_secs),
+        }
+    }
+
+    #[cfg(windows)]
+    fn shell(&self) -> Command {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(&self.command);
+        command
+    }
+
+    /// The shell leads its own process group, so a timeout can kill its children too
+    #[cfg(unix)]
+    fn shell(&self) -> Command {
+        use std::os::unix::process::CommandExt;
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&self.command).process_group(0);
+        command
+    }
+
+    fn failure(&self, message: String) -> FeederError {
+        FeederError::SinkTransient {
+            sink: "exec".to_string(),
+            message,
+        }
+    }
+}
+
+impl NotificationSink for ExecSink {
+    fn name(&self) -> &str {
+        "exec"
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        let input = serde_json::to_vec(&Payload::new(notification)).map_err(|e| {
+            FeederError::Notification(format!("Could not encode notification: {}", e))
+        })?;
+
+        let mut child = self
+            .shell()
+            .envs(environment(notification))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| FeederError::Notification(format!("Cannot run '{}': {}", self.command, e)))?;
+
+        // Feed stdin and drain the pipes on threads, so a chatty or non-reading
+        // command can't block us while we wait for it
+        let mut stdin = child.stdin.take();
+        let writer = std::thread::spawn(move || {
+            if let Some(stdin) = stdin.as_mut() {
+                // The command may exit without reading its input; that's not an error
+                let _ = stdin.write_all(&input);
+            }
+        });
+        let stdout = capture(child.stdout.take());
+        let stderr = capture(child.stderr.take());
+
+        // A command that hangs would most likely hang again, so a timeout isn't retried.
+        // The capture threads are left to finish on their own, in case something outside
+        // the process group still holds the pipes open.
+        let status = match wait_with_timeout(&mut child, self.timeout)? {
+            Some(status) => status,
+            None => {
+                return Err(FeederError::Notification(format!(
+                    "'{}' timed out after {}s",
+                    self.command,
+                    self.timeout.as_secs()
+                )))
+            }
+        };
+
+        let _ = writer.join();
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if status.success() {
+            return Ok(());
+        }
+
+        let output = if stderr.trim().is_empty() { stdout } else { stderr };
+        Err(self.failure(format!(
+            "'{}' exited with {}: {}",
+            self.command,
+            status,
+            snippet(&output, MAX_OUTPUT_CHARS)
+        )))
+    }
+}
+
+/// Variables describing the notification, for commands that don't parse JSON
+fn environment(notification: &Notification) -> Vec<(&'static str, String)> {
+    vec![
+        ("FEEDER_FEED_TITLE", notification.feed_title.clone()),
+        ("FEEDER_ARTICLE_TITLE", notification.article_title.clone()),
+        ("FEEDER_ARTICLE_TEXT", notification.text.clone()),
+        (
+            "FEEDER_ARTICLE_URL",
+            notification.links.first().cloned().unwrap_or_default(),
+        ),
+        ("FEEDER_ARTICLE_LINKS", notification.links.join(" ")),
+        (
+            "FEEDER_ARTICLE_PUBLISHED",
+            notification.published.clone().unwrap_or_default(),
+        ),
+        ("FEEDER_MESSAGE", notification.format()),
+    ]
+}
+
+/// Read a pipe to the end on a background thread
+fn capture<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+/// Wait for the child to exit; `None` when it was killed for running too long
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> FeederResult<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            kill(child)?;
+            child.wait()?;
+            return Ok(None);
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Kill the command's whole process group, not just the shell
+#[cfg(unix)]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    // SAFETY: kill(2) has no memory effects; the group id is the shell's own pid
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        child.kill()
+    }
+}
+
+#[cfg(windows)]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    child.kill()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::services::NotificationService;
+
+    fn notification() -> Notification {
+        Notification {
+            feed_title: "Podcast".to_string(),
+            article_title: "Episode 12".to_string(),
+            links: vec!["https://example.com/ep12.mp3".to_string()],
+            ..Notification::test_default()
+        }
+    }
+
+    fn sink(command: &str, timeout_secs: u64) -> ExecSink {
+        ExecSink::new(&ExecConfig {
+            command: command.to_string(),
+            timeout_secs,
+        })
+    }
+
+    #[test]
+    fn test_passes_json_and_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let command = format!(
+            "cat > {0}.json && printf '%s|%s' \"$FEEDER_FEED_TITLE\" \"$FEEDER_ARTICLE_URL\" > {0}.env",
+            out.display()
+        );
+
+        sink(&command, 5).send(&notification(), &Route::default()).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(out.with_extension("json")).unwrap())
+                .unwrap();
+        assert_eq!(json["version"], PAYLOAD_VERSION);
+        assert_eq!(json["article_title"], "Episode 12");
+        assert_eq!(json["links"][0], "https://example.com/ep12.mp3");
+        assert_eq!(
+            std::fs::read_to_string(out.with_extension("env")).unwrap(),
+            "Podcast|https://example.com/ep12.mp3"
+        );
+    }
+
+    #[test]
+    fn test_non_zero_exit_is_retryable_with_output() {
+        let err = sink("echo 'disk full' >&2; exit 3", 5)
+            .send(&notification(), &Route::default())
+            .unwrap_err();
+
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("disk full"));
+    }
+
+    /// Stands in for Notebrook, which takes every notification
+    struct Delivered;
+
+    impl NotificationSink for Delivered {
+        fn name(&self) -> &str {
+            "delivered"
+        }
+
+        fn send(&self, _notification: &Notification, _route: &Route) -> FeederResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_non_zero_exit_kept_pending_when_other_sinks_delivered() {
+        let sinks: Vec<Box<dyn NotificationSink>> =
+            vec![Box::new(Delivered), Box::new(sink("exit 1", 5))];
+        let service = NotificationService::with_sinks(sinks, 0);
+
+        let failed = service.send(&notification(), &Route::default()).unwrap();
+
+        let pending = failed[0].pending(&notification()).unwrap();
+        assert_eq!(pending.sink, "exec");
+        assert_eq!(pending.notification.article_title, "Episode 12");
+    }
+
+    #[test]
+    fn test_timeout_kills_command_and_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = Instant::now();
+        let err = sink(&command, 1).send(&notification(), &Route::default()).unwrap_err();
+
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let pid: libc::pid_t = std::fs::read_to_string(&pid_file).unwrap().trim().parse().unwrap();
+        // The killed sleep may linger as a zombie until reaped, which signal 0 can't tell apart
+        let alive = (0..40).all(|_| {
+            std::thread::sleep(Duration::from_millis(25));
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+            !stat.is_empty() && !stat.contains(") Z ")
+        });
+        assert!(!alive, "background child survived the timeout");
+    }
+}
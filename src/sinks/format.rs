@@ -0,0 +1,165 @@
+use crate::domain::Notification;
+
+/// Escape text for inclusion in HTML bodies and attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// HTML counterpart of `Notification::format`: the article title links to the first
+/// URL and any further links follow as anchors. Uses only `<b>` and `<a>` so it stays
+/// valid for clients that support a small subset of HTML.
+pub fn to_html(notification: &Notification) -> String {
+    let title = escape_html(&notification.article_title);
+    let mut html = format!("<b>{}</b> ", escape_html(&notification.feed_title));
+
+    match notification.links.first() {
+        Some(link) => html.push_str(&format!("<a href=\"{}\">{}</a>", escape_html(link), title)),
+        None => html.push_str(&title),
+    }
+
+    if !notification.text.is_empty() {
+        html.push_str(": ");
+        html.push_str(&escape_html(&notification.text));
+    }
+
+    for link in notification.links.iter().skip(1) {
+        let link = escape_html(link);
+        html.push_str(&format!(" <a href=\"{}\">{}</a>", link, link));
+    }
+
+    html
+}
+
+/// Truncate string to at most `max_chars` characters, respecting char boundaries
+pub(crate) fn truncate_to_char_boundary(s: &str, max_chars: usize) -> String {
+    s.chars().take(max_chars).collect()
+}
+
+/// Shorten text to `max_chars` characters for previews, ending in an ellipsis when cut
+pub fn snippet(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut cut = truncate_to_char_boundary(text, max_chars.saturating_sub(1));
+    cut.truncate(cut.trim_end().len());
+    cut.push('…');
+    cut
+}
+
+/// Split off a head of at most `max_chars` characters, preferring to break between
+/// paragraphs, then lines, then sentences, then words. Returns the head and the rest.
+pub fn split_head(text: &str, max_chars: usize) -> (&str, &str) {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return (text, "");
+    }
+
+    let head_len = text
+        .char_indices()
+        .nth(max_chars.max(1))
+        .map_or(text.len(), |(i, _)| i);
+    let head = &text[..head_len];
+
+    // Only accept a boundary in the second half, so chunks don't become tiny
+    let cut = ["\n\n", "\n", ". ", " "]
+        .iter()
+        .find_map(|sep| {
+            head.rfind(sep)
+                .filter(|&i| i >= head.len() / 2)
+                .map(|i| i + sep.len())
+        })
+        .unwrap_or(head.len());
+
+    (text[..cut].trim_end(), text[cut..].trim_start())
+}
+
+/// Split text into chunks of at most `max_chars` characters on the boundaries `split_head` uses
+pub fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let (head, tail) = split_head(rest, max_chars);
+        chunks.push(head.to_string());
+        rest = tail;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("  short  ", 10), "short");
+        assert_eq!(snippet("héllo wörld", 7), "héllo…");
+        assert_eq!(snippet("abcdef", 6), "abcdef");
+    }
+
+    #[test]
+    fn test_split_text_on_boundaries() {
+        assert_eq!(split_text("short", 10), vec!["short"]);
+        assert_eq!(
+            split_text("First paragraph.\n\nSecond one here.", 20),
+            vec!["First paragraph.", "Second one here."]
+        );
+        assert_eq!(split_text("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(split_text("ééééé", 2), vec!["éé", "éé", "é"]);
+        assert_eq!(split_head("one two three", 8), ("one two", "three"));
+    }
+
+    #[test]
+    fn test_to_html_links_title() {
+        let notification = Notification {
+            feed_title: "Blog <3".to_string(),
+            article_title: "Fish & Chips".to_string(),
+            text: "Tasty".to_string(),
+            links: vec![
+                "https://example.com/post?a=1&b=2".to_string(),
+                "https://example.com/audio.mp3".to_string(),
+            ],
+            ..Notification::test_default()
+        };
+
+        assert_eq!(
+            to_html(&notification),
+            "<b>Blog &lt;3</b> <a href=\"https://example.com/post?a=1&amp;b=2\">Fish &amp; Chips</a>: Tasty \
+             <a href=\"https://example.com/audio.mp3\">https://example.com/audio.mp3</a>"
+        );
+    }
+
+    #[test]
+    fn test_to_html_without_links() {
+        let notification = Notification {
+            feed_title: "Blog".to_string(),
+            article_title: "Title".to_string(),
+            links: vec![],
+            ..Notification::test_default()
+        };
+
+        assert_eq!(to_html(&notification), "<b>Blog</b> Title");
+    }
+}
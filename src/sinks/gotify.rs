@@ -0,0 +1,170 @@
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+use crate::config::{GotifyConfig, HttpConfig};
+use crate::domain::Notification;
+use crate::errors::FeederResult;
+use crate::http::HttpClient;
+use crate::sinks::response::check_status;
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// Sends notifications to a Gotify server as markdown messages
+pub struct GotifySink {
+    client: Client,
+    url: String,
+    token: String,
+    priority: u8,
+}
+
+impl GotifySink {
+    pub fn new(config: &GotifyConfig, http: &HttpConfig) -> FeederResult<Self> {
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            url: format!("{}/message", config.url.trim_end_matches('/')),
+            token: config.token.clone(),
+            priority: config.priority,
+        })
+    }
+
+    fn body(&self, notification: &Notification, route: &Route) -> Value {
+        let mut extras = json!({
+            "client::display": { "contentType": "text/markdown" },
+        });
+
+        if let Some(link) = notification.links.first() {
+            extras["client::notification"] = json!({ "click": { "url": link } });
+        }
+
+        json!({
+            "title": format!("{}: {}", notification.feed_title, notification.article_title),
+            "message": markdown(notification),
+            "priority": gotify_priority(route.priority.unwrap_or(self.priority)),
+            "extras": extras,
+        })
+    }
+}
+
+impl NotificationSink for GotifySink {
+    fn name(&self) -> &str {
+        "gotify"
+    }
+
+    fn send(&self, notification: &Notification, route: &Route) -> FeederResult<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("X-Gotify-Key", &self.token)
+            .json(&self.body(notification, route))
+            .send()?;
+
+        check_status("gotify", response)?;
+        Ok(())
+    }
+}
+
+/// Map feeder's 1-5 scale onto Gotify's 0-10, where 8+ pops up on Android
+fn gotify_priority(priority: u8) -> u8 {
+    match priority {
+        0 | 1 => 1,
+        2 => 3,
+        3 => 5,
+        4 => 8,
+        _ => 10,
+    }
+}
+
+/// Article text followed by one markdown link per URL
+fn markdown(notification: &Notification) -> String {
+    let mut parts = Vec::new();
+
+    if !notification.text.is_empty() {
+        parts.push(notification.text.clone());
+    }
+
+    for (i, link) in notification.links.iter().enumerate() {
+        let label = if i == 0 {
+            notification.article_title.replace(['[', ']'], "")
+        } else {
+            link.clone()
+        };
+        parts.push(format!("[{}]({})", label, link));
+    }
+
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification() -> Notification {
+        Notification {
+            feed_title: "Releases".to_string(),
+            article_title: "v2.0 [stable]".to_string(),
+            text: "Big release".to_string(),
+            links: vec!["https://example.com/v2".to_string()],
+            ..Notification::test_default()
+        }
+    }
+
+    fn route(priority: u8) -> Route {
+        Route {
+            priority: Some(priority),
+            ..Route::default()
+        }
+    }
+
+    fn config(url: &str) -> GotifyConfig {
+        GotifyConfig {
+            url: url.to_string(),
+            token: "app-token".to_string(),
+            priority: 3,
+        }
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        assert_eq!(
+            markdown(&notification()),
+            "Big release\n\n[v2.0 stable](https://example.com/v2)"
+        );
+    }
+
+    #[test]
+    fn test_priority_mapping() {
+        assert_eq!(gotify_priority(1), 1);
+        assert_eq!(gotify_priority(3), 5);
+        assert_eq!(gotify_priority(5), 10);
+    }
+
+    #[test]
+    fn test_send_to_mock_server() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/message")
+            .match_header("x-gotify-key", "app-token")
+            .match_body(Matcher::Json(json!({
+                "title": "Releases: v2.0 [stable]",
+                "message": "Big release\n\n[v2.0 stable](https://example.com/v2)",
+                "priority": 8,
+                "extras": {
+                    "client::display": { "contentType": "text/markdown" },
+                    "client::notification": { "click": { "url": "https://example.com/v2" } },
+                },
+            })))
+            .with_status(200)
+            .create();
+
+        let sink = GotifySink::new(
+            &config(&format!("{}/", server.url())),
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        sink.send(&notification(), &route(4)).unwrap();
+
+        mock.assert();
+    }
+}
@@ -0,0 +1,228 @@
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::{HttpConfig, MatrixConfig};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::format::to_html;
+use crate::sinks::response::{check_status, retry_after_header};
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// Body of Matrix `M_LIMIT_EXCEEDED` errors
+#[derive(Debug, Deserialize)]
+struct RateLimited {
+    retry_after_ms: Option<u64>,
+}
+
+/// Posts notifications to a Matrix room via the client-server API
+pub struct MatrixSink {
+    client: Client,
+    homeserver: Url,
+    access_token: String,
+    room_id: String,
+}
+
+impl MatrixSink {
+    pub fn new(config: &MatrixConfig, http: &HttpConfig) -> FeederResult<Self> {
+        let homeserver = Url::parse(&config.homeserver)
+            .map_err(|e| FeederError::Config(format!("Invalid Matrix homeserver URL: {}", e)))?;
+
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            homeserver,
+            access_token: config.access_token.clone(),
+            room_id: config.room_id.clone(),
+        })
+    }
+
+    /// Transaction ID derived from the room and message, so a retried send of the same
+    /// notification is deduplicated by the homeserver instead of posted twice
+    fn transaction_id(&self, notification: &Notification) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.room_id.as_str(),
+            notification.feed_title.as_str(),
+            notification.article_title.as_str(),
+            notification.text.as_str(),
+            notification.published.as_deref().unwrap_or_default(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for link in &notification.links {
+            hasher.update(link.as_bytes());
+            hasher.update([0]);
+        }
+
+        format!("feeder-{:x}", hasher.finalize())
+    }
+
+    fn send_url(&self, txn_id: &str) -> FeederResult<Url> {
+        let mut url = self.homeserver.clone();
+        url.path_segments_mut()
+            .map_err(|_| FeederError::Config("Invalid Matrix homeserver URL".to_string()))?
+            .pop_if_empty()
+            .extend([
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                &self.room_id,
+                "send",
+                "m.room.message",
+                txn_id,
+            ]);
+        Ok(url)
+    }
+}
+
+impl NotificationSink for MatrixSink {
+    fn name(&self) -> &str {
+        "matrix"
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        let url = self.send_url(&self.transaction_id(notification))?;
+
+        let body = json!({
+            "msgtype": "m.text",
+            "body": notification.format(),
+            "format": "org.matrix.custom.html",
+            "formatted_body": to_html(notification),
+        });
+
+        let response = self
+            .client
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(&body)
+            .send()?;
+
+        // Matrix reports rate limits in the body rather than a Retry-After header
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+            && retry_after_header(&response).is_none()
+        {
+            let retry_after = response
+                .json::<RateLimited>()
+                .ok()
+                .and_then(|r| r.retry_after_ms)
+                .map(|ms| ms.div_ceil(1000));
+
+            return Err(FeederError::SinkStatus {
+                sink: "matrix".to_string(),
+                status: 429,
+                retry_after,
+            });
+        }
+
+        check_status("matrix", response)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification() -> Notification {
+        Notification::test_default()
+    }
+
+    fn sink(homeserver: &str) -> MatrixSink {
+        let config = MatrixConfig {
+            homeserver: homeserver.to_string(),
+            access_token: "syt_token".to_string(),
+            room_id: "!room:example.org".to_string(),
+        };
+
+        MatrixSink::new(&config, &HttpConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_send_url_encodes_room_id() {
+        let sink = sink("https://matrix.example.org/");
+        let url = sink.send_url("txn1").unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://matrix.example.org/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/txn1"
+        );
+    }
+
+    #[test]
+    fn test_transaction_id_stable_per_notification() {
+        let sink = sink("https://matrix.example.org");
+
+        let first = sink.transaction_id(&notification());
+        assert_eq!(first, sink.transaction_id(&notification()));
+
+        let mut other = notification();
+        other.article_title = "Something else".to_string();
+        assert_ne!(first, sink.transaction_id(&other));
+    }
+
+    #[test]
+    fn test_send_to_mock_homeserver() {
+        let mut server = mockito::Server::new();
+        let sink = sink(&server.url());
+        let txn = sink.transaction_id(&notification());
+
+        let mock = server
+            .mock(
+                "PUT",
+                format!("/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/{}", txn)
+                    .as_str(),
+            )
+            .match_header("authorization", "Bearer syt_token")
+            .match_body(Matcher::Json(json!({
+                "msgtype": "m.text",
+                "body": "Rust Blog Announcing Rust 1.75.0 https://blog.rust-lang.org/1.75",
+                "format": "org.matrix.custom.html",
+                "formatted_body": "<b>Rust Blog</b> <a href=\"https://blog.rust-lang.org/1.75\">Announcing Rust 1.75.0</a>",
+            })))
+            .with_status(200)
+            .with_body(r#"{"event_id": "$abc"}"#)
+            .expect(2)
+            .create();
+
+        // A retry reuses the transaction ID, so the homeserver can deduplicate it
+        sink.send(&notification(), &Route::default()).unwrap();
+        sink.send(&notification(), &Route::default()).unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_rate_limit_from_body() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("PUT", Matcher::Any)
+            .with_status(429)
+            .with_body(r#"{"errcode": "M_LIMIT_EXCEEDED", "retry_after_ms": 2500}"#)
+            .create();
+
+        let err = sink(&server.url()).send(&notification(), &Route::default()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            FeederError::SinkStatus { status: 429, retry_after: Some(3), .. }
+        ));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_forbidden_not_retryable() {
+        let mut server = mockito::Server::new();
+        server.mock("PUT", Matcher::Any).with_status(403).create();
+
+        let err = sink(&server.url()).send(&notification(), &Route::default()).unwrap_err();
+        assert!(!err.is_retryable());
+    }
+}
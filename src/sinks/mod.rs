@@ -0,0 +1,25 @@
+pub mod traits;
+pub mod format;
+pub(crate) mod response;
+pub mod notebrook;
+pub mod webhook;
+pub mod matrix;
+pub mod email;
+pub mod ntfy;
+pub mod gotify;
+pub mod discord;
+pub mod slack;
+pub mod telegram;
+pub mod exec;
+
+pub use traits::{DeliveryMode, NotificationSink, Route};
+pub use notebrook::NotebrookSink;
+pub use webhook::WebhookSink;
+pub use matrix::MatrixSink;
+pub use email::EmailSink;
+pub use ntfy::NtfySink;
+pub use gotify::GotifySink;
+pub use discord::DiscordSink;
+pub use slack::SlackSink;
+pub use telegram::TelegramSink;
+pub use exec::ExecSink;
@@ -0,0 +1,333 @@
+use std::io::Read;
+use std::time::Duration;
+
+use channels::ChannelClient;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+
+use crate::config::{Config, HttpConfig};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::format::truncate_to_char_boundary;
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// An image downloaded for attaching to a message
+struct Attachment {
+    file_name: String,
+    content_type: String,
+    bytes: Vec<u8>,
+}
+
+/// Posts notifications to a Notebrook channel
+pub struct NotebrookSink {
+    client: ChannelClient,
+    channel: String,
+    /// Fetches article images for feeds that attach them
+    http: HttpClient,
+    attach_max_bytes: u64,
+    attach_types: Vec<String>,
+    /// Only fetch images from public http(s) addresses; off in tests against local servers
+    public_images_only: bool,
+}
+
+impl NotebrookSink {
+    pub fn new(config: &Config) -> FeederResult<Self> {
+        let http = HttpClient::new(&config.http)?;
+        // Sends are retried by the notification service, so the transport tries each
+        // request once rather than multiplying the attempts
+        let transport = HttpClient::new(&HttpConfig {
+            retries: 0,
+            ..config.http.clone()
+        })?;
+        let client = ChannelClient::with_transport(
+            &config.notebrook_url,
+            &config.notebrook_token,
+            transport,
+        )?
+        .with_cache_ttl(Duration::from_secs(config.notebrook_cache_ttl_secs))
+        .with_create_missing(config.notebrook_create_channel);
+
+        Ok(Self {
+            client,
+            channel: config.notebrook_channel.clone(),
+            http,
+            attach_max_bytes: config.notebrook_attach_max_bytes,
+            attach_types: config.notebrook_attach_types.clone(),
+            public_images_only: true,
+        })
+    }
+
+    fn send_text(&self, notification: &Notification) -> FeederResult<channels::Message> {
+        // Try with full message first
+        let message = notification.format();
+        match self.client.send_message(&self.channel, &message) {
+            Ok(sent) => return Ok(sent),
+            Err(channels::ChannelError::PayloadTooLarge) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // Message too large, try truncating the text
+        let mut truncated = notification.clone();
+
+        // Binary search for max text length that fits
+        let mut high = truncated.text.len();
+
+        while high > 0 {
+            let mid = high / 2;
+            truncated.text = truncate_to_char_boundary(&notification.text, mid);
+
+            let message = truncated.format();
+            match self.client.send_message(&self.channel, &message) {
+                Ok(sent) => return Ok(sent),
+                Err(channels::ChannelError::PayloadTooLarge) => {
+                    high = mid;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Try with no text at all
+        truncated.text = String::new();
+        let message = truncated.format();
+        Ok(self.client.send_message(&self.channel, &message)?)
+    }
+
+    /// Download an image if it is an allowed type and within the size cap, or nothing
+    /// if it isn't.
+    ///
+    /// The URL comes from the feed, so it may only point at public http(s) hosts.
+    fn download(&self, url: &str) -> FeederResult<Option<Attachment>> {
+        let response = if self.public_images_only {
+            self.http.fetch_public(url)?
+        } else {
+            self.http.fetch(url)?
+        };
+        if !response.status().is_success() {
+            return Err(FeederError::Notification(format!(
+                "image returned HTTP {}",
+                response.status().as_u16()
+            )));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase());
+        let Some(content_type) = content_type.filter(|t| self.attach_types.contains(t)) else {
+            return Ok(None);
+        };
+
+        let declared = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if declared.is_some_and(|len| len > self.attach_max_bytes) {
+            return Ok(None);
+        }
+
+        // The declared length may be missing or wrong, so cap the read as well
+        let mut bytes = Vec::new();
+        response.take(self.attach_max_bytes + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > self.attach_max_bytes {
+            return Ok(None);
+        }
+
+        Ok(Some(Attachment {
+            file_name: file_name(url, &content_type),
+            content_type,
+            bytes,
+        }))
+    }
+
+    /// Attach the image at `url` to the sent message `message_id`
+    fn attach(&self, message_id: i64, url: &str) -> FeederResult<()> {
+        let Some(attachment) = self.download(url)? else {
+            return Ok(());
+        };
+
+        self.client.upload_file(
+            &self.channel,
+            message_id,
+            &attachment.file_name,
+            attachment.bytes,
+            &attachment.content_type,
+        )?;
+        Ok(())
+    }
+}
+
+impl NotificationSink for NotebrookSink {
+    fn name(&self) -> &str {
+        "notebrook"
+    }
+
+    /// Send a notification to notebrook, truncating text if too large, then attach
+    /// the article image when the feed asks for it
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        let sent = self.send_text(notification)?;
+
+        // The message is already delivered, so an image that can't be fetched or uploaded
+        // is reported without failing (and re-sending) the whole notification. Images
+        // of other types or over the size cap are left out.
+        if let Some(url) = notification.image.as_deref() {
+            self.attach(sent.id, url).map_err(|e| FeederError::PartlyDelivered {
+                sink: "notebrook".to_string(),
+                message: format!("not its image: {}", e),
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Last path segment of the image URL, or a generic name with an extension for the type
+fn file_name(url: &str, content_type: &str) -> String {
+    let from_path = url::Url::parse(url).ok().and_then(|u| {
+        u.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| name.contains('.'))
+            .map(|name| name.to_string())
+    });
+
+    from_path.unwrap_or_else(|| {
+        let extension = content_type.rsplit('/').next().unwrap_or("bin");
+        format!("image.{}", extension)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    const CHANNELS: &str = r#"{"channels": [{"id": 7, "name": "feeds"}]}"#;
+    const MESSAGE: &str = r#"{"id": 11, "content": "hi", "channelId": 7}"#;
+
+    fn sink(server: &Server, max_bytes: u64) -> NotebrookSink {
+        NotebrookSink {
+            client: ChannelClient::new(&server.url(), "token").unwrap(),
+            channel: "feeds".to_string(),
+            http: HttpClient::default(),
+            attach_max_bytes: max_bytes,
+            attach_types: vec!["image/png".to_string(), "image/jpeg".to_string()],
+            public_images_only: false,
+        }
+    }
+
+    fn notification(image: Option<String>) -> Notification {
+        Notification {
+            feed_title: "Photos".to_string(),
+            article_title: "Sunset".to_string(),
+            links: vec!["https://example.com/sunset".to_string()],
+            image,
+            ..Notification::test_default()
+        }
+    }
+
+    fn notebrook(server: &mut Server) {
+        server.mock("GET", "/channels").with_body(CHANNELS).create();
+        server
+            .mock("POST", "/channels/7/messages")
+            .with_body(MESSAGE)
+            .create();
+    }
+
+    #[test]
+    fn test_image_uploaded_after_message() {
+        let mut server = Server::new();
+        notebrook(&mut server);
+        server
+            .mock("GET", "/img/sunset.png")
+            .with_header("content-type", "image/png")
+            .with_body("png-bytes")
+            .create();
+        let upload = server
+            .mock("POST", "/channels/7/messages/11/files")
+            .match_body(Matcher::Regex(r#"filename="sunset.png""#.into()))
+            .with_status(201)
+            .create();
+
+        let image = format!("{}/img/sunset.png", server.url());
+        sink(&server, 1024).send(&notification(Some(image)), &Route::default()).unwrap();
+
+        upload.assert();
+    }
+
+    #[test]
+    fn test_disallowed_or_oversized_image_skipped() {
+        let mut server = Server::new();
+        notebrook(&mut server);
+        server
+            .mock("GET", "/img/anim.svg")
+            .with_header("content-type", "image/svg+xml")
+            .with_body("<svg/>")
+            .create();
+        server
+            .mock("GET", "/img/huge.png")
+            .with_header("content-type", "image/png")
+            .with_body("x".repeat(100))
+            .create();
+        let upload = server
+            .mock("POST", "/channels/7/messages/11/files")
+            .expect(0)
+            .create();
+
+        let sink = sink(&server, 10);
+        for path in ["/img/anim.svg", "/img/huge.png"] {
+            let image = format!("{}{}", server.url(), path);
+            sink.send(&notification(Some(image)), &Route::default()).unwrap();
+        }
+
+        upload.assert();
+    }
+
+    #[test]
+    fn test_local_image_not_fetched() {
+        let mut server = Server::new();
+        notebrook(&mut server);
+        let image = server.mock("GET", "/img/sunset.png").expect(0).create();
+
+        let sink = NotebrookSink {
+            public_images_only: true,
+            ..sink(&server, 1024)
+        };
+        let url = format!("{}/img/sunset.png", server.url());
+        let err = sink.send(&notification(Some(url)), &Route::default()).unwrap_err();
+
+        assert!(matches!(err, FeederError::PartlyDelivered { .. }), "{}", err);
+        image.assert();
+    }
+
+    #[test]
+    fn test_failed_download_or_upload_reported() {
+        let mut server = Server::new();
+        notebrook(&mut server);
+        server
+            .mock("GET", "/img/sunset.png")
+            .with_header("content-type", "image/png")
+            .with_body("png-bytes")
+            .create();
+        server
+            .mock("POST", "/channels/7/messages/11/files")
+            .with_status(500)
+            .create();
+
+        let sink = sink(&server, 1024);
+        for path in ["/img/missing.png", "/img/sunset.png"] {
+            let image = format!("{}{}", server.url(), path);
+            let err = sink.send(&notification(Some(image)), &Route::default()).unwrap_err();
+
+            assert!(matches!(err, FeederError::PartlyDelivered { .. }), "{}", err);
+            assert!(err.to_string().starts_with("notebrook sent the message, but not its image"));
+        }
+    }
+
+    #[test]
+    fn test_file_name_from_url_or_type() {
+        assert_eq!(file_name("https://e.com/a/b/cover.jpg?w=300", "image/jpeg"), "cover.jpg");
+        assert_eq!(file_name("https://e.com/thumb", "image/webp"), "image.webp");
+    }
+}
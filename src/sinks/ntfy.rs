@@ -0,0 +1,196 @@
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::config::{HttpConfig, NtfyConfig};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::response::check_status;
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// Publishes notifications to an ntfy topic.
+///
+/// Messages are sent as JSON to the server root with the topic in the body, which keeps
+/// non-ASCII titles intact (ntfy's header-based publishing is limited to ASCII).
+pub struct NtfySink {
+    client: Client,
+    server: Url,
+    topic: String,
+    token: Option<String>,
+    tags: Vec<String>,
+    priority: u8,
+}
+
+impl NtfySink {
+    pub fn new(config: &NtfyConfig, http: &HttpConfig) -> FeederResult<Self> {
+        let (server, topic) = split_topic_url(&config.url)?;
+
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            server,
+            topic,
+            token: config.token.clone(),
+            tags: config.tags.clone(),
+            priority: config.priority,
+        })
+    }
+
+    fn body(&self, notification: &Notification, route: &Route) -> Value {
+        let mut body = json!({
+            "topic": self.topic,
+            "title": format!("{}: {}", notification.feed_title, notification.article_title),
+            "message": message_text(notification),
+            "priority": route.priority.unwrap_or(self.priority),
+        });
+
+        if !self.tags.is_empty() {
+            body["tags"] = json!(self.tags);
+        }
+
+        if let Some(link) = notification.links.first() {
+            body["click"] = json!(link);
+        }
+
+        body
+    }
+}
+
+impl NotificationSink for NtfySink {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    fn send(&self, notification: &Notification, route: &Route) -> FeederResult<()> {
+        let mut request = self.client.post(self.server.clone()).json(&self.body(notification, route));
+
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        check_status("ntfy", request.send()?)?;
+        Ok(())
+    }
+}
+
+/// `https://ntfy.sh/my-topic` -> (`https://ntfy.sh/`, `my-topic`)
+fn split_topic_url(topic_url: &str) -> FeederResult<(Url, String)> {
+    let invalid = || FeederError::Config(format!("Invalid ntfy topic URL: {}", topic_url));
+
+    let mut server = Url::parse(topic_url).map_err(|_| invalid())?;
+    let topic = server
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        .map(str::to_string)
+        .ok_or_else(invalid)?;
+
+    server
+        .path_segments_mut()
+        .map_err(|_| invalid())?
+        .pop_if_empty()
+        .pop()
+        .push("");
+
+    Ok((server, topic))
+}
+
+/// Push body: the article text, or its links when there is no text
+fn message_text(notification: &Notification) -> String {
+    if !notification.text.is_empty() {
+        notification.text.clone()
+    } else if !notification.links.is_empty() {
+        notification.links.join("\n")
+    } else {
+        notification.article_title.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification() -> Notification {
+        Notification {
+            feed_title: "Status".to_string(),
+            article_title: "Outage résolue".to_string(),
+            text: "All systems operational".to_string(),
+            links: vec!["https://status.example.com/1".to_string()],
+            ..Notification::test_default()
+        }
+    }
+
+    fn route(priority: u8) -> Route {
+        Route {
+            priority: Some(priority),
+            ..Route::default()
+        }
+    }
+
+    fn config(url: &str) -> NtfyConfig {
+        NtfyConfig {
+            url: url.to_string(),
+            token: Some("tk_secret".to_string()),
+            tags: vec!["rss".to_string()],
+            priority: 3,
+        }
+    }
+
+    #[test]
+    fn test_split_topic_url() {
+        let (server, topic) = split_topic_url("https://ntfy.sh/alerts").unwrap();
+        assert_eq!(server.as_str(), "https://ntfy.sh/");
+        assert_eq!(topic, "alerts");
+
+        let (server, topic) = split_topic_url("https://example.com/ntfy/alerts/").unwrap();
+        assert_eq!(server.as_str(), "https://example.com/ntfy/");
+        assert_eq!(topic, "alerts");
+
+        assert!(split_topic_url("https://ntfy.sh/").is_err());
+    }
+
+    #[test]
+    fn test_send_publishes_json() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/")
+            .match_header("authorization", "Bearer tk_secret")
+            .match_body(Matcher::Json(json!({
+                "topic": "alerts",
+                "title": "Status: Outage résolue",
+                "message": "All systems operational",
+                "priority": 5,
+                "tags": ["rss"],
+                "click": "https://status.example.com/1",
+            })))
+            .with_status(200)
+            .create();
+
+        let sink = NtfySink::new(
+            &config(&format!("{}/alerts", server.url())),
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        sink.send(&notification(), &route(5)).unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_default_priority_and_links_as_message() {
+        let sink = NtfySink::new(
+            &config("https://ntfy.sh/alerts"),
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        let mut notification = notification();
+        notification.text = String::new();
+
+        let body = sink.body(&notification, &Route::default());
+
+        assert_eq!(body["priority"], 3);
+        assert_eq!(body["message"], "https://status.example.com/1");
+    }
+}
@@ -0,0 +1,26 @@
+use reqwest::blocking::Response;
+
+use crate::errors::{FeederError, FeederResult};
+
+/// Turn a non-2xx response into `FeederError::SinkStatus`, keeping any `Retry-After` delay
+pub(crate) fn check_status(sink: &str, response: Response) -> FeederResult<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    Err(FeederError::SinkStatus {
+        sink: sink.to_string(),
+        status: status.as_u16(),
+        retry_after: retry_after_header(&response),
+    })
+}
+
+/// Seconds from a `Retry-After` header given in seconds
+pub(crate) fn retry_after_header(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
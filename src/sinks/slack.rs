@@ -0,0 +1,234 @@
+use chrono::DateTime;
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+use crate::config::HttpConfig;
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::format::snippet;
+use crate::sinks::response::check_status;
+use crate::sinks::traits::{target_id, NotificationSink, Route};
+
+const MAX_SNIPPET_CHARS: usize = 500;
+
+/// Posts notifications to a Slack incoming webhook as Block Kit messages.
+///
+/// Slack answers rate-limited requests with 429 and a `Retry-After` header, which
+/// `check_status` turns into a retryable error.
+pub struct SlackSink {
+    client: Client,
+    url: String,
+}
+
+impl SlackSink {
+    pub fn new(url: &str, http: &HttpConfig) -> FeederResult<Self> {
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+
+    fn body(notification: &Notification) -> Value {
+        let title = escape_mrkdwn(&notification.article_title);
+        let mut section = match notification.links.first() {
+            Some(link) => format!("*<{}|{}>*", escape_link(link), title),
+            None => format!("*{}*", title),
+        };
+
+        if !notification.text.is_empty() {
+            section.push('\n');
+            section.push_str(&escape_mrkdwn(&snippet(&notification.text, MAX_SNIPPET_CHARS)));
+        }
+
+        let mut blocks = vec![
+            json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": escape_mrkdwn(&notification.feed_title) }],
+            }),
+            json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": section },
+            }),
+        ];
+
+        if let Some(published) = notification
+            .published
+            .as_deref()
+            .and_then(|p| DateTime::parse_from_rfc3339(p).ok())
+        {
+            // Rendered in each reader's own timezone, with the raw date as fallback
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{
+                    "type": "mrkdwn",
+                    "text": format!(
+                        "<!date^{}^{{date_short_pretty}} {{time}}|{}>",
+                        published.timestamp(),
+                        published.to_rfc3339()
+                    ),
+                }],
+            }));
+        }
+
+        json!({
+            "text": notification.format(),
+            "blocks": blocks,
+        })
+    }
+}
+
+impl NotificationSink for SlackSink {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn id(&self) -> String {
+        target_id(self.name(), &self.url)
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        // The webhook URL is the secret, so keep it out of the error
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&Self::body(notification))
+            .send()
+            .map_err(|e| FeederError::Http(e.without_url()))?;
+
+        check_status("slack", response)?;
+        Ok(())
+    }
+}
+
+/// Slack's mrkdwn only needs the three control characters escaped
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A `|` would end the URL of a `<url|text>` link early, so it's percent-encoded
+fn escape_link(url: &str) -> String {
+    escape_mrkdwn(url).replace('|', "%7C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification() -> Notification {
+        Notification {
+            article_title: "Announcing <Rust> 1.75.0".to_string(),
+            text: "Happy new release".to_string(),
+            published: Some("2023-12-28T00:00:00+00:00".to_string()),
+            ..Notification::test_default()
+        }
+    }
+
+    #[test]
+    fn test_block_kit_body() {
+        let body = SlackSink::body(&notification());
+
+        assert_eq!(
+            body["blocks"][1]["text"]["text"],
+            "*<https://blog.rust-lang.org/1.75|Announcing &lt;Rust&gt; 1.75.0>*\nHappy new release"
+        );
+        assert_eq!(body["blocks"][0]["elements"][0]["text"], "Rust Blog");
+        assert_eq!(
+            body["blocks"][2]["elements"][0]["text"],
+            "<!date^1703721600^{date_short_pretty} {time}|2023-12-28T00:00:00+00:00>"
+        );
+    }
+
+    #[test]
+    fn test_link_url_escaped() {
+        let notification = Notification {
+            links: vec!["https://example.com/?a=1&b=<2>|3".to_string()],
+            ..notification()
+        };
+
+        assert_eq!(
+            SlackSink::body(&notification)["blocks"][1]["text"]["text"],
+            "*<https://example.com/?a=1&amp;b=&lt;2&gt;%7C3|Announcing &lt;Rust&gt; 1.75.0>*\nHappy new release"
+        );
+    }
+
+    #[test]
+    fn test_send_to_mock_webhook() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/services/T/B/X")
+            .match_body(Matcher::PartialJson(json!({
+                "text": "Rust Blog Announcing <Rust> 1.75.0: Happy new release https://blog.rust-lang.org/1.75"
+            })))
+            .with_status(200)
+            .with_body("ok")
+            .create();
+
+        let sink = SlackSink::new(
+            &format!("{}/services/T/B/X", server.url()),
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        sink.send(&notification(), &Route::default()).unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_rate_limit_header() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", Matcher::Any)
+            .with_status(429)
+            .with_header("retry-after", "30")
+            .create();
+
+        let err = SlackSink::new(&server.url(), &HttpConfig::default())
+            .unwrap()
+            .send(&notification(), &Route::default())
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            FeederError::SinkStatus { status: 429, retry_after: Some(30), .. }
+        ));
+    }
+
+    #[test]
+    fn test_connection_error_hides_url() {
+        let err = SlackSink::new("http://127.0.0.1:1/secret-token", &HttpConfig::default())
+            .unwrap()
+            .send(&notification(), &Route::default())
+            .unwrap_err();
+
+        assert!(!err.to_string().contains("secret-token"));
+    }
+
+    #[test]
+    fn test_sent_through_proxy_with_user_agent() {
+        let mut proxy = mockito::Server::new();
+        let mock = proxy
+            .mock("POST", "/services/T/B/X")
+            .match_header("host", "hooks.slack.invalid")
+            .match_header("user-agent", "feeder-test")
+            .with_body("ok")
+            .create();
+
+        let http = HttpConfig {
+            user_agent: "feeder-test".to_string(),
+            proxy: Some(proxy.url()),
+            ..HttpConfig::default()
+        };
+        SlackSink::new("http://hooks.slack.invalid/services/T/B/X", &http)
+            .unwrap()
+            .send(&notification(), &Route::default())
+            .unwrap();
+
+        mock.assert();
+    }
+}
@@ -0,0 +1,324 @@
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::{HttpConfig, TelegramConfig};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::format::{escape_html, snippet, split_head, split_text, to_html};
+use crate::sinks::response::{check_status, retry_after_header};
+use crate::sinks::traits::{NotificationSink, Route};
+
+/// Telegram's limit on the visible text of one message
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Smallest text chunk worth appending to a message that already holds the title
+const MIN_CHUNK_CHARS: usize = 500;
+
+/// Error body of the Bot API, which carries the rate-limit delay in `parameters`
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    parameters: Option<ResponseParameters>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseParameters {
+    retry_after: Option<u64>,
+}
+
+/// Sends notifications through a Telegram bot with `sendMessage`
+pub struct TelegramSink {
+    client: Client,
+    url: String,
+    chat_id: String,
+    disable_preview: bool,
+}
+
+impl TelegramSink {
+    pub fn new(config: &TelegramConfig, http: &HttpConfig) -> FeederResult<Self> {
+        let client = HttpClient::sink_client(http)?;
+
+        Ok(Self {
+            client,
+            url: format!(
+                "{}/bot{}/sendMessage",
+                config.api_url.trim_end_matches('/'),
+                config.bot_token
+            ),
+            chat_id: config.chat_id.clone(),
+            disable_preview: config.disable_preview,
+        })
+    }
+
+    fn send_message(&self, chat_id: &str, html: &str) -> FeederResult<()> {
+        let body = json!({
+            "chat_id": chat_id,
+            "text": html,
+            "parse_mode": "HTML",
+            "link_preview_options": { "is_disabled": self.disable_preview },
+        });
+
+        // The URL holds the bot token, so keep it out of the error
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .map_err(|e| FeederError::Http(e.without_url()))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let header = retry_after_header(&response);
+            let retry_after = response
+                .json::<ApiError>()
+                .ok()
+                .and_then(|e| e.parameters)
+                .and_then(|p| p.retry_after)
+                .or(header);
+
+            return Err(FeederError::SinkStatus {
+                sink: "telegram".to_string(),
+                status: 429,
+                retry_after,
+            });
+        }
+
+        check_status("telegram", response)?;
+        Ok(())
+    }
+}
+
+impl NotificationSink for TelegramSink {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send(&self, notification: &Notification, route: &Route) -> FeederResult<()> {
+        let chat_id = route.telegram_chat.as_deref().unwrap_or(&self.chat_id);
+
+        for part in messages(notification) {
+            self.send_message(chat_id, &part)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Render a notification as one or more HTML messages within Telegram's length limit.
+///
+/// Long text is split on paragraph, line or word boundaries, and links that don't fit move
+/// on to further messages. The first message keeps the feed and linked title. Each piece is
+/// escaped on its own, so a split never lands inside a tag or entity.
+fn messages(notification: &Notification) -> Vec<String> {
+    // The plain format is at least as long as the visible text of the HTML one
+    if notification.format().chars().count() <= MAX_MESSAGE_CHARS {
+        return vec![to_html(notification)];
+    }
+
+    let mut head = notification.clone();
+    head.text.clear();
+    head.links.truncate(1);
+    head.feed_title = snippet(&notification.feed_title, MIN_CHUNK_CHARS);
+    let feed_chars = head.feed_title.chars().count();
+    head.article_title = snippet(&notification.article_title, MAX_MESSAGE_CHARS - feed_chars - 1);
+
+    let mut parts = Parts::default();
+    parts.push("", to_html(&head), feed_chars + 1 + head.article_title.chars().count());
+
+    // Fill the rest of the first message if that leaves a chunk worth sending
+    let mut chunks = Vec::new();
+    let mut text = notification.text.trim();
+    let room = MAX_MESSAGE_CHARS.saturating_sub(parts.chars + 2);
+    if room >= MIN_CHUNK_CHARS {
+        let (first, rest) = split_head(text, room);
+        chunks.push(first.to_string());
+        text = rest;
+    }
+    chunks.extend(split_text(text, MAX_MESSAGE_CHARS));
+
+    let mut separator = ": ";
+    for chunk in chunks.iter().filter(|chunk| !chunk.is_empty()) {
+        parts.push(separator, escape_html(chunk), chunk.chars().count());
+        separator = " ";
+    }
+
+    for link in notification.links.iter().skip(1) {
+        let label = snippet(link, MAX_MESSAGE_CHARS);
+        let html = format!("<a href=\"{}\">{}</a>", escape_html(link), escape_html(&label));
+        parts.push(" ", html, label.chars().count());
+    }
+
+    parts.messages
+}
+
+/// Messages being packed, each holding at most `MAX_MESSAGE_CHARS` visible characters
+#[derive(Default)]
+struct Parts {
+    messages: Vec<String>,
+    /// Visible characters in the last message
+    chars: usize,
+}
+
+impl Parts {
+    /// Append a piece to the last message after `separator`, or start a new message with it
+    fn push(&mut self, separator: &str, html: String, chars: usize) {
+        let joined = self.chars + separator.chars().count() + chars;
+        match self.messages.last_mut() {
+            Some(last) if joined <= MAX_MESSAGE_CHARS => {
+                last.push_str(separator);
+                last.push_str(&html);
+                self.chars = joined;
+            }
+            _ => {
+                self.messages.push(html);
+                self.chars = chars;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn notification(text: &str) -> Notification {
+        Notification {
+            feed_title: "News".to_string(),
+            article_title: "Long read".to_string(),
+            text: text.to_string(),
+            links: vec![
+                "https://example.com/read".to_string(),
+                "https://example.com/audio.mp3".to_string(),
+            ],
+            ..Notification::test_default()
+        }
+    }
+
+    /// Characters Telegram counts: everything outside tags (no entities in these tests)
+    fn visible_chars(html: &str) -> usize {
+        let mut in_tag = false;
+        html.chars()
+            .filter(|&c| {
+                match c {
+                    '<' => in_tag = true,
+                    '>' => {
+                        in_tag = false;
+                        return false;
+                    }
+                    _ => {}
+                }
+                !in_tag
+            })
+            .count()
+    }
+
+    fn config(api_url: &str) -> TelegramConfig {
+        TelegramConfig {
+            bot_token: "123:abc".to_string(),
+            chat_id: "42".to_string(),
+            disable_preview: true,
+            api_url: api_url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_short_message_unsplit() {
+        let parts = messages(&notification("Short & sweet"));
+
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].contains("Short &amp; sweet"));
+    }
+
+    #[test]
+    fn test_long_message_split_on_boundaries() {
+        let paragraph = "word ".repeat(500);
+        let text = format!("{}\n\n{}", paragraph.trim(), paragraph.trim());
+        let parts = messages(&notification(&text));
+
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].starts_with("<b>News</b> <a href=\"https://example.com/read\">Long read</a>: word"));
+        assert!(parts[0].ends_with("word"));
+        assert!(parts[1].starts_with("word"));
+        assert!(parts[1].ends_with("<a href=\"https://example.com/audio.mp3\">https://example.com/audio.mp3</a>"));
+        for part in &parts {
+            assert!(visible_chars(part) <= MAX_MESSAGE_CHARS);
+        }
+    }
+
+    #[test]
+    fn test_links_alone_over_limit_split() {
+        let mut notification = notification("Some text & more");
+        notification.links = (0..200)
+            .map(|i| format!("https://example.com/episodes/{:04}.mp3", i))
+            .collect();
+        let parts = messages(&notification);
+
+        assert!(parts.len() > 1);
+        assert!(parts[0].contains("Long read</a>: Some text &amp; more"));
+        for part in &parts {
+            assert!(visible_chars(part) <= MAX_MESSAGE_CHARS);
+            assert_eq!(part.matches("<a ").count(), part.matches("</a>").count());
+        }
+        let links: usize = parts.iter().map(|part| part.matches("<a ").count()).sum();
+        assert_eq!(links, 200);
+        assert!(parts.last().unwrap().ends_with("0199.mp3</a>"));
+    }
+
+    #[test]
+    fn test_send_uses_per_feed_chat() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/bot123:abc/sendMessage")
+            .match_body(Matcher::PartialJson(json!({
+                "chat_id": "-100999",
+                "parse_mode": "HTML",
+                "link_preview_options": { "is_disabled": true },
+            })))
+            .with_status(200)
+            .with_body(r#"{"ok": true}"#)
+            .create();
+
+        let route = Route {
+            telegram_chat: Some("-100999".to_string()),
+            ..Route::default()
+        };
+
+        TelegramSink::new(&config(&server.url()), &HttpConfig::default())
+            .unwrap()
+            .send(&notification("Hi"), &route)
+            .unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_rate_limit_from_body() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", Matcher::Any)
+            .with_status(429)
+            .with_body(r#"{"ok": false, "error_code": 429, "parameters": {"retry_after": 14}}"#)
+            .create();
+
+        let err = TelegramSink::new(&config(&server.url()), &HttpConfig::default())
+            .unwrap()
+            .send(&notification("Hi"), &Route::default())
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            FeederError::SinkStatus { status: 429, retry_after: Some(14), .. }
+        ));
+    }
+
+    #[test]
+    fn test_connection_error_hides_token() {
+        let err = TelegramSink::new(&config("http://127.0.0.1:1"), &HttpConfig::default())
+            .unwrap()
+            .send(&notification("Hi"), &Route::default())
+            .unwrap_err();
+
+        assert!(!err.to_string().contains("123:abc"));
+    }
+}
@@ -0,0 +1,76 @@
+use sha2::{Digest, Sha256};
+
+use crate::domain::{Feed, Notification};
+use crate::errors::FeederResult;
+
+/// Whether a sink receives every notification as it happens or one digest per run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryMode {
+    #[default]
+    Each,
+    Digest,
+}
+
+impl std::str::FromStr for DeliveryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "each" => Ok(DeliveryMode::Each),
+            "digest" => Ok(DeliveryMode::Digest),
+            _ => Err(format!("Unknown delivery mode: {}", s)),
+        }
+    }
+}
+
+/// Where a feed's notifications go within sinks, set per feed rather than per article
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Route {
+    /// Push priority (1-5), if the user set one
+    pub priority: Option<u8>,
+    /// Telegram chat overriding the configured one
+    pub telegram_chat: Option<String>,
+}
+
+impl Route {
+    pub fn for_feed(feed: &Feed) -> Self {
+        Self {
+            priority: feed.settings.priority,
+            telegram_chat: feed.settings.telegram_chat.clone(),
+        }
+    }
+}
+
+/// ID for a sink that can be configured more than once, telling the copies apart
+/// by their target. The target is hashed, as it may hold a secret like a webhook token.
+pub(crate) fn target_id(name: &str, target: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(target.as_bytes()));
+    format!("{}:{}", name, &hash[..16])
+}
+
+pub trait NotificationSink: Send + Sync {
+    /// Short name used in output and errors
+    fn name(&self) -> &str;
+
+    /// Tells this sink apart from the other configured ones, so what it still has to
+    /// be sent is kept for it alone
+    fn id(&self) -> String {
+        self.name().to_string()
+    }
+
+    fn mode(&self) -> DeliveryMode {
+        DeliveryMode::Each
+    }
+
+    /// Deliver a single notification
+    fn send(&self, notification: &Notification, route: &Route) -> FeederResult<()>;
+
+    /// Deliver all notifications of a run at once. Sinks without a digest
+    /// format send them one by one.
+    fn send_digest(&self, notifications: &[Notification]) -> FeederResult<()> {
+        for notification in notifications {
+            self.send(notification, &Route::default())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+
+use crate::config::{HttpConfig, WebhookConfig};
+use crate::domain::Notification;
+use crate::errors::{FeederError, FeederResult};
+use crate::http::HttpClient;
+use crate::sinks::response::check_status;
+use crate::sinks::traits::{target_id, DeliveryMode, NotificationSink, Route};
+
+/// Header carrying the hex HMAC-SHA256 of the request body when a secret is configured
+pub const SIGNATURE_HEADER: &str = "X-Feeder-Signature";
+
+/// POSTs notifications as JSON to an arbitrary HTTP endpoint.
+///
+/// The body is either the default payload or a user template in which string values
+/// may reference placeholders like `{{article_title}}`. A string consisting of a single
+/// placeholder is replaced by the raw value, so `"{{links}}"` becomes a JSON array.
+///
+/// Placeholders for single notifications: `feed_title`, `article_title`, `text`, `links`,
+/// `link`, `published`, `message`. For digests: `count`, `message`, `notifications`.
+pub struct WebhookSink {
+    client: Client,
+    url: String,
+    template: Option<Value>,
+    headers: Vec<(String, String)>,
+    secret: Option<String>,
+    mode: DeliveryMode,
+}
+
+impl WebhookSink {
+    pub fn new(config: &WebhookConfig, url: &str, http: &HttpConfig) -> FeederResult<Self> {
+        let template = match &config.template {
+            Some(template) => Some(serde_json::from_str(template).map_err(|e| {
+                FeederError::Config(format!("Invalid webhook template: {}", e))
+            })?),
+            None => None,
+        };
+
+        let client = HttpClient::sink_client(&HttpConfig {
+            timeout_secs: config.timeout_secs,
+            ..http.clone()
+        })?;
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            template,
+            headers: config.headers.clone(),
+            secret: config.secret.clone(),
+            mode: config.mode,
+        })
+    }
+
+    /// Values available to templates for a single notification
+    fn notification_vars(notification: &Notification) -> Map<String, Value> {
+        let mut vars = Map::new();
+        vars.insert("feed_title".into(), json!(notification.feed_title));
+        vars.insert("article_title".into(), json!(notification.article_title));
+        vars.insert("text".into(), json!(notification.text));
+        vars.insert("links".into(), json!(notification.links));
+        vars.insert(
+            "link".into(),
+            json!(notification.links.first().cloned().unwrap_or_default()),
+        );
+        vars.insert("published".into(), json!(notification.published));
+        vars.insert("message".into(), json!(notification.format()));
+        vars
+    }
+
+    /// Values available to templates for a digest
+    fn digest_vars(notifications: &[Notification]) -> Map<String, Value> {
+        let message = notifications
+            .iter()
+            .map(|n| n.format())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut vars = Map::new();
+        vars.insert("count".into(), json!(notifications.len()));
+        vars.insert("message".into(), json!(message));
+        vars.insert(
+            "notifications".into(),
+            Value::Array(
+                notifications
+                    .iter()
+                    .map(|n| Value::Object(Self::notification_vars(n)))
+                    .collect(),
+            ),
+        );
+        vars
+    }
+
+    /// Build the request body: the template filled in, or the variables themselves
+    fn render_body(&self, vars: Map<String, Value>) -> Value {
+        match &self.template {
+            Some(template) => render_template(template, &vars),
+            None => Value::Object(vars),
+        }
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        // HMAC accepts keys of any length, so this cannot fail
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts any key length");
+        mac.update(body);
+        format!("sha256={:x}", mac.finalize().into_bytes())
+    }
+
+    fn post(&self, body: &Value) -> FeederResult<()> {
+        let body = serde_json::to_vec(body)
+            .map_err(|e| FeederError::Notification(format!("Could not encode webhook body: {}", e)))?;
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, Self::sign(secret, &body));
+        }
+
+        let response = request.body(body).send()?;
+        check_status("webhook", response)?;
+        Ok(())
+    }
+}
+
+impl NotificationSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn id(&self) -> String {
+        target_id(self.name(), &self.url)
+    }
+
+    fn mode(&self) -> DeliveryMode {
+        self.mode
+    }
+
+    fn send(&self, notification: &Notification, _route: &Route) -> FeederResult<()> {
+        self.post(&self.render_body(Self::notification_vars(notification)))
+    }
+
+    fn send_digest(&self, notifications: &[Notification]) -> FeederResult<()> {
+        if notifications.is_empty() {
+            return Ok(());
+        }
+
+        self.post(&self.render_body(Self::digest_vars(notifications)))
+    }
+}
+
+/// Replace `{{name}}` placeholders in every string of a JSON template
+fn render_template(template: &Value, vars: &Map<String, Value>) -> Value {
+    match template {
+        Value::String(s) => {
+            // A lone placeholder keeps the value's JSON type (arrays, numbers, null)
+            if let Some(name) = s.trim().strip_prefix("{{").and_then(|r| r.strip_suffix("}}")) {
+                if let Some(value) = vars.get(name.trim()) {
+                    return value.clone();
+                }
+            }
+
+            let mut rendered = s.clone();
+            for (name, value) in vars {
+                let placeholder = format!("{{{{{}}}}}", name);
+                if rendered.contains(&placeholder) {
+                    let text = match value {
+                        Value::String(v) => v.clone(),
+                        Value::Null => String::new(),
+                        Value::Array(items) => items
+                            .iter()
+                            .map(|i| i.as_str().map(str::to_string).unwrap_or_else(|| i.to_string()))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        other => other.to_string(),
+                    };
+                    rendered = rendered.replace(&placeholder, &text);
+                }
+            }
+            Value::String(rendered)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|i| render_template(i, vars)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), render_template(v, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn notification() -> Notification {
+        Notification {
+            published: Some("2023-12-28T00:00:00+00:00".to_string()),
+            ..Notification::test_default()
+        }
+    }
+
+    fn config(template: Option<&str>) -> WebhookConfig {
+        WebhookConfig {
+            urls: Vec::new(),
+            template: template.map(str::to_string),
+            headers: vec![("X-Api-Key".to_string(), "abc".to_string())],
+            secret: None,
+            timeout_secs: 5,
+            mode: DeliveryMode::Each,
+        }
+    }
+
+    fn new_sink(config: &WebhookConfig, url: &str) -> FeederResult<WebhookSink> {
+        WebhookSink::new(config, url, &HttpConfig::default())
+    }
+
+    #[test]
+    fn test_render_template_placeholders() {
+        let sink = new_sink(
+            &config(Some(
+                r#"{"text": "New: {{article_title}} ({{feed_title}})", "urls": "{{links}}", "meta": {"n": 1, "url": "{{link}}"}}"#,
+            )),
+            "http://localhost",
+        )
+        .unwrap();
+
+        let body = sink.render_body(WebhookSink::notification_vars(&notification()));
+
+        assert_eq!(
+            body,
+            json!({
+                "text": "New: Announcing Rust 1.75.0 (Rust Blog)",
+                "urls": ["https://blog.rust-lang.org/1.75"],
+                "meta": {"n": 1, "url": "https://blog.rust-lang.org/1.75"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_body() {
+        let sink = new_sink(&config(None), "http://localhost").unwrap();
+        let body = sink.render_body(WebhookSink::notification_vars(&notification()));
+
+        assert_eq!(body["feed_title"], "Rust Blog");
+        assert_eq!(body["links"], json!(["https://blog.rust-lang.org/1.75"]));
+        assert_eq!(
+            body["message"],
+            "Rust Blog Announcing Rust 1.75.0 https://blog.rust-lang.org/1.75"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template_rejected() {
+        let result = new_sink(&config(Some("{not json")), "http://localhost");
+        assert!(matches!(result, Err(FeederError::Config(_))));
+    }
+
+    #[test]
+    fn test_sign_known_value() {
+        assert_eq!(
+            WebhookSink::sign("secret", br#"{"a":1}"#),
+            "sha256=aa9e2e3575f5d7098b6caccd790888c36d5fdb63342a73bada2d6a51747a8494"
+        );
+    }
+
+    #[test]
+    fn test_post_sends_headers_and_signature() {
+        let mut server = mockito::Server::new();
+        let mut config = config(Some(r#"{"title": "{{article_title}}"}"#));
+        config.secret = Some("secret".to_string());
+
+        let expected_body = r#"{"title":"Announcing Rust 1.75.0"}"#;
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header("x-api-key", "abc")
+            .match_header("content-type", "application/json")
+            .match_header(
+                "x-feeder-signature",
+                WebhookSink::sign("secret", expected_body.as_bytes()).as_str(),
+            )
+            .match_body(expected_body)
+            .with_status(204)
+            .create();
+
+        let sink = new_sink(&config, &format!("{}/hook", server.url())).unwrap();
+        sink.send(&notification(), &Route::default()).unwrap();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn test_non_success_becomes_typed_error() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", "/hook")
+            .with_status(429)
+            .with_header("retry-after", "7")
+            .create();
+
+        let sink = new_sink(&config(None), &format!("{}/hook", server.url())).unwrap();
+        let err = sink.send(&notification(), &Route::default()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            FeederError::SinkStatus { status: 429, retry_after: Some(7), .. }
+        ));
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn test_client_error_not_retryable() {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/hook").with_status(400).create();
+
+        let sink = new_sink(&config(None), &format!("{}/hook", server.url())).unwrap();
+        let err = sink.send(&notification(), &Route::default()).unwrap_err();
+
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_digest_body() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "count": 2,
+                "notifications": [
+                    {"article_title": "Announcing Rust 1.75.0"},
+                    {"article_title": "Announcing Rust 1.75.0"}
+                ]
+            })))
+            .with_status(200)
+            .create();
+
+        let mut config = config(None);
+        config.mode = DeliveryMode::Digest;
+        let sink = new_sink(&config, &format!("{}/hook", server.url())).unwrap();
+
+        assert_eq!(sink.mode(), DeliveryMode::Digest);
+        sink.send_digest(&[notification(), notification()]).unwrap();
+
+        mock.assert();
+    }
+}
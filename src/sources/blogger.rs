@@ -1,8 +1,9 @@
 use url::Url;
 
-use crate::domain::{Article, Feed, SourceType};
+use crate::domain::{Feed, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
+use crate::http::HttpClient;
+use crate::sources::traits::{FeedMetadata, FeedSource, FetchedFeed};
 use crate::sources::rss_atom::RssAtomSource;
 
 pub struct BloggerSource {
@@ -11,8 +12,12 @@ pub struct BloggerSource {
 
 impl BloggerSource {
     pub fn new() -> Self {
+        Self::with_http(HttpClient::default())
+    }
+
+    pub fn with_http(http: HttpClient) -> Self {
         Self {
-            rss_source: RssAtomSource::new(),
+            rss_source: RssAtomSource::with_http(http),
         }
     }
 
@@ -43,17 +48,17 @@ impl FeedSource for BloggerSource {
         url.contains(".blogspot.com")
     }
 
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
         let feed_url = self.build_feed_url(url)?;
 
         // Use the RSS source to validate the feed
-        let mut metadata = self.rss_source.validate(&feed_url)?;
+        let mut metadata = self.rss_source.validate(&feed_url, request)?;
         metadata.source_type = SourceType::Blogger;
 
         Ok(metadata)
     }
 
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
         self.rss_source.fetch_articles(feed)
     }
 }
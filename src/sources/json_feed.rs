@@ -0,0 +1,297 @@
+//! Native JSON Feed (https://jsonfeed.org) 1.0 and 1.1 support
+
+use serde::Deserialize;
+
+use crate::domain::Article;
+use crate::errors::{FeederError, FeederResult};
+use crate::sources::html_to_text;
+
+/// Every JSON Feed declares a version URL starting with this prefix
+const VERSION_PREFIX: &str = "https://jsonfeed.org/version/";
+
+/// Maximum length of a title derived from `content_text` for title-less items
+const MAX_DERIVED_TITLE_LEN: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct JsonFeed {
+    pub version: String,
+    pub title: Option<String>,
+    pub home_page_url: Option<String>,
+    pub feed_url: Option<String>,
+    pub description: Option<String>,
+    /// 1.1 replaced the single `author` with `authors`; both are accepted
+    #[serde(default)]
+    pub authors: Vec<JsonFeedAuthor>,
+    pub author: Option<JsonFeedAuthor>,
+    #[serde(default)]
+    pub items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonFeedAuthor {
+    pub name: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonFeedItem {
+    /// Spec says string, but numbers are common in the wild
+    pub id: Option<serde_json::Value>,
+    pub url: Option<String>,
+    pub external_url: Option<String>,
+    pub title: Option<String>,
+    pub content_text: Option<String>,
+    pub content_html: Option<String>,
+    pub summary: Option<String>,
+    pub image: Option<String>,
+    pub banner_image: Option<String>,
+    pub date_published: Option<String>,
+    pub date_modified: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<JsonFeedAuthor>,
+    pub author: Option<JsonFeedAuthor>,
+    #[serde(default)]
+    pub attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonFeedAttachment {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub title: Option<String>,
+}
+
+impl JsonFeed {
+    /// Whether a response looks like JSON rather than XML, from its content type or first byte
+    pub fn looks_like_json(content_type: Option<&str>, bytes: &[u8]) -> bool {
+        if let Some(content_type) = content_type {
+            if content_type.to_lowercase().contains("json") {
+                return true;
+            }
+        }
+
+        bytes
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .map(|b| *b == b'{')
+            .unwrap_or(false)
+    }
+
+    pub fn parse(bytes: &[u8]) -> FeederResult<Self> {
+        let feed: JsonFeed = serde_json::from_slice(bytes)
+            .map_err(|e| FeederError::FeedParse(format!("Invalid JSON Feed: {}", e)))?;
+
+        if !feed.version.starts_with(VERSION_PREFIX) {
+            return Err(FeederError::FeedParse(format!(
+                "Unsupported JSON Feed version: {}",
+                feed.version
+            )));
+        }
+
+        Ok(feed)
+    }
+
+    /// Map items onto articles, leaving out content as RSS and Atom do to keep notifications
+    /// short, but keeping the summary or text for the history. Items without a title
+    /// (common for microblogs) get one derived from `content_text`.
+    pub fn articles(&self) -> Vec<Article> {
+        let feed_author = author_names(&self.authors, self.author.as_ref());
+
+        self.items
+            .iter()
+            .filter_map(|item| {
+                let id = item
+                    .id
+                    .as_ref()
+                    .map(|id| match id {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .or_else(|| item.url.clone())?;
+
+                let title = item
+                    .title
+                    .clone()
+                    .filter(|t| !t.trim().is_empty())
+                    .or_else(|| {
+                        item.content_text
+                            .as_deref()
+                            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                            .filter(|text| !text.is_empty())
+                            .map(|text| truncate_for_title(&text, MAX_DERIVED_TITLE_LEN))
+                    })
+                    .unwrap_or_else(|| "Untitled".to_string());
+
+                let mut links: Vec<String> = Vec::new();
+                for link in item
+                    .url
+                    .iter()
+                    .chain(item.external_url.iter())
+                    .chain(item.attachments.iter().map(|a| &a.url))
+                {
+                    if !links.contains(link) {
+                        links.push(link.clone());
+                    }
+                }
+
+                let published = item
+                    .date_published
+                    .as_deref()
+                    .or(item.date_modified.as_deref())
+                    .map(normalize_date);
+
+                let author = author_names(&item.authors, item.author.as_ref())
+                    .or_else(|| feed_author.clone());
+
+                let summary = item
+                    .summary
+                    .clone()
+                    .or_else(|| item.content_text.clone())
+                    .or_else(|| item.content_html.as_deref().map(html_to_text))
+                    .filter(|text| !text.trim().is_empty());
+
+                Some(
+                    Article::new(id, title)
+                        .with_summary(summary)
+                        .with_links(links)
+                        .with_published(published)
+                        .with_author(author)
+                        .with_image(item.image.clone().or_else(|| item.banner_image.clone())),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Join author names, preferring the 1.1 `authors` list over the 1.0 `author` object
+fn author_names(authors: &[JsonFeedAuthor], author: Option<&JsonFeedAuthor>) -> Option<String> {
+    let names: Vec<&str> = authors
+        .iter()
+        .chain(author)
+        .filter_map(|a| a.name.as_deref())
+        .filter(|n| !n.trim().is_empty())
+        .collect();
+
+    if names.is_empty() {
+        None
+    } else {
+        Some(names.join(", "))
+    }
+}
+
+/// Dates are RFC 3339 per the spec; re-emit them the way other sources do when they parse
+fn normalize_date(date: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// Truncate at a word boundary, respecting char boundaries
+fn truncate_for_title(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let truncated: String = text.chars().take(max_chars).collect();
+    match truncated.rfind(' ') {
+        Some(pos) => format!("{}...", &truncated[..pos]),
+        None => format!("{}...", truncated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_JSON_FEED: &[u8] = br#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "Example Microblog",
+  "home_page_url": "https://example.org/",
+  "feed_url": "https://example.org/feed.json",
+  "description": "Short posts",
+  "authors": [{ "name": "Feed Author" }],
+  "items": [
+    {
+      "id": "2",
+      "url": "https://example.org/2",
+      "title": "A titled post",
+      "summary": "What this post is about",
+      "content_html": "<p>Long body</p>",
+      "date_published": "2024-01-15T12:00:00Z",
+      "authors": [{ "name": "Alice" }, { "name": "Bob" }],
+      "attachments": [
+        { "url": "https://example.org/episode.mp3", "mime_type": "audio/mpeg" }
+      ]
+    },
+    {
+      "id": 1,
+      "url": "https://example.org/1",
+      "content_text": "Just a quick   note without a title",
+      "date_modified": "2024-01-14T08:30:00+01:00"
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_parse_and_map_articles() {
+        let feed = JsonFeed::parse(SAMPLE_JSON_FEED).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example Microblog"));
+
+        let articles = feed.articles();
+        assert_eq!(articles.len(), 2);
+
+        let titled = &articles[0];
+        assert_eq!(titled.id, "2");
+        assert_eq!(titled.title, "A titled post");
+        assert!(titled.content.is_none());
+        assert_eq!(titled.summary.as_deref(), Some("What this post is about"));
+        assert_eq!(
+            titled.links,
+            vec!["https://example.org/2", "https://example.org/episode.mp3"]
+        );
+        assert_eq!(titled.author.as_deref(), Some("Alice, Bob"));
+        assert_eq!(titled.published.as_deref(), Some("2024-01-15T12:00:00+00:00"));
+
+        let untitled = &articles[1];
+        assert_eq!(untitled.id, "1");
+        assert_eq!(untitled.title, "Just a quick note without a title");
+        assert!(untitled.content.is_none());
+        assert_eq!(untitled.summary.as_deref(), Some("Just a quick   note without a title"));
+        assert_eq!(untitled.author.as_deref(), Some("Feed Author"));
+        assert_eq!(untitled.published.as_deref(), Some("2024-01-14T08:30:00+01:00"));
+    }
+
+    #[test]
+    fn test_version_1_0_author() {
+        let json = br#"{
+  "version": "https://jsonfeed.org/version/1",
+  "title": "Old Feed",
+  "author": { "name": "Single Author" },
+  "items": [{ "id": "a", "title": "Post", "content_text": "Body" }]
+}"#;
+
+        let articles = JsonFeed::parse(json).unwrap().articles();
+        assert_eq!(articles[0].author.as_deref(), Some("Single Author"));
+        assert!(articles[0].content.is_none());
+    }
+
+    #[test]
+    fn test_rejects_non_json_feed() {
+        assert!(JsonFeed::parse(br#"{"version": "2.0", "items": []}"#).is_err());
+        assert!(JsonFeed::parse(b"<rss></rss>").is_err());
+    }
+
+    #[test]
+    fn test_looks_like_json() {
+        assert!(JsonFeed::looks_like_json(Some("application/feed+json"), b""));
+        assert!(JsonFeed::looks_like_json(None, b"  \n{\"version\": 1}"));
+        assert!(!JsonFeed::looks_like_json(Some("application/rss+xml"), b"<?xml"));
+    }
+
+    #[test]
+    fn test_truncate_for_title() {
+        assert_eq!(truncate_for_title("short", 10), "short");
+        assert_eq!(truncate_for_title("one two three four", 10), "one two...");
+        assert_eq!(truncate_for_title("ñññññññññññ", 5), "ñññññ...");
+    }
+}
@@ -1,50 +1,29 @@
 use feed_rs::parser;
 use regex::Regex;
-use reqwest::blocking::Client;
-use scraper::Html;
 use url::Url;
 
-use crate::domain::{Article, Feed, SourceType};
+use crate::domain::{Article, Feed, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
-use crate::sources::rss_atom::RssAtomSource;
+use crate::http::HttpClient;
+use crate::sources::traits::{FeedMetadata, FeedSource, FetchedFeed};
+use crate::sources::html_to_text;
+use crate::sources::rss_atom::{entry_image, RssAtomSource};
 
 pub struct MastodonSource {
-    client: Client,
+    http: HttpClient,
     rss_source: RssAtomSource,
 }
 
 impl MastodonSource {
     pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-            rss_source: RssAtomSource::new(),
-        }
+        Self::with_http(HttpClient::default())
     }
 
-    /// Extract plain text from HTML content, preserving some structure
-    fn html_to_text(html: &str) -> String {
-        let document = Html::parse_fragment(html);
-        let mut text = String::new();
-
-        for node in document.root_element().descendants() {
-            if let Some(text_node) = node.value().as_text() {
-                text.push_str(text_node);
-            }
-            // Add space after block elements to preserve word boundaries
-            if let Some(element) = node.value().as_element() {
-                match element.name() {
-                    "p" | "br" | "div" => text.push(' '),
-                    _ => {}
-                }
-            }
+    pub fn with_http(http: HttpClient) -> Self {
+        Self {
+            rss_source: RssAtomSource::with_http(http.clone()),
+            http,
         }
-
-        // Collapse whitespace and trim
-        text.split_whitespace().collect::<Vec<_>>().join(" ")
     }
 
     /// Truncate text to a reasonable length for a title
@@ -112,44 +91,48 @@ impl FeedSource for MastodonSource {
         user_regex.is_match(url)
     }
 
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
         let (instance, username) = self.extract_user_info(url)?;
         let feed_url = self.build_feed_url(&instance, &username);
 
         // Use the RSS source to validate the feed
-        let mut metadata = self.rss_source.validate(&feed_url)?;
+        let mut metadata = self.rss_source.validate(&feed_url, request)?;
         metadata.source_type = SourceType::Mastodon;
 
         Ok(metadata)
     }
 
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
         // Fetch and parse the feed ourselves to handle Mastodon's title-less posts
-        let response = self.client.get(&feed.feed_url).send()?;
+        let (response, redirect) = self
+            .http
+            .fetch_following(&feed.feed_url, &feed.settings.request)?;
         let bytes = response.bytes()?;
         let parsed = parser::parse(&bytes[..])
             .map_err(|e| FeederError::FeedParse(e.to_string()))?;
 
+        let title = parsed.title.map(|t| t.content);
         let articles: Vec<Article> = parsed
             .entries
             .into_iter()
             .map(|entry| {
+                let image = entry_image(&entry);
                 let id = entry.id;
 
+                // Extract text from content or summary
+                let html_content = entry
+                    .content
+                    .and_then(|c| c.body)
+                    .or_else(|| entry.summary.map(|s| s.content))
+                    .unwrap_or_default();
+                let text = html_to_text(&html_content);
+
                 // Mastodon posts typically don't have titles, so use the content/summary
                 let title = entry
                     .title
                     .map(|t| t.content)
                     .filter(|t| !t.is_empty())
                     .unwrap_or_else(|| {
-                        // Try to extract text from content or summary
-                        let html_content = entry
-                            .content
-                            .and_then(|c| c.body)
-                            .or_else(|| entry.summary.map(|s| s.content))
-                            .unwrap_or_default();
-
-                        let text = Self::html_to_text(&html_content);
                         if text.is_empty() {
                             "Untitled".to_string()
                         } else {
@@ -166,12 +149,18 @@ impl FeedSource for MastodonSource {
                     .map(|dt| dt.to_rfc3339());
 
                 Article::new(id, title)
+                    .with_summary(Some(text).filter(|t| !t.is_empty()))
                     .with_links(links)
                     .with_published(published)
+                    .with_image(image)
             })
             .collect();
 
-        Ok(articles)
+        Ok(FetchedFeed {
+            articles,
+            title,
+            redirect,
+        })
     }
 }
 
@@ -219,42 +208,42 @@ mod tests {
     #[test]
     fn test_html_to_text_simple() {
         let html = "<p>Hello world</p>";
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "Hello world");
     }
 
     #[test]
     fn test_html_to_text_with_links() {
         let html = r#"<p>Check out <a href="https://example.com">this link</a>!</p>"#;
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "Check out this link!");
     }
 
     #[test]
     fn test_html_to_text_multiple_paragraphs() {
         let html = "<p>First paragraph</p><p>Second paragraph</p>";
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "First paragraph Second paragraph");
     }
 
     #[test]
     fn test_html_to_text_with_hashtags() {
         let html = r#"<p>Post content <a href="https://mastodon.social/tags/test" class="mention hashtag">#<span>test</span></a></p>"#;
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "Post content #test");
     }
 
     #[test]
     fn test_html_to_text_strips_extra_whitespace() {
         let html = "<p>  Multiple   spaces   here  </p>";
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "Multiple spaces here");
     }
 
     #[test]
     fn test_html_to_text_empty() {
         let html = "";
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert_eq!(text, "");
     }
 
@@ -290,7 +279,7 @@ mod tests {
     fn test_html_to_text_real_mastodon_post() {
         // Real example from Humble Bundle bot
         let html = r#"<p>Design Unlimited Bundle Encore</p><p>Get CorelDRAW Standard 2024!</p><p><a href="https://www.humblebundle.com/software/design-unlimited-bundle-encore-software" target="_blank" rel="nofollow noopener" translate="no"><span class="invisible">https://www.</span><span class="ellipsis">humblebundle.com/software/desi</span><span class="invisible">gn-unlimited-bundle-encore-software</span></a></p><p><a href="https://tech.lgbt/tags/humblebundle" class="mention hashtag" rel="tag">#<span>humblebundle</span></a></p>"#;
-        let text = MastodonSource::html_to_text(html);
+        let text = html_to_text(html);
         assert!(text.starts_with("Design Unlimited Bundle Encore"));
         assert!(text.contains("CorelDRAW"));
     }
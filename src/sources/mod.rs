@@ -4,7 +4,33 @@ pub mod youtube;
 pub mod mastodon;
 pub mod wordpress;
 pub mod blogger;
+pub mod json_feed;
+pub mod page_watch;
 pub mod registry;
 
-pub use traits::{FeedSource, FeedMetadata};
+pub use traits::{FeedCandidate, FeedSource, FeedMetadata, FetchedFeed};
 pub use registry::SourceRegistry;
+
+use scraper::Html;
+
+/// Extract plain text from HTML content, preserving some structure
+pub(crate) fn html_to_text(html: &str) -> String {
+    let document = Html::parse_fragment(html);
+    let mut text = String::new();
+
+    for node in document.root_element().descendants() {
+        if let Some(text_node) = node.value().as_text() {
+            text.push_str(text_node);
+        }
+        // Add space after block elements to preserve word boundaries
+        if let Some(element) = node.value().as_element() {
+            match element.name() {
+                "p" | "br" | "div" => text.push(' '),
+                _ => {}
+            }
+        }
+    }
+
+    // Collapse whitespace and trim
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
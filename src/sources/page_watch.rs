@@ -0,0 +1,419 @@
+use scraper::{ElementRef, Html, Node, Selector};
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+
+use crate::domain::{Article, Feed, FeedType, RequestOptions, SourceType};
+use crate::errors::{FeederError, FeederResult};
+use crate::http::{HttpClient, Redirect};
+use crate::sources::traits::{FeedMetadata, FeedSource, FetchedFeed};
+use crate::storage::traits::FeedStateRepository;
+
+/// State keys used to remember the watched content between runs
+const STATE_HASH: &str = "page_watch.hash";
+const STATE_SNAPSHOT: &str = "page_watch.snapshot";
+const STATE_PREVIOUS: &str = "page_watch.previous";
+const STATE_CHANGES: &str = "page_watch.changes";
+
+/// Maximum number of changed lines included in a notification
+const MAX_DIFF_LINES: usize = 10;
+
+/// Elements whose text is never part of the visible page
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "head"];
+
+/// Elements that start a new line in the normalized text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "footer",
+    "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "nav", "ol",
+    "p", "pre", "section", "table", "td", "th", "tr", "ul",
+];
+
+/// Watches a regular web page and emits an article whenever its content changes.
+/// Never auto-detected: feeds are created with this source explicitly (`feeder add --watch`).
+pub struct PageWatchSource {
+    http: HttpClient,
+}
+
+impl PageWatchSource {
+    pub fn new() -> Self {
+        Self::with_http(HttpClient::default())
+    }
+
+    pub fn with_http(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Fetch the page's HTML, along with where redirects took the request
+    fn fetch_page(
+        &self,
+        url: &str,
+        request: &RequestOptions,
+    ) -> FeederResult<(String, Option<Redirect>)> {
+        let (response, redirect) = self.http.fetch_following(url, request)?;
+        if !response.status().is_success() {
+            return Err(FeederError::FeedValidation(format!(
+                "Page returned HTTP {}",
+                response.status().as_u16()
+            )));
+        }
+
+        Ok((response.text()?, redirect))
+    }
+
+    /// Extract the page title, if any
+    fn page_title(document: &Html) -> Option<String> {
+        let selector = Selector::parse("title").unwrap();
+        document
+            .select(&selector)
+            .next()
+            .map(|t| t.text().collect::<String>().trim().to_string())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Normalize the page (or the parts matching `selector`) to plain text,
+    /// one block per line with whitespace collapsed
+    fn extract_text(html: &str, selector: Option<&str>) -> FeederResult<String> {
+        let document = Html::parse_document(html);
+        let mut raw = String::new();
+
+        match selector {
+            Some(selector) => {
+                let parsed = Selector::parse(selector).map_err(|e| {
+                    FeederError::InvalidInput(format!("Invalid CSS selector '{}': {}", selector, e))
+                })?;
+
+                let mut matched = false;
+                for element in document.select(&parsed) {
+                    matched = true;
+                    Self::collect_text(element, &mut raw);
+                    raw.push('\n');
+                }
+
+                if !matched {
+                    return Err(FeederError::FeedParse(format!(
+                        "Selector '{}' matched nothing on the page",
+                        selector
+                    )));
+                }
+            }
+            None => Self::collect_text(document.root_element(), &mut raw),
+        }
+
+        let lines: Vec<String> = raw
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        Ok(lines.join("\n"))
+    }
+
+    fn collect_text(element: ElementRef<'_>, out: &mut String) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(child_element) => {
+                    let name = child_element.name();
+                    if SKIPPED_ELEMENTS.contains(&name) {
+                        continue;
+                    }
+
+                    let is_block = BLOCK_ELEMENTS.contains(&name);
+                    if is_block {
+                        out.push('\n');
+                    }
+                    if let Some(child) = ElementRef::wrap(child) {
+                        Self::collect_text(child, out);
+                    }
+                    if is_block {
+                        out.push('\n');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn content_hash(text: &str) -> String {
+        format!("{:x}", Sha256::digest(text.as_bytes()))
+    }
+
+    /// Short line-based diff, listing removed lines with "-" and added lines with "+"
+    fn short_diff(old: &str, new: &str) -> String {
+        let diff = TextDiff::from_lines(old, new);
+        let changes: Vec<String> = diff
+            .iter_all_changes()
+            .filter_map(|change| {
+                let sign = match change.tag() {
+                    ChangeTag::Delete => "-",
+                    ChangeTag::Insert => "+",
+                    ChangeTag::Equal => return None,
+                };
+                Some(format!("{} {}", sign, change.value().trim_end()))
+            })
+            .collect();
+
+        let mut lines: Vec<String> = changes.iter().take(MAX_DIFF_LINES).cloned().collect();
+        if changes.len() > MAX_DIFF_LINES {
+            lines.push(format!("(+{} more changed lines)", changes.len() - MAX_DIFF_LINES));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Build the article for the current content, updating the stored snapshot.
+    ///
+    /// The first version is stored as the baseline without an article. Each change after
+    /// that gets an ID from the content hash and a change counter, so the notified-article
+    /// cache emits it once, even when the page returns to an earlier version. The previous
+    /// snapshot is kept until the content changes again, so a failed notification is
+    /// retried with the same diff on the next run.
+    fn article_for_content(
+        feed: &Feed,
+        text: &str,
+        state: &dyn FeedStateRepository,
+    ) -> FeederResult<Option<Article>> {
+        let feed_id = feed
+            .id
+            .ok_or_else(|| FeederError::FeedNotFound("Feed has no ID".to_string()))?;
+        let hash = Self::content_hash(text);
+
+        let mut changes: u64 = state
+            .get(feed_id, STATE_CHANGES)?
+            .and_then(|changes| changes.parse().ok())
+            .unwrap_or(0);
+
+        match state.get(feed_id, STATE_HASH)? {
+            None => {
+                state.set(feed_id, STATE_SNAPSHOT, text)?;
+                state.set(feed_id, STATE_HASH, &hash)?;
+                return Ok(None);
+            }
+            Some(stored_hash) if stored_hash != hash => {
+                if let Some(snapshot) = state.get(feed_id, STATE_SNAPSHOT)? {
+                    state.set(feed_id, STATE_PREVIOUS, &snapshot)?;
+                }
+                changes += 1;
+                state.set(feed_id, STATE_SNAPSHOT, text)?;
+                state.set(feed_id, STATE_HASH, &hash)?;
+                state.set(feed_id, STATE_CHANGES, &changes.to_string())?;
+            }
+            Some(_) => {}
+        }
+
+        let Some(previous) = state.get(feed_id, STATE_PREVIOUS)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            Article::new(format!("{}-{}", changes, hash), "Page changed".to_string())
+                .with_content(Some(Self::short_diff(&previous, text)))
+                .with_links(vec![feed.url.clone()])
+                .with_published(Some(chrono::Utc::now().to_rfc3339())),
+        ))
+    }
+}
+
+impl Default for PageWatchSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeedSource for PageWatchSource {
+    fn source_type(&self) -> SourceType {
+        SourceType::PageWatch
+    }
+
+    fn can_handle(&self, _url: &str) -> bool {
+        // Any page could be watched, so this source is only used when asked for
+        false
+    }
+
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
+        let (html, redirect) = self.fetch_page(url, request)?;
+        let document = Html::parse_document(&html);
+
+        let title = Self::page_title(&document).unwrap_or_else(|| url.to_string());
+        let feed_url = match redirect {
+            Some(redirect) if redirect.permanent => redirect.url,
+            _ => url.to_string(),
+        };
+
+        Ok(FeedMetadata {
+            title,
+            feed_type: FeedType::Html,
+            feed_url,
+            source_type: SourceType::PageWatch,
+            description: None,
+        })
+    }
+
+    fn fetch_articles(&self, _feed: &Feed) -> FeederResult<FetchedFeed> {
+        // Without stored state there is nothing to diff against
+        Err(FeederError::InvalidInput(
+            "Watching a page needs the feed's stored state".to_string(),
+        ))
+    }
+
+    fn fetch_articles_with_state(
+        &self,
+        feed: &Feed,
+        state: &dyn FeedStateRepository,
+    ) -> FeederResult<FetchedFeed> {
+        let (html, redirect) = self.fetch_page(&feed.feed_url, &feed.settings.request)?;
+        let text = Self::extract_text(&html, feed.settings.selector.as_deref())?;
+
+        // The feed keeps the title it was added with; pages often change theirs
+        Ok(FetchedFeed {
+            articles: Self::article_for_content(feed, &text, state)?.into_iter().collect(),
+            redirect,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sqlite::{SqliteFeedRepository, SqliteFeedStateRepository, SqliteStorage};
+    use crate::storage::traits::FeedRepository;
+
+    const STATUS_PAGE: &str = r#"<html>
+<head><title>Example Status</title><style>body { color: red; }</style></head>
+<body>
+  <nav>Home | Incidents</nav>
+  <div id="status">
+    <h2>API</h2><p>Operational</p>
+    <h2>Dashboard</h2><p>Degraded   performance</p>
+  </div>
+  <script>var tracking = true;</script>
+</body>
+</html>"#;
+
+    fn setup() -> (Feed, SqliteFeedStateRepository) {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let feed_repo = SqliteFeedRepository::new(storage.clone());
+
+        let mut feed = Feed::new(
+            "https://status.example.com".to_string(),
+            "https://status.example.com".to_string(),
+            "Example Status".to_string(),
+            FeedType::Html,
+            SourceType::PageWatch,
+        );
+        feed.id = Some(feed_repo.add(&feed).unwrap());
+
+        (feed, SqliteFeedStateRepository::new(storage))
+    }
+
+    #[test]
+    fn test_never_auto_detected() {
+        let source = PageWatchSource::new();
+        assert!(!source.can_handle("https://status.example.com"));
+        assert_eq!(source.source_type(), SourceType::PageWatch);
+    }
+
+    #[test]
+    fn test_extract_text_whole_page() {
+        let text = PageWatchSource::extract_text(STATUS_PAGE, None).unwrap();
+
+        assert_eq!(
+            text,
+            "Home | Incidents\nAPI\nOperational\nDashboard\nDegraded performance"
+        );
+        assert!(!text.contains("tracking"), "Scripts should be skipped");
+        assert!(!text.contains("color"), "Styles should be skipped");
+    }
+
+    #[test]
+    fn test_extract_text_with_selector() {
+        let text = PageWatchSource::extract_text(STATUS_PAGE, Some("#status")).unwrap();
+        assert_eq!(text, "API\nOperational\nDashboard\nDegraded performance");
+    }
+
+    #[test]
+    fn test_extract_text_selector_without_match() {
+        let result = PageWatchSource::extract_text(STATUS_PAGE, Some("#missing"));
+        assert!(matches!(result, Err(FeederError::FeedParse(_))));
+    }
+
+    #[test]
+    fn test_extract_text_invalid_selector() {
+        let result = PageWatchSource::extract_text(STATUS_PAGE, Some("[[["));
+        assert!(matches!(result, Err(FeederError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_page_title() {
+        let document = Html::parse_document(STATUS_PAGE);
+        assert_eq!(
+            PageWatchSource::page_title(&document).as_deref(),
+            Some("Example Status")
+        );
+    }
+
+    #[test]
+    fn test_short_diff() {
+        let diff = PageWatchSource::short_diff("API\nOperational\n", "API\nMajor outage\n");
+        assert_eq!(diff, "- Operational\n+ Major outage");
+    }
+
+    #[test]
+    fn test_short_diff_is_capped() {
+        let old = (0..20).map(|i| format!("old {}", i)).collect::<Vec<_>>().join("\n");
+        let new = (0..20).map(|i| format!("new {}", i)).collect::<Vec<_>>().join("\n");
+
+        let diff = PageWatchSource::short_diff(&old, &new);
+        assert_eq!(diff.lines().count(), MAX_DIFF_LINES + 1);
+        assert!(diff.ends_with("(+30 more changed lines)"));
+    }
+
+    #[test]
+    fn test_first_snapshot_stored_silently() {
+        let (feed, state) = setup();
+
+        let article = PageWatchSource::article_for_content(&feed, "API\nOperational", &state);
+        assert!(article.unwrap().is_none());
+
+        // Fetching the baseline again still has nothing to report
+        let again = PageWatchSource::article_for_content(&feed, "API\nOperational", &state);
+        assert!(again.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_changed_content_emits_diff() {
+        let (feed, state) = setup();
+
+        PageWatchSource::article_for_content(&feed, "API\nOperational", &state).unwrap();
+        let changed = PageWatchSource::article_for_content(&feed, "API\nMajor outage", &state)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(changed.title, "Page changed");
+        assert_eq!(changed.content.as_deref(), Some("- Operational\n+ Major outage"));
+        assert_eq!(changed.links, vec!["https://status.example.com"]);
+
+        // Re-fetching the same version keeps the diff so failed notifications can be retried
+        let again = PageWatchSource::article_for_content(&feed, "API\nMajor outage", &state)
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.id, changed.id);
+        assert_eq!(again.content, changed.content);
+    }
+
+    #[test]
+    fn test_return_to_earlier_content_is_a_new_change() {
+        let (feed, state) = setup();
+        let mut ids = Vec::new();
+
+        PageWatchSource::article_for_content(&feed, "API\nOperational", &state).unwrap();
+        for text in ["API\nMajor outage", "API\nOperational", "API\nMajor outage"] {
+            let article = PageWatchSource::article_for_content(&feed, text, &state).unwrap();
+            ids.push(article.unwrap().id);
+        }
+
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+        assert_ne!(ids[1], ids[2]);
+    }
+}
@@ -1,10 +1,12 @@
-use crate::domain::{Article, Feed};
+use crate::domain::{Feed, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
+use crate::http::HttpClient;
+use crate::sources::traits::{FeedCandidate, FeedMetadata, FeedSource, FetchedFeed};
 use crate::sources::{
-    blogger::BloggerSource, mastodon::MastodonSource, rss_atom::RssAtomSource,
-    wordpress::WordPressSource, youtube::YouTubeSource,
+    blogger::BloggerSource, mastodon::MastodonSource, page_watch::PageWatchSource,
+    rss_atom::RssAtomSource, wordpress::WordPressSource, youtube::YouTubeSource,
 };
+use crate::storage::traits::FeedStateRepository;
 
 pub struct SourceRegistry {
     sources: Vec<Box<dyn FeedSource>>,
@@ -12,17 +14,23 @@ pub struct SourceRegistry {
 
 impl SourceRegistry {
     pub fn new() -> Self {
+        Self::with_http(HttpClient::default())
+    }
+
+    /// Register the built-in sources, all sharing one HTTP client
+    pub fn with_http(http: HttpClient) -> Self {
         let mut registry = Self {
             sources: Vec::new(),
         };
 
         // Register sources in order of specificity (most specific first)
         // The order matters for auto-detection
-        registry.register(Box::new(YouTubeSource::new()));
-        registry.register(Box::new(MastodonSource::new()));
-        registry.register(Box::new(BloggerSource::new()));
-        registry.register(Box::new(WordPressSource::new()));
-        registry.register(Box::new(RssAtomSource::new())); // Fallback
+        registry.register(Box::new(YouTubeSource::with_http(http.clone())));
+        registry.register(Box::new(MastodonSource::with_http(http.clone())));
+        registry.register(Box::new(BloggerSource::with_http(http.clone())));
+        registry.register(Box::new(WordPressSource::with_http(http.clone())));
+        registry.register(Box::new(PageWatchSource::with_http(http.clone()))); // Only used when requested
+        registry.register(Box::new(RssAtomSource::with_http(http))); // Fallback
 
         registry
     }
@@ -40,24 +48,75 @@ impl SourceRegistry {
     }
 
     /// Validate URL using appropriate source
-    pub fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
+    pub fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
         let source = self
             .find_source(url)
             .ok_or_else(|| FeederError::UnsupportedSource(url.to_string()))?;
 
-        source.validate(url)
+        source.validate(url, request)
     }
 
-    /// Fetch articles from a feed
-    pub fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
-        // Find source by source_type stored in feed
+    /// List the feeds offered at a URL by the source that handles it
+    pub fn discover(&self, url: &str, request: &RequestOptions) -> FeederResult<Vec<FeedCandidate>> {
         let source = self
-            .sources
+            .find_source(url)
+            .ok_or_else(|| FeederError::UnsupportedSource(url.to_string()))?;
+
+        source.discover(url, request)
+    }
+
+    /// Validate a feed chosen from `discover`, using the source that handles the page URL,
+    /// unless discovery validated it already
+    pub fn validate_candidate(
+        &self,
+        url: &str,
+        candidate: &FeedCandidate,
+        request: &RequestOptions,
+    ) -> FeederResult<FeedMetadata> {
+        if let Some(metadata) = &candidate.metadata {
+            return Ok(metadata.clone());
+        }
+
+        let source = self
+            .find_source(url)
+            .ok_or_else(|| FeederError::UnsupportedSource(url.to_string()))?;
+
+        source.validate(&candidate.url, request)
+    }
+
+    /// Find source by its type, bypassing URL auto-detection
+    pub fn source_for_type(&self, source_type: SourceType) -> FeederResult<&dyn FeedSource> {
+        self.sources
             .iter()
-            .find(|s| s.source_type() == feed.source_type)
-            .ok_or_else(|| FeederError::UnsupportedSource(feed.source_type.to_string()))?;
+            .find(|s| s.source_type() == source_type)
+            .map(|s| s.as_ref())
+            .ok_or_else(|| FeederError::UnsupportedSource(source_type.to_string()))
+    }
+
+    /// Validate URL with an explicitly chosen source
+    pub fn validate_as(
+        &self,
+        source_type: SourceType,
+        url: &str,
+        request: &RequestOptions,
+    ) -> FeederResult<FeedMetadata> {
+        self.source_for_type(source_type)?.validate(url, request)
+    }
+
+    /// Fetch articles from a feed
+    pub fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
+        // Find source by source_type stored in feed
+        self.source_for_type(feed.source_type)?.fetch_articles(feed)
+    }
 
-        source.fetch_articles(feed)
+    /// Fetch articles from a feed, giving stateful sources access to stored state
+    pub fn fetch_articles_with_state(
+        &self,
+        feed: &Feed,
+        state: &dyn FeedStateRepository,
+    ) -> FeederResult<FetchedFeed> {
+        self.source_for_type(feed.source_type)?
+            .fetch_articles_with_state(feed, state)
     }
 }
 
@@ -104,6 +163,17 @@ mod tests {
         assert_eq!(source.source_type(), SourceType::RssAtom);
     }
 
+    #[test]
+    fn test_page_watch_never_auto_detected() {
+        let registry = SourceRegistry::new();
+
+        let source = registry.find_source("https://status.example.com").unwrap();
+        assert_eq!(source.source_type(), SourceType::RssAtom);
+
+        let source = registry.source_for_type(SourceType::PageWatch).unwrap();
+        assert_eq!(source.source_type(), SourceType::PageWatch);
+    }
+
     #[test]
     fn test_youtube_urls_not_handled_by_rss() {
         let registry = SourceRegistry::new();
@@ -1,10 +1,16 @@
 use feed_rs::parser;
-use reqwest::blocking::Client;
+use scraper::{Html, Selector};
 use url::Url;
 
-use crate::domain::{Article, Feed, FeedType, SourceType};
+use crate::domain::{Article, Feed, FeedType, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
+use crate::http::{with_options, HttpClient, Redirect};
+use crate::sources::html_to_text;
+use crate::sources::json_feed::JsonFeed;
+use crate::sources::traits::{FeedCandidate, FeedMetadata, FeedSource, FetchedFeed};
+
+/// Title given to feeds that don't name themselves
+pub const UNTITLED_FEED: &str = "Untitled Feed";
 
 /// Common feed URL patterns to try when direct URL fails
 const FEED_PATTERNS: &[&str] = &[
@@ -19,49 +25,102 @@ const FEED_PATTERNS: &[&str] = &[
     "/.rss",            // Some static generators
 ];
 
+/// MIME types advertised in `<link rel="alternate">` tags that point at feeds. Plain
+/// `application/json` is left out: WordPress advertises its REST API (`wp-json`) with it.
+const FEED_MIME_TYPES: &[(&str, FeedType)] = &[
+    ("application/rss+xml", FeedType::Rss),
+    ("application/atom+xml", FeedType::Atom),
+    ("application/feed+json", FeedType::Json),
+];
+
 pub struct RssAtomSource {
-    client: Client,
+    http: HttpClient,
 }
 
 impl RssAtomSource {
     pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-        }
+        Self::with_http(HttpClient::default())
     }
 
-    /// Try to discover a valid feed URL by testing common patterns
-    /// Returns the first URL that successfully parses as a feed
-    fn discover_feed_url(&self, url: &str) -> FeederResult<(String, feed_rs::model::Feed)> {
-        // First, try the URL as-is (might already be a feed URL)
-        if let Ok(feed) = self.fetch_and_parse(url) {
-            return Ok((url.to_string(), feed));
+    pub fn with_http(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Find the feeds offered at a URL, best first.
+    ///
+    /// A URL that is itself a feed yields just that feed, validated from the response
+    /// already in hand. Otherwise the page's `<link rel="alternate">` tags are used, and
+    /// failing that the common `FEED_PATTERNS` are probed under the page's path and then
+    /// the host root.
+    fn discover_candidates(
+        &self,
+        url: &str,
+        request: &RequestOptions,
+    ) -> FeederResult<Vec<FeedCandidate>> {
+        let (response, redirect) = self.http.fetch_following(url, request)?;
+        let final_url = response.url().clone();
+        let content_type = Self::content_type(&response);
+        let bytes = response.bytes()?;
+
+        // The URL might already be a feed
+        if let Ok(feed) = Self::parse_bytes(&bytes, content_type.as_deref()) {
+            let metadata = Self::metadata(url, feed, redirect);
+            return Ok(vec![FeedCandidate {
+                url: url.to_string(),
+                title: Some(metadata.title.clone()),
+                feed_type: metadata.feed_type,
+                metadata: Some(metadata),
+            }]);
         }
 
-        // Parse the base URL
-        let parsed = Url::parse(url).map_err(|e| FeederError::InvalidUrl(e.to_string()))?;
-        let base_url = format!(
-            "{}://{}",
-            parsed.scheme(),
-            parsed.host_str().ok_or_else(|| FeederError::InvalidUrl("Missing host".to_string()))?
-        );
+        let html = String::from_utf8_lossy(&bytes);
+        let advertised = Self::candidates_from_html(&html, &final_url);
+        if !advertised.is_empty() {
+            return Ok(advertised);
+        }
 
-        // Try each pattern
-        let mut last_error = FeederError::FeedParse("No valid feed found".to_string());
+        for base in Self::probe_bases(&final_url) {
+            if let Some(candidate) = self.probe_patterns(&base, request) {
+                return Ok(vec![candidate]);
+            }
+        }
+
+        Err(FeederError::FeedParse("No valid feed found".to_string()))
+    }
+
+    /// Describe a parsed feed. Where it has moved for good is stored rather than
+    /// following the redirect on every fetch.
+    fn metadata(url: &str, feed: ParsedFeed, redirect: Option<Redirect>) -> FeedMetadata {
+        let feed_url = match redirect {
+            Some(redirect) if redirect.permanent => redirect.url,
+            _ => url.to_string(),
+        };
+
+        FeedMetadata {
+            title: feed.title.unwrap_or_else(|| UNTITLED_FEED.to_string()),
+            feed_type: feed.feed_type,
+            feed_url,
+            source_type: SourceType::RssAtom,
+            description: feed.description,
+        }
+    }
 
+    /// Try each of `FEED_PATTERNS` under `base`, returning the first that parses as a feed
+    fn probe_patterns(&self, base: &str, request: &RequestOptions) -> Option<FeedCandidate> {
         for pattern in FEED_PATTERNS {
-            let feed_url = format!("{}{}", base_url, pattern);
+            let feed_url = format!("{}{}", base, pattern);
 
-            // Check if URL returns success before trying to parse
-            match self.client.head(&feed_url).send() {
+            // Check if URL returns success before trying to parse; probes are sent
+            // once, since a missing pattern isn't worth retrying
+            match with_options(self.http.head(&feed_url), request).send() {
                 Ok(response) if response.status().is_success() => {
-                    // Try to parse as feed
-                    match self.fetch_and_parse(&feed_url) {
-                        Ok(feed) => return Ok((feed_url, feed)),
-                        Err(e) => last_error = e,
+                    if let Ok((feed, _)) = self.fetch_and_parse(&feed_url, request) {
+                        return Some(FeedCandidate {
+                            url: feed_url,
+                            title: feed.title,
+                            feed_type: feed.feed_type,
+                            metadata: None,
+                        });
                     }
                 }
                 Ok(_) => continue, // Non-success status, try next
@@ -69,35 +128,212 @@ impl RssAtomSource {
             }
         }
 
-        Err(last_error)
+        None
+    }
+
+    /// Collect feeds advertised with `<link rel="alternate" type="...">`, ranked best first
+    fn candidates_from_html(html: &str, base_url: &Url) -> Vec<FeedCandidate> {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("link[rel][type][href]").unwrap();
+
+        let mut candidates: Vec<FeedCandidate> = Vec::new();
+
+        for element in document.select(&selector) {
+            let attrs = element.value();
+
+            let is_alternate = attrs
+                .attr("rel")
+                .map(|rel| rel.split_whitespace().any(|r| r.eq_ignore_ascii_case("alternate")))
+                .unwrap_or(false);
+            if !is_alternate {
+                continue;
+            }
+
+            let feed_type = match attrs.attr("type").and_then(Self::feed_type_for_mime) {
+                Some(feed_type) => feed_type,
+                None => continue,
+            };
+
+            let href = attrs.attr("href").unwrap_or_default().trim();
+            let url = match base_url.join(href) {
+                Ok(url) => url.to_string(),
+                Err(_) => continue,
+            };
+
+            if candidates.iter().any(|c| c.url == url) {
+                continue;
+            }
+
+            candidates.push(FeedCandidate {
+                url,
+                title: attrs
+                    .attr("title")
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty()),
+                feed_type,
+                metadata: None,
+            });
+        }
+
+        // Stable sort keeps the page's own order within each rank
+        candidates.sort_by_key(Self::candidate_rank);
+        candidates
+    }
+
+    /// Map an advertised MIME type to a feed type, ignoring anything that isn't a feed
+    fn feed_type_for_mime(mime: &str) -> Option<FeedType> {
+        let mime = mime.split(';').next().unwrap_or_default().trim();
+        FEED_MIME_TYPES
+            .iter()
+            .find(|(m, _)| m.eq_ignore_ascii_case(mime))
+            .map(|(_, feed_type)| *feed_type)
+    }
+
+    /// Lower is better: main feeds before comment feeds, RSS/Atom before JSON Feed
+    fn candidate_rank(candidate: &FeedCandidate) -> (bool, u8) {
+        let is_comments = candidate
+            .title
+            .as_deref()
+            .map(|t| t.to_lowercase().contains("comments"))
+            .unwrap_or(false)
+            || candidate.url.to_lowercase().contains("/comments/");
+
+        let type_rank = match candidate.feed_type {
+            FeedType::Rss | FeedType::Atom => 0,
+            FeedType::Json => 1,
+            FeedType::Html => 2,
+        };
+
+        (is_comments, type_rank)
     }
 
-    fn fetch_and_parse(&self, url: &str) -> FeederResult<feed_rs::model::Feed> {
-        let response = self.client.get(url).send()?;
+    /// Base URLs to probe for feed patterns: the page's directory, then the host root
+    fn probe_bases(url: &Url) -> Vec<String> {
+        let root = format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default());
+        let root = match url.port() {
+            Some(port) => format!("{}:{}", root, port),
+            None => root,
+        };
+
+        let mut segments: Vec<&str> = url
+            .path_segments()
+            .map(|s| s.filter(|seg| !seg.is_empty()).collect())
+            .unwrap_or_default();
+
+        // Drop a trailing file name like index.html
+        if segments.last().map(|seg| seg.contains('.')).unwrap_or(false) {
+            segments.pop();
+        }
+
+        let mut bases = Vec::new();
+        if !segments.is_empty() {
+            bases.push(format!("{}/{}", root, segments.join("/")));
+        }
+        bases.push(root);
+        bases
+    }
+
+    /// Fetch and parse a feed, along with where redirects took the request
+    fn fetch_and_parse(
+        &self,
+        url: &str,
+        request: &RequestOptions,
+    ) -> FeederResult<(ParsedFeed, Option<Redirect>)> {
+        let (response, redirect) = self.http.fetch_following(url, request)?;
+        let content_type = Self::content_type(&response);
         let bytes = response.bytes()?;
 
-        Self::parse_bytes(&bytes)
+        Ok((Self::parse_bytes(&bytes, content_type.as_deref())?, redirect))
+    }
+
+    fn content_type(response: &reqwest::blocking::Response) -> Option<String> {
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
     }
 
-    fn parse_bytes(bytes: &[u8]) -> FeederResult<feed_rs::model::Feed> {
-        parser::parse(bytes).map_err(|e| FeederError::FeedParse(e.to_string()))
+    /// Parse RSS, Atom or JSON Feed. JSON is recognised by content type or leading `{`
+    /// and handled natively; everything else goes through feed-rs.
+    fn parse_bytes(bytes: &[u8], content_type: Option<&str>) -> FeederResult<ParsedFeed> {
+        if JsonFeed::looks_like_json(content_type, bytes) {
+            return JsonFeed::parse(bytes).map(ParsedFeed::from_json_feed);
+        }
+
+        parser::parse(bytes)
+            .map(ParsedFeed::from_syndication)
+            .map_err(|e| FeederError::FeedParse(e.to_string()))
     }
 
     /// Parse articles from raw feed bytes (used for testing)
     #[cfg(test)]
     fn articles_from_bytes(bytes: &[u8]) -> FeederResult<Vec<Article>> {
-        let parsed = Self::parse_bytes(bytes)?;
+        Ok(Self::parse_bytes(bytes, None)?.articles)
+    }
 
-        let articles: Vec<Article> = parsed
+    fn determine_feed_type(feed_type: &feed_rs::model::FeedType) -> FeedType {
+        match feed_type {
+            feed_rs::model::FeedType::Atom => FeedType::Atom,
+            feed_rs::model::FeedType::JSON => FeedType::Json,
+            _ => FeedType::Rss,
+        }
+    }
+}
+
+/// A fetched feed reduced to what feeder needs, whatever format it came in
+struct ParsedFeed {
+    title: Option<String>,
+    description: Option<String>,
+    feed_type: FeedType,
+    articles: Vec<Article>,
+}
+
+/// First thumbnail of an entry, else its first image from media content or enclosures
+pub(crate) fn entry_image(entry: &feed_rs::model::Entry) -> Option<String> {
+    let thumbnail = entry
+        .media
+        .iter()
+        .flat_map(|m| &m.thumbnails)
+        .map(|t| t.image.uri.clone())
+        .next();
+
+    thumbnail.or_else(|| {
+        entry
+            .media
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter(|c| {
+                c.content_type
+                    .as_ref()
+                    .is_some_and(|t| t.ty() == "image")
+            })
+            .find_map(|c| c.url.as_ref().map(|u| u.to_string()))
+    })
+}
+
+impl ParsedFeed {
+    fn from_syndication(feed: feed_rs::model::Feed) -> Self {
+        let articles = feed
             .entries
             .into_iter()
             .map(|entry| {
+                let image = entry_image(&entry);
                 let id = entry.id;
                 let title = entry
                     .title
                     .map(|t| t.content)
                     .unwrap_or_else(|| "Untitled".to_string());
 
+                // Content stays out of notifications to keep them concise; the summary is
+                // only kept for the history
+                let summary = entry
+                    .summary
+                    .map(|s| s.content)
+                    .or_else(|| entry.content.and_then(|c| c.body))
+                    .map(|html| html_to_text(&html))
+                    .filter(|text| !text.is_empty());
+
                 let links: Vec<String> = entry.links.into_iter().map(|l| l.href).collect();
 
                 let published = entry
@@ -105,20 +341,35 @@ impl RssAtomSource {
                     .or(entry.updated)
                     .map(|dt| dt.to_rfc3339());
 
+                let author = entry
+                    .authors
+                    .into_iter()
+                    .map(|a| a.name)
+                    .find(|n| !n.trim().is_empty());
+
                 Article::new(id, title)
+                    .with_summary(summary)
                     .with_links(links)
                     .with_published(published)
+                    .with_author(author)
+                    .with_image(image)
             })
             .collect();
 
-        Ok(articles)
+        Self {
+            title: feed.title.map(|t| t.content),
+            description: feed.description.map(|d| d.content),
+            feed_type: RssAtomSource::determine_feed_type(&feed.feed_type),
+            articles,
+        }
     }
 
-    fn determine_feed_type(feed: &feed_rs::model::Feed) -> FeedType {
-        match feed.feed_type {
-            feed_rs::model::FeedType::Atom => FeedType::Atom,
-            feed_rs::model::FeedType::JSON => FeedType::Json,
-            _ => FeedType::Rss,
+    fn from_json_feed(feed: JsonFeed) -> Self {
+        Self {
+            articles: feed.articles(),
+            title: feed.title,
+            description: feed.description,
+            feed_type: FeedType::Json,
         }
     }
 }
@@ -139,56 +390,36 @@ impl FeedSource for RssAtomSource {
         true
     }
 
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
-        // Use feed discovery to find a valid feed URL
-        let (feed_url, feed) = self.discover_feed_url(url)?;
-
-        let feed_type = Self::determine_feed_type(&feed);
-
-        let title = feed
-            .title
-            .map(|t| t.content)
-            .unwrap_or_else(|| "Untitled Feed".to_string());
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
+        // Use feed discovery to find the feed URL, taking the best candidate that
+        // turns out to be a feed
+        let mut error = FeederError::FeedParse("No valid feed found".to_string());
+        for candidate in self.discover_candidates(url, request)? {
+            if let Some(metadata) = candidate.metadata {
+                return Ok(metadata);
+            }
 
-        let description = feed.description.map(|d| d.content);
+            match self.fetch_and_parse(&candidate.url, request) {
+                Ok((feed, redirect)) => return Ok(Self::metadata(&candidate.url, feed, redirect)),
+                Err(e) => error = e,
+            }
+        }
 
-        Ok(FeedMetadata {
-            title,
-            feed_type,
-            feed_url,
-            source_type: SourceType::RssAtom,
-            description,
-        })
+        Err(error)
     }
 
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
-        let parsed = self.fetch_and_parse(&feed.feed_url)?;
-
-        let articles: Vec<Article> = parsed
-            .entries
-            .into_iter()
-            .map(|entry| {
-                let id = entry.id;
-                let title = entry
-                    .title
-                    .map(|t| t.content)
-                    .unwrap_or_else(|| "Untitled".to_string());
-
-                // Only extract title and links - skip content/summary to keep notifications concise
-                let links: Vec<String> = entry.links.into_iter().map(|l| l.href).collect();
-
-                let published = entry
-                    .published
-                    .or(entry.updated)
-                    .map(|dt| dt.to_rfc3339());
+    fn discover(&self, url: &str, request: &RequestOptions) -> FeederResult<Vec<FeedCandidate>> {
+        self.discover_candidates(url, request)
+    }
 
-                Article::new(id, title)
-                    .with_links(links)
-                    .with_published(published)
-            })
-            .collect();
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
+        let (parsed, redirect) = self.fetch_and_parse(&feed.feed_url, &feed.settings.request)?;
 
-        Ok(articles)
+        Ok(FetchedFeed {
+            articles: parsed.articles,
+            title: parsed.title,
+            redirect,
+        })
     }
 }
 
@@ -262,6 +493,10 @@ mod tests {
             articles[0].content.is_none(),
             "RSS articles should not include content/description"
         );
+        assert_eq!(
+            articles[0].summary.as_deref(),
+            Some("The Rust team is happy to announce a new version of Rust, 1.75.0. This release includes async fn in traits and many other improvements.")
+        );
         assert!(!articles[0].links.is_empty(), "Articles should have links");
         assert!(articles[0]
             .links
@@ -292,6 +527,72 @@ mod tests {
         assert!(article.links.iter().any(|l| l.contains("wasm-intro")));
     }
 
+    #[test]
+    fn test_entry_images_from_thumbnails_and_enclosures() {
+        let rss = br#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+  <channel>
+    <title>Photos</title>
+    <item>
+      <guid>1</guid>
+      <title>Thumbnail</title>
+      <media:thumbnail url="https://example.com/thumb.jpg"/>
+    </item>
+    <item>
+      <guid>2</guid>
+      <title>Enclosure</title>
+      <enclosure url="https://example.com/ep.mp3" type="audio/mpeg" length="1"/>
+      <media:content url="https://example.com/cover.png" type="image/png"/>
+    </item>
+    <item>
+      <guid>3</guid>
+      <title>Audio only</title>
+      <enclosure url="https://example.com/ep2.mp3" type="audio/mpeg" length="1"/>
+    </item>
+  </channel>
+</rss>"#;
+
+        let articles = RssAtomSource::articles_from_bytes(rss).unwrap();
+
+        assert_eq!(articles[0].image.as_deref(), Some("https://example.com/thumb.jpg"));
+        assert_eq!(articles[1].image.as_deref(), Some("https://example.com/cover.png"));
+        assert_eq!(articles[2].image, None);
+    }
+
+    const SAMPLE_JSON_FEED: &[u8] = br#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "JSON Blog",
+  "items": [
+    { "id": "1", "url": "https://example.org/1", "title": "First", "summary": "Short summary" }
+  ]
+}"#;
+
+    #[test]
+    fn test_json_feed_parsed_natively() {
+        let parsed = RssAtomSource::parse_bytes(SAMPLE_JSON_FEED, Some("application/feed+json")).unwrap();
+
+        assert_eq!(parsed.feed_type, FeedType::Json);
+        assert_eq!(parsed.title.as_deref(), Some("JSON Blog"));
+        assert_eq!(parsed.articles.len(), 1);
+        assert!(parsed.articles[0].content.is_none());
+    }
+
+    #[test]
+    fn test_json_feed_detected_without_content_type() {
+        let parsed = RssAtomSource::parse_bytes(SAMPLE_JSON_FEED, None).unwrap();
+        assert_eq!(parsed.feed_type, FeedType::Json);
+    }
+
+    #[test]
+    fn test_xml_feed_types_detected() {
+        let rss = RssAtomSource::parse_bytes(SAMPLE_RSS, Some("application/rss+xml")).unwrap();
+        assert_eq!(rss.feed_type, FeedType::Rss);
+        assert_eq!(rss.title.as_deref(), Some("Rust Blog"));
+
+        let atom = RssAtomSource::parse_bytes(SAMPLE_ATOM, None).unwrap();
+        assert_eq!(atom.feed_type, FeedType::Atom);
+    }
+
     #[test]
     fn test_rss_article_links_extracted() {
         let articles = RssAtomSource::articles_from_bytes(SAMPLE_RSS).unwrap();
@@ -337,6 +638,148 @@ mod tests {
         );
     }
 
+    const BLOG_PAGE: &str = r#"<html><head>
+  <title>Example Blog</title>
+  <link rel="stylesheet" type="text/css" href="/style.css">
+  <link rel="alternate" type="application/rss+xml" title="Example Blog &raquo; Comments Feed" href="/comments/feed/">
+  <link rel="alternate" type="application/feed+json" title="Example Blog JSON" href="feed.json">
+  <link rel="alternate" type="application/atom+xml" title="Example Blog" href="https://example.com/blog/atom.xml">
+  <link rel="alternate" hreflang="es" href="/es/">
+  <link rel="alternate" type="application/atom+xml" href="https://example.com/blog/atom.xml">
+</head><body></body></html>"#;
+
+    #[test]
+    fn test_candidates_from_html_ranked() {
+        let base = Url::parse("https://example.com/blog/").unwrap();
+        let candidates = RssAtomSource::candidates_from_html(BLOG_PAGE, &base);
+
+        let urls: Vec<&str> = candidates.iter().map(|c| c.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/blog/atom.xml",
+                "https://example.com/blog/feed.json",
+                "https://example.com/comments/feed/",
+            ]
+        );
+        assert_eq!(candidates[0].feed_type, FeedType::Atom);
+        assert_eq!(candidates[0].title.as_deref(), Some("Example Blog"));
+        assert_eq!(candidates[1].feed_type, FeedType::Json);
+        assert_eq!(candidates[2].feed_type, FeedType::Rss);
+    }
+
+    #[test]
+    fn test_candidates_from_html_none_advertised() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = "<html><head><title>No feeds</title></head></html>";
+        assert!(RssAtomSource::candidates_from_html(html, &base).is_empty());
+    }
+
+    #[test]
+    fn test_feed_type_for_mime() {
+        assert_eq!(RssAtomSource::feed_type_for_mime("application/rss+xml"), Some(FeedType::Rss));
+        assert_eq!(
+            RssAtomSource::feed_type_for_mime("application/atom+xml; charset=utf-8"),
+            Some(FeedType::Atom)
+        );
+        assert_eq!(RssAtomSource::feed_type_for_mime("application/feed+json"), Some(FeedType::Json));
+        assert_eq!(RssAtomSource::feed_type_for_mime("application/json"), None);
+        assert_eq!(RssAtomSource::feed_type_for_mime("text/css"), None);
+    }
+
+    #[test]
+    fn test_validate_falls_back_to_next_candidate() {
+        let mut server = mockito::Server::new();
+        let page = r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="Broken" href="/broken.xml">
+            <link rel="alternate" type="application/atom+xml" title="Posts" href="/atom.xml">
+        </head></html>"#;
+        let _page = server.mock("GET", "/").with_body(page).create();
+        let _broken = server.mock("GET", "/broken.xml").with_status(404).create();
+        let _atom = server
+            .mock("GET", "/atom.xml")
+            .with_body(SAMPLE_ATOM)
+            .create();
+
+        let metadata = RssAtomSource::new()
+            .validate(&server.url(), &RequestOptions::default())
+            .unwrap();
+
+        assert_eq!(metadata.feed_url, format!("{}/atom.xml", server.url()));
+        assert_eq!(metadata.feed_type, FeedType::Atom);
+    }
+
+    #[test]
+    fn test_direct_feed_fetched_once() {
+        let mut server = mockito::Server::new();
+        let feed = server
+            .mock("GET", "/atom.xml")
+            .with_body(SAMPLE_ATOM)
+            .expect(2)
+            .create();
+        let url = format!("{}/atom.xml", server.url());
+        let source = RssAtomSource::new();
+
+        let metadata = source.validate(&url, &RequestOptions::default()).unwrap();
+        let candidates = source.discover(&url, &RequestOptions::default()).unwrap();
+
+        // One request each: the feed is validated from the response discovery got
+        feed.assert();
+        assert_eq!(metadata.feed_url, url);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].metadata.as_ref(), Some(&metadata));
+    }
+
+    #[test]
+    fn test_discover_without_feeds_fails() {
+        let mut server = mockito::Server::new();
+        let _page = server.mock("GET", "/").with_body("<html></html>").create();
+        let _missing = server.mock("HEAD", mockito::Matcher::Any).with_status(404).create();
+
+        let result = RssAtomSource::new().discover(&server.url(), &RequestOptions::default());
+
+        assert!(matches!(result, Err(FeederError::FeedParse(_))));
+    }
+
+    #[test]
+    fn test_discover_sends_credentials() {
+        let mut server = mockito::Server::new();
+        let _feed = server
+            .mock("GET", "/private.atom")
+            .match_header("authorization", "Bearer tk")
+            .with_body(SAMPLE_ATOM)
+            .create();
+
+        let request = RequestOptions {
+            auth: Some(crate::domain::FeedAuth::Bearer { token: "tk".to_string() }),
+            ..RequestOptions::default()
+        };
+        let candidates = RssAtomSource::new()
+            .discover(&format!("{}/private.atom", server.url()), &request)
+            .unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].feed_type, FeedType::Atom);
+    }
+
+    #[test]
+    fn test_probe_bases_include_subpath() {
+        let url = Url::parse("https://example.com/blog/").unwrap();
+        assert_eq!(
+            RssAtomSource::probe_bases(&url),
+            vec!["https://example.com/blog", "https://example.com"]
+        );
+
+        let url = Url::parse("https://example.com/blog/index.html").unwrap();
+        assert_eq!(
+            RssAtomSource::probe_bases(&url),
+            vec!["https://example.com/blog", "https://example.com"]
+        );
+
+        let url = Url::parse("http://localhost:8080").unwrap();
+        assert_eq!(RssAtomSource::probe_bases(&url), vec!["http://localhost:8080"]);
+    }
+
     #[test]
     fn test_feed_patterns_no_duplicates() {
         let mut seen = std::collections::HashSet::new();
@@ -1,7 +1,9 @@
-use crate::domain::{Article, Feed, FeedType, SourceType};
+use crate::domain::{Article, Feed, FeedType, RequestOptions, SourceType};
 use crate::errors::FeederResult;
+use crate::http::Redirect;
+use crate::storage::traits::FeedStateRepository;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FeedMetadata {
     pub title: String,
     pub feed_type: FeedType,
@@ -10,6 +12,44 @@ pub struct FeedMetadata {
     pub description: Option<String>,
 }
 
+/// A feed advertised by or found on a web page, offered to the user when there are several
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCandidate {
+    pub url: String,
+    pub title: Option<String>,
+    pub feed_type: FeedType,
+    /// Set when finding the candidate already validated it, so adding it needn't
+    /// fetch the feed again
+    pub metadata: Option<FeedMetadata>,
+}
+
+/// A feed's articles as fetched, with what the fetch revealed about the feed itself
+#[derive(Debug, Clone, Default)]
+pub struct FetchedFeed {
+    pub articles: Vec<Article>,
+    /// The title the feed currently gives itself, if it has one
+    pub title: Option<String>,
+    /// Where the feed was served from, when requesting it was redirected
+    pub redirect: Option<Redirect>,
+}
+
+impl FetchedFeed {
+    pub fn new(articles: Vec<Article>) -> Self {
+        Self {
+            articles,
+            ..Default::default()
+        }
+    }
+
+    /// The URL the feed moved to, if every redirect on the way said it moved for good
+    pub fn moved_to(&self) -> Option<&str> {
+        self.redirect
+            .as_ref()
+            .filter(|redirect| redirect.permanent)
+            .map(|redirect| redirect.url.as_str())
+    }
+}
+
 pub trait FeedSource: Send + Sync {
     /// Identifies this source type
     fn source_type(&self) -> SourceType;
@@ -17,9 +57,27 @@ pub trait FeedSource: Send + Sync {
     /// Check if this source can handle the given URL
     fn can_handle(&self, url: &str) -> bool;
 
-    /// Validate that the URL points to a valid feed and return metadata
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata>;
+    /// Validate that the URL points to a valid feed and return metadata, sending the
+    /// feed's headers and credentials with every request
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata>;
+
+    /// List the feeds a URL offers, best first, sending the feed's headers and credentials.
+    /// Sources that map a URL to exactly one feed return nothing and leave the choice
+    /// to `validate`; sources that discover fail when they find no feed.
+    fn discover(&self, _url: &str, _request: &RequestOptions) -> FeederResult<Vec<FeedCandidate>> {
+        Ok(Vec::new())
+    }
+
+    /// Fetch articles from a feed, with the headers and credentials in its settings
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed>;
 
-    /// Fetch articles from a feed
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>>;
+    /// Fetch articles with access to state persisted for the feed between runs.
+    /// Stateless sources keep the default, which ignores the store.
+    fn fetch_articles_with_state(
+        &self,
+        feed: &Feed,
+        _state: &dyn FeedStateRepository,
+    ) -> FeederResult<FetchedFeed> {
+        self.fetch_articles(feed)
+    }
 }
@@ -1,24 +1,25 @@
-use reqwest::blocking::Client;
 use url::Url;
 
-use crate::domain::{Article, Feed, SourceType};
+use crate::domain::{Feed, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
+use crate::http::HttpClient;
+use crate::sources::traits::{FeedMetadata, FeedSource, FetchedFeed};
 use crate::sources::rss_atom::RssAtomSource;
 
 pub struct WordPressSource {
-    client: Client,
+    http: HttpClient,
     rss_source: RssAtomSource,
 }
 
 impl WordPressSource {
     pub fn new() -> Self {
+        Self::with_http(HttpClient::default())
+    }
+
+    pub fn with_http(http: HttpClient) -> Self {
         Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-            rss_source: RssAtomSource::new(),
+            rss_source: RssAtomSource::with_http(http.clone()),
+            http,
         }
     }
 
@@ -37,15 +38,15 @@ impl WordPressSource {
 
         let wp_json_url = format!("{}/wp-json/", base_url);
 
-        // Try HEAD request first (faster)
-        if let Ok(response) = self.client.head(&wp_json_url).send() {
+        // Try HEAD request first (faster); detection probes are not retried
+        if let Ok(response) = self.http.head(&wp_json_url).send() {
             if response.status().is_success() {
                 return true;
             }
         }
 
         // Fall back to GET request
-        if let Ok(response) = self.client.get(&wp_json_url).send() {
+        if let Ok(response) = self.http.get(&wp_json_url).send() {
             return response.status().is_success();
         }
 
@@ -87,17 +88,17 @@ impl FeedSource for WordPressSource {
         self.is_wordpress(url)
     }
 
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
         let feed_url = self.build_feed_url(url)?;
 
         // Use the RSS source to validate the feed
-        let mut metadata = self.rss_source.validate(&feed_url)?;
+        let mut metadata = self.rss_source.validate(&feed_url, request)?;
         metadata.source_type = SourceType::WordPress;
 
         Ok(metadata)
     }
 
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
         self.rss_source.fetch_articles(feed)
     }
 }
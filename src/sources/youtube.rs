@@ -1,30 +1,31 @@
 use regex::Regex;
-use reqwest::blocking::Client;
 use scraper::{Html, Selector};
 
-use crate::domain::{Article, Feed, SourceType};
+use crate::domain::{Feed, RequestOptions, SourceType};
 use crate::errors::{FeederError, FeederResult};
-use crate::sources::traits::{FeedMetadata, FeedSource};
+use crate::http::HttpClient;
+use crate::sources::traits::{FeedMetadata, FeedSource, FetchedFeed};
 use crate::sources::rss_atom::RssAtomSource;
 
 pub struct YouTubeSource {
-    client: Client,
+    http: HttpClient,
     rss_source: RssAtomSource,
 }
 
 impl YouTubeSource {
     pub fn new() -> Self {
+        Self::with_http(HttpClient::default())
+    }
+
+    pub fn with_http(http: HttpClient) -> Self {
         Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-            rss_source: RssAtomSource::new(),
+            rss_source: RssAtomSource::with_http(http.clone()),
+            http,
         }
     }
 
     /// Extract channel ID from various YouTube URL formats
-    fn extract_channel_id(&self, url: &str) -> FeederResult<String> {
+    fn extract_channel_id(&self, url: &str, request: &RequestOptions) -> FeederResult<String> {
         // Pattern 1: /channel/UC... URLs
         let channel_regex = Regex::new(r"youtube\.com/channel/(UC[\w-]{22})").unwrap();
         if let Some(caps) = channel_regex.captures(url) {
@@ -33,7 +34,7 @@ impl YouTubeSource {
 
         // Pattern 2: /@username or /c/customname URLs - need to fetch page and extract
         if url.contains("/@") || url.contains("/c/") || url.contains("/user/") {
-            return self.extract_channel_id_from_page(url);
+            return self.extract_channel_id_from_page(url, request);
         }
 
         Err(FeederError::InvalidUrl(
@@ -42,8 +43,12 @@ impl YouTubeSource {
     }
 
     /// Fetch YouTube page and extract channel ID from meta tags or page content
-    fn extract_channel_id_from_page(&self, url: &str) -> FeederResult<String> {
-        let response = self.client.get(url).send()?;
+    fn extract_channel_id_from_page(
+        &self,
+        url: &str,
+        request: &RequestOptions,
+    ) -> FeederResult<String> {
+        let response = self.http.fetch_with(url, request)?;
         let html = response.text()?;
         let document = Html::parse_document(&html);
 
@@ -126,14 +131,14 @@ impl FeedSource for YouTubeSource {
             || url.contains("youtube.com/user/")
     }
 
-    fn validate(&self, url: &str) -> FeederResult<FeedMetadata> {
+    fn validate(&self, url: &str, request: &RequestOptions) -> FeederResult<FeedMetadata> {
         // Normalize the URL by stripping tab paths like /videos, /shorts, /streams, etc.
         let normalized_url = self.normalize_channel_url(url);
-        let channel_id = self.extract_channel_id(&normalized_url)?;
+        let channel_id = self.extract_channel_id(&normalized_url, request)?;
         let feed_url = self.build_feed_url(&channel_id);
 
         // Check if the feed URL returns a successful response before trying to parse
-        let response = self.client.get(&feed_url).send()?;
+        let response = self.http.fetch_with(&feed_url, request)?;
         if !response.status().is_success() {
             return Err(FeederError::FeedValidation(format!(
                 "YouTube RSS feed not available for this channel (HTTP {}). \
@@ -143,13 +148,13 @@ impl FeedSource for YouTubeSource {
         }
 
         // Use the RSS source to validate the feed
-        let mut metadata = self.rss_source.validate(&feed_url)?;
+        let mut metadata = self.rss_source.validate(&feed_url, request)?;
         metadata.source_type = SourceType::YouTube;
 
         Ok(metadata)
     }
 
-    fn fetch_articles(&self, feed: &Feed) -> FeederResult<Vec<Article>> {
+    fn fetch_articles(&self, feed: &Feed) -> FeederResult<FetchedFeed> {
         self.rss_source.fetch_articles(feed)
     }
 }
@@ -0,0 +1,186 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::file::format::write_atomic;
+use crate::storage::file::DataFormat;
+use crate::storage::memory::{HistoryRecord, Persistence, StoreData, STORE_VERSION};
+
+/// Modification time and size of a file, if it exists
+type Stamp = Option<(SystemTime, u64)>;
+
+/// The file behind a `FileStorage`. It is read again whenever it changes on disk,
+/// so edits by hand or by another feeder process are picked up.
+///
+/// The article history only grows, so it is kept apart in a JSON Lines file next
+/// to it (`feeder.toml.history`) that new articles are appended to. The store file
+/// itself stays small enough to rewrite on every change.
+pub(crate) struct DataFile {
+    path: PathBuf,
+    history_path: PathBuf,
+    /// Locked while a process changes the store
+    lock_path: PathBuf,
+    format: DataFormat,
+    saved: Mutex<Saved>,
+}
+
+/// The files as last read or written
+#[derive(Default)]
+struct Saved {
+    /// Stamps of the store and the history file
+    stamps: Option<(Stamp, Stamp)>,
+    /// What the history file holds
+    history: Arc<Vec<HistoryRecord>>,
+}
+
+impl DataFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            format: DataFormat::from_path(&path),
+            history_path: with_suffix(&path, ".history"),
+            lock_path: with_suffix(&path, ".flock"),
+            path,
+            saved: Mutex::new(Saved::default()),
+        }
+    }
+
+    fn saved(&self) -> FeederResult<std::sync::MutexGuard<'_, Saved>> {
+        self.saved
+            .lock()
+            .map_err(|_| FeederError::Storage("store lock poisoned".to_string()))
+    }
+
+    fn read_history(&self) -> FeederResult<Vec<HistoryRecord>> {
+        let content = match std::fs::read_to_string(&self.history_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        // A line without its newline was cut short by a crash while appending
+        content
+            .split_inclusive('\n')
+            .filter(|line| line.ends_with('\n') && !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| {
+                    let path = self.history_path.display();
+                    FeederError::Storage(format!("Can't read {}: {}", path, e))
+                })
+            })
+            .collect()
+    }
+
+    /// Bring the history file in line with `history`, appending when it only grew
+    fn save_history(&self, saved: &[HistoryRecord], history: &[HistoryRecord]) -> FeederResult<()> {
+        let encode = |records: &[HistoryRecord]| -> FeederResult<String> {
+            records.iter().try_fold(String::new(), |mut lines, record| {
+                let line = serde_json::to_string(record)
+                    .map_err(|e| FeederError::Storage(e.to_string()))?;
+                lines.push_str(&line);
+                lines.push('\n');
+                Ok(lines)
+            })
+        };
+
+        match history.strip_prefix(saved) {
+            Some([]) => Ok(()),
+            Some(added) => {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.history_path)?;
+                file.write_all(encode(added)?.as_bytes())?;
+                Ok(())
+            }
+            None => write_atomic(&self.history_path, &encode(history)?),
+        }
+    }
+}
+
+impl Persistence for DataFile {
+    fn lock(&self) -> FeederResult<Box<dyn Send>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)?;
+        file.lock()?;
+        Ok(Box::new(file))
+    }
+
+    /// The time and size of the store file may not change with every write, so
+    /// `always` reads it regardless. The history file grows with every change.
+    fn load_if_changed(&self, always: bool) -> FeederResult<Option<StoreData>> {
+        let current = (stamp(&self.path)?, stamp(&self.history_path)?);
+        // Not created yet: it is written on the first change
+        if current.0.is_none() {
+            return Ok(None);
+        }
+
+        let mut saved = self.saved()?;
+        if !always && saved.stamps == Some(current) {
+            return Ok(None);
+        }
+
+        let mut data = self.format.read(&self.path).map_err(|e| {
+            FeederError::Storage(format!("Can't read {}: {}", self.path.display(), e))
+        })?;
+        // Files written by hand may leave out the version and feed IDs
+        if data.version == 0 {
+            data.version = STORE_VERSION;
+        }
+        data.normalize();
+
+        // Stores written before the history had a file of its own hold it inline;
+        // it moves to the history file on the next change
+        let history = match saved.stamps {
+            Some((_, history_stamp)) if history_stamp == current.1 => saved.history.clone(),
+            _ => Arc::new(self.read_history()?),
+        };
+        if data.history.is_empty() {
+            data.history = history.clone();
+        } else {
+            Arc::make_mut(&mut data.history).extend(history.iter().cloned());
+        }
+
+        saved.stamps = Some(current);
+        saved.history = history;
+        Ok(Some(data))
+    }
+
+    fn save(&self, data: &StoreData) -> FeederResult<()> {
+        let mut saved = self.saved()?;
+
+        if !Arc::ptr_eq(&saved.history, &data.history) {
+            self.save_history(&saved.history, &data.history)?;
+            saved.history = data.history.clone();
+        }
+
+        let store = StoreData {
+            history: Arc::default(),
+            ..data.clone()
+        };
+        self.format.write(&self.path, &store)?;
+
+        saved.stamps = Some((stamp(&self.path)?, stamp(&self.history_path)?));
+        Ok(())
+    }
+}
+
+fn stamp(path: &Path) -> FeederResult<Stamp> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(Some((metadata.modified()?, metadata.len()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `feeder.toml` -> `feeder.toml<suffix>`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
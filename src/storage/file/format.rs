@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::memory::{StoreData, STORE_VERSION};
+
+/// How a store file is written, picked by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Toml,
+    Json,
+}
+
+impl DataFormat {
+    /// JSON for `.json` files, TOML for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => DataFormat::Json,
+            _ => DataFormat::Toml,
+        }
+    }
+
+    pub(crate) fn encode(self, data: &StoreData) -> FeederResult<String> {
+        match self {
+            DataFormat::Toml => toml::to_string_pretty(data).map_err(|e| FeederError::Storage(e.to_string())),
+            DataFormat::Json => serde_json::to_string_pretty(data)
+                .map(|json| json + "\n")
+                .map_err(|e| FeederError::Storage(e.to_string())),
+        }
+    }
+
+    /// Parse a store, refusing ones written by a newer version of feeder
+    pub(crate) fn decode(self, content: &str) -> FeederResult<StoreData> {
+        let data: StoreData = match self {
+            DataFormat::Toml => toml::from_str(content).map_err(|e| FeederError::Storage(e.to_string()))?,
+            DataFormat::Json => {
+                serde_json::from_str(content).map_err(|e| FeederError::Storage(e.to_string()))?
+            }
+        };
+
+        if data.version > STORE_VERSION {
+            return Err(FeederError::Storage(format!(
+                "it has version {}, newer than the {} this version of feeder supports",
+                data.version, STORE_VERSION
+            )));
+        }
+
+        Ok(data)
+    }
+
+    pub(crate) fn read(self, path: &Path) -> FeederResult<StoreData> {
+        self.decode(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write `data` next to `path` and rename it into place, so readers never see
+    /// a half-written file
+    pub(crate) fn write(self, path: &Path, data: &StoreData) -> FeederResult<()> {
+        write_atomic(path, &self.encode(data)?)
+    }
+}
+
+/// Write `content` next to `path` and rename it into place
+pub(crate) fn write_atomic(path: &Path, content: &str) -> FeederResult<()> {
+    let partial = partial_path(path);
+
+    let result = std::fs::write(&partial, content).and_then(|()| std::fs::rename(&partial, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+
+    Ok(result?)
+}
+
+/// `feeder.toml` -> `feeder.toml.partial`
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    path.with_file_name(name)
+}
@@ -0,0 +1,272 @@
+mod data_file;
+mod format;
+mod run_lock;
+
+use std::path::{Path, PathBuf};
+
+use crate::errors::FeederResult;
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::memory::{
+    MemoryArticleCacheRepository, MemoryArticleHistoryRepository, MemoryFeedRepository,
+    MemoryFeedStateRepository, MemoryStorage,
+};
+use crate::storage::traits::{
+    ArticleCacheRepository, ArticleHistoryRepository, FeedRepository, FeedStateRepository,
+    RunGuard, Storage,
+};
+
+use data_file::DataFile;
+
+pub use format::DataFormat;
+pub use run_lock::FileRunLock;
+
+/// Storage backend keeping everything in one TOML or JSON file (by extension),
+/// meant to be read and edited by hand in small setups.
+///
+/// Every change rewrites the file, except for the article history, which is
+/// appended to a file of its own. Processes changing the store take turns through
+/// an advisory lock, and changes made by hand or by another process are picked up
+/// on the next access. Feeds added by hand need no `id`; one is assigned when the
+/// file is read.
+#[derive(Clone)]
+pub struct FileStorage {
+    store: MemoryStorage,
+    path: PathBuf,
+}
+
+impl FileStorage {
+    /// Open the store at `path`; the file is created on the first change
+    pub fn open<P: AsRef<Path>>(path: P) -> FeederResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let store = MemoryStorage::persisted(Box::new(DataFile::new(path.clone())))?;
+        Ok(Self { store, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn store(&self) -> &MemoryStorage {
+        &self.store
+    }
+}
+
+impl Storage for FileStorage {
+    fn feeds(&self) -> Box<dyn FeedRepository> {
+        Box::new(MemoryFeedRepository::new(self.store.clone()))
+    }
+
+    fn article_cache(&self) -> Box<dyn ArticleCacheRepository> {
+        Box::new(MemoryArticleCacheRepository::new(self.store.clone()))
+    }
+
+    fn feed_state(&self) -> Box<dyn FeedStateRepository> {
+        Box::new(MemoryFeedStateRepository::new(self.store.clone()))
+    }
+
+    fn history(&self) -> Box<dyn ArticleHistoryRepository> {
+        Box::new(MemoryArticleHistoryRepository::new(self.store.clone()))
+    }
+
+    fn lock_run(&self) -> FeederResult<RunGuard> {
+        Ok(Box::new(FileStorage::lock_run(self)?))
+    }
+
+    fn backup(&self, path: &Path) -> FeederResult<()> {
+        self.store.backup(path)
+    }
+
+    fn restore(&self, path: &Path) -> FeederResult<i64> {
+        self.store.restore(path)
+    }
+
+    fn check(&self) -> FeederResult<IntegrityReport> {
+        self.store.check()
+    }
+
+    /// Every change already rewrites the file and the history only grows, so there
+    /// is nothing to reclaim
+    fn compact(&self) -> FeederResult<u64> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Article, Feed, FeedType, SourceType};
+
+    fn feed(url: &str) -> Feed {
+        Feed::new(
+            url.to_string(),
+            format!("{}/feed", url),
+            url.to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        )
+    }
+
+    fn round_trip(file_name: &str) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(file_name);
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert!(!path.exists());
+
+        let mut watched = feed("https://status.example.com");
+        watched.settings.selector = Some("#incidents".to_string());
+        let id = storage.feeds().add(&watched).unwrap();
+        storage.article_cache().mark_notified("k1", id, "First").unwrap();
+        storage.feed_state().set(id, "etag", "\"abc\"").unwrap();
+        storage
+            .history()
+            .record("k1", id, &Article::new("1".to_string(), "First".to_string()))
+            .unwrap();
+
+        // A later process sees everything
+        let reopened = FileStorage::open(&path).unwrap();
+        let stored = reopened.feeds().get_by_id(id).unwrap().unwrap();
+        assert_eq!(stored.settings.selector.as_deref(), Some("#incidents"));
+        assert!(reopened.article_cache().is_notified("k1").unwrap());
+        assert_eq!(reopened.feed_state().get(id, "etag").unwrap().as_deref(), Some("\"abc\""));
+        assert_eq!(reopened.history().recent(None, 10).unwrap()[0].title, "First");
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        round_trip("feeder.toml");
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        round_trip("feeder.json");
+    }
+
+    #[test]
+    fn test_hand_written_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[feeds]]
+url = "https://blog.example.com"
+feed_url = "https://blog.example.com/feed.xml"
+title = "Example Blog"
+feed_type = "rss"
+source_type = "rssatom"
+"#,
+        )
+        .unwrap();
+
+        let storage = FileStorage::open(&path).unwrap();
+        let feeds = storage.feeds().get_all().unwrap();
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].id, Some(1));
+
+        // The next feed gets a fresh ID and the file is rewritten with both
+        let id = storage.feeds().add(&feed("https://other.example")).unwrap();
+        assert_eq!(id, 2);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("Example Blog"));
+        assert!(content.contains("https://other.example"));
+    }
+
+    #[test]
+    fn test_changes_by_other_process_picked_up() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.json");
+
+        let first = FileStorage::open(&path).unwrap();
+        let second = FileStorage::open(&path).unwrap();
+
+        first.feeds().add(&feed("https://a.example")).unwrap();
+        second.feeds().add(&feed("https://b.example")).unwrap();
+
+        assert_eq!(first.feeds().get_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_history_appended_to_its_own_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.toml");
+        let history_path = dir.path().join("feeder.toml.history");
+
+        let storage = FileStorage::open(&path).unwrap();
+        let id = storage.feeds().add(&feed("https://a.example")).unwrap();
+        let history = storage.history();
+        history.record("k1", id, &Article::new("1".to_string(), "First".to_string())).unwrap();
+        let first = std::fs::read_to_string(&history_path).unwrap();
+
+        // Changing anything else leaves the history file alone
+        storage.feed_state().set(id, "etag", "\"abc\"").unwrap();
+        assert_eq!(std::fs::read_to_string(&history_path).unwrap(), first);
+
+        history.record("k2", id, &Article::new("2".to_string(), "Second".to_string())).unwrap();
+        let appended = std::fs::read_to_string(&history_path).unwrap();
+        assert!(appended.starts_with(&first));
+        assert_eq!(appended.lines().count(), 2);
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("First"));
+
+        let reopened = FileStorage::open(&path).unwrap();
+        assert_eq!(reopened.history().count(None).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_inline_history_moves_to_its_own_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "feeds": [{"id": 1, "url": "https://a.example", "feed_url": "https://a.example/feed",
+                "title": "A", "feed_type": "rss", "source_type": "rssatom"}],
+                "history": [{"cache_key": "k1", "feed_id": 1, "title": "Old", "notified_at": "2024-01-01 00:00:00"}]}"#,
+        )
+        .unwrap();
+
+        let storage = FileStorage::open(&path).unwrap();
+        storage
+            .history()
+            .record("k2", 1, &Article::new("2".to_string(), "New".to_string()))
+            .unwrap();
+
+        let reopened = FileStorage::open(&path).unwrap();
+        let recent = reopened.history().recent(None, 10).unwrap();
+        let titles: Vec<_> = recent.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["New", "Old"]);
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("Old"));
+    }
+
+    #[test]
+    fn test_concurrent_changes_are_not_lost() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.json");
+
+        let writers: Vec<_> = (0..2)
+            .map(|writer| {
+                let storage = FileStorage::open(&path).unwrap();
+                std::thread::spawn(move || {
+                    for i in 0..20 {
+                        let url = format!("https://{}-{}.example", writer, i);
+                        storage.feeds().add(&feed(&url)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.feeds().get_all().unwrap().len(), 40);
+    }
+
+    #[test]
+    fn test_unreadable_file_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.toml");
+        std::fs::write(&path, "feeds = \"not a list\"").unwrap();
+
+        assert!(FileStorage::open(&path).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::file::FileStorage;
+
+/// A lock file not touched for this long is treated as abandoned
+const STALE_AFTER: Duration = Duration::from_secs(120);
+const HEARTBEAT_EVERY: Duration = Duration::from_secs(20);
+
+/// Exclusive lock on a store file held for the length of a run.
+///
+/// Kept as a `.lock` file next to the store, holding the owner's PID. A background
+/// thread touches it while held; a lock file left by a process that died goes
+/// stale after two minutes and is taken over. Removed on drop.
+pub struct FileRunLock {
+    path: PathBuf,
+    owner: String,
+    stop: Option<Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl FileStorage {
+    /// Take the run lock, failing if another live process holds it.
+    ///
+    /// Cache entries still claimed by an earlier run that died before sending them
+    /// are released, so those articles are tried again.
+    pub fn lock_run(&self) -> FeederResult<FileRunLock> {
+        self.lock_run_with(STALE_AFTER, HEARTBEAT_EVERY)
+    }
+
+    fn lock_run_with(&self, stale_after: Duration, heartbeat_every: Duration) -> FeederResult<FileRunLock> {
+        let path = lock_path(self.path());
+        let owner = format!("{} {:016x}", std::process::id(), fastrand::u64(..));
+
+        create_lock_file(&path, &owner, stale_after)?;
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let heartbeat_path = path.clone();
+        let heartbeat = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(heartbeat_every) {
+                if let Ok(file) = OpenOptions::new().write(true).open(&heartbeat_path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+            }
+        });
+
+        let lock = FileRunLock {
+            path,
+            owner,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        };
+        self.store().write(|data| {
+            data.release_claims();
+            Ok(())
+        })?;
+
+        Ok(lock)
+    }
+}
+
+/// Create the lock file holding `owner`, replacing it if it went stale
+fn create_lock_file(path: &Path, owner: &str, stale_after: Duration) -> FeederResult<()> {
+    // A second attempt only follows removing a stale file
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                file.write_all(owner.as_bytes())?;
+                return Ok(());
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let age = std::fs::metadata(path)?
+                    .modified()?
+                    .elapsed()
+                    .unwrap_or_default();
+
+                if age < stale_after {
+                    let holder = std::fs::read_to_string(path).unwrap_or_default();
+                    let pid = holder.split_whitespace().next().unwrap_or("?");
+                    return Err(FeederError::RunInProgress(format!(
+                        "process {} holds {}",
+                        pid,
+                        path.display()
+                    )));
+                }
+
+                match std::fs::remove_file(path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(FeederError::RunInProgress(format!(
+        "another process took over {}",
+        path.display()
+    )))
+}
+
+/// `feeder.toml` -> `feeder.toml.lock`
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+impl Drop for FileRunLock {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+
+        // Leave the file alone if another process took it over meanwhile
+        if std::fs::read_to_string(&self.path).is_ok_and(|holder| holder == self.owner) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    fn store_path() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.toml");
+        (dir, path)
+    }
+
+    #[test]
+    fn test_second_lock_fails_until_first_released() {
+        let (_dir, path) = store_path();
+        // Two processes open the store separately
+        let first = FileStorage::open(&path).unwrap();
+        let second = FileStorage::open(&path).unwrap();
+
+        let lock = first.lock_run().unwrap();
+        let err = second.lock_run().err().unwrap();
+        assert!(matches!(err, FeederError::RunInProgress(_)), "{}", err);
+
+        drop(lock);
+        assert!(!lock_path(&path).exists());
+        assert!(second.lock_run().is_ok());
+    }
+
+    #[test]
+    fn test_stale_lock_taken_over() {
+        let (_dir, path) = store_path();
+        let storage = FileStorage::open(&path).unwrap();
+
+        // A process that died: its lock file is never touched or removed
+        let stale = File::create(lock_path(&path)).unwrap();
+        stale
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+
+        assert!(storage.lock_run().is_ok());
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_lock_fresh() {
+        let (_dir, path) = store_path();
+        let storage = FileStorage::open(&path).unwrap();
+
+        let _lock = storage
+            .lock_run_with(STALE_AFTER, Duration::from_millis(10))
+            .unwrap();
+        File::options()
+            .write(true)
+            .open(lock_path(&path))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let age = std::fs::metadata(lock_path(&path))
+            .unwrap()
+            .modified()
+            .unwrap()
+            .elapsed()
+            .unwrap_or_default();
+        assert!(age < Duration::from_secs(60));
+    }
+}
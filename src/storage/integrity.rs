@@ -0,0 +1,26 @@
+/// Findings of `Storage::check`
+#[derive(Debug, Default)]
+pub struct IntegrityReport {
+    /// Problems with the store itself, such as SQLite's `PRAGMA integrity_check`
+    /// messages; empty when it is sound
+    pub integrity_errors: Vec<String>,
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+    /// Cache entries whose feed no longer exists
+    pub orphaned_notifications: usize,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.orphaned_notifications == 0
+    }
+}
+
+/// A row referring to a missing row of another table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
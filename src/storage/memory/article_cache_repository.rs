@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::{Article, RetentionPolicy};
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::memory::article_history_repository::record_entry;
+use crate::storage::memory::store::{now, CacheEntry, CacheState, StoreData};
+use crate::storage::memory::MemoryStorage;
+use crate::storage::traits::{ArticleCacheRepository, FETCHED_AT_KEY};
+
+pub struct MemoryArticleCacheRepository {
+    storage: MemoryStorage,
+}
+
+impl MemoryArticleCacheRepository {
+    pub fn new(storage: MemoryStorage) -> Self {
+        Self { storage }
+    }
+}
+
+/// Like the SQL backends' foreign keys, entries must belong to a stored feed
+fn check_feed(data: &StoreData, feed_id: i64) -> FeederResult<()> {
+    match data.feed(feed_id) {
+        Some(_) => Ok(()),
+        None => Err(FeederError::FeedNotFound(feed_id.to_string())),
+    }
+}
+
+fn mark_sent(
+    data: &mut StoreData,
+    cache_key: &str,
+    feed_id: i64,
+    title: &str,
+    now: &str,
+) -> FeederResult<()> {
+    check_feed(data, feed_id)?;
+
+    match data.notified.iter_mut().find(|entry| entry.cache_key == cache_key) {
+        Some(entry) => entry.state = CacheState::Sent,
+        None => data.notified.push(CacheEntry {
+            cache_key: cache_key.to_string(),
+            feed_id,
+            title: title.to_string(),
+            notified_at: now.to_string(),
+            last_seen_at: Some(now.to_string()),
+            state: CacheState::Sent,
+        }),
+    }
+    Ok(())
+}
+
+impl ArticleCacheRepository for MemoryArticleCacheRepository {
+    fn is_notified(&self, cache_key: &str) -> FeederResult<bool> {
+        self.storage
+            .read(|data| data.notified.iter().any(|entry| entry.cache_key == cache_key))
+    }
+
+    fn mark_notified(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<()> {
+        self.storage.write(|data| {
+            mark_sent(data, cache_key, feed_id, title, &now())
+        })
+    }
+
+    fn mark_notified_batch(&self, feed_id: i64, entries: &[(String, &Article)]) -> FeederResult<()> {
+        self.storage.write(|data| {
+            let now = now();
+            for (cache_key, article) in entries {
+                mark_sent(data, cache_key, feed_id, &article.title, &now)?;
+                record_entry(data, cache_key, feed_id, article, &now);
+            }
+            Ok(())
+        })
+    }
+
+    fn claim(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<bool> {
+        self.storage.write(|data| {
+            check_feed(data, feed_id)?;
+            if data.notified.iter().any(|entry| entry.cache_key == cache_key) {
+                return Ok(false);
+            }
+
+            let now = now();
+            data.notified.push(CacheEntry {
+                cache_key: cache_key.to_string(),
+                feed_id,
+                title: title.to_string(),
+                notified_at: now.clone(),
+                last_seen_at: Some(now),
+                state: CacheState::Claimed,
+            });
+            Ok(true)
+        })
+    }
+
+    fn release(&self, cache_key: &str) -> FeederResult<()> {
+        self.storage.write(|data| {
+            data.notified
+                .retain(|entry| entry.cache_key != cache_key || entry.state != CacheState::Claimed);
+            Ok(())
+        })
+    }
+
+    fn get_unnotified(&self, cache_keys: &[String]) -> FeederResult<Vec<String>> {
+        self.storage.read(|data| {
+            let notified: HashSet<&str> =
+                data.notified.iter().map(|entry| entry.cache_key.as_str()).collect();
+
+            cache_keys
+                .iter()
+                .filter(|key| !notified.contains(key.as_str()))
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn mark_seen(&self, cache_keys: &[String], seen_at: &str) -> FeederResult<()> {
+        let keys: HashSet<&str> = cache_keys.iter().map(String::as_str).collect();
+
+        self.storage.write(|data| {
+            for entry in data
+                .notified
+                .iter_mut()
+                .filter(|entry| keys.contains(entry.cache_key.as_str()))
+            {
+                entry.last_seen_at = Some(seen_at.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    fn prune(&self, policy: &RetentionPolicy) -> FeederResult<usize> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+
+        let cutoff = policy.days.map(|days| {
+            (chrono::Utc::now() - chrono::Duration::days(days.into()))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        });
+
+        self.storage.write(|data| {
+            // Same rules as the SQLite backend: entries still in their feed's last
+            // successful fetch are kept, as are all entries of feeds never fetched
+            let fetched_at: HashMap<i64, &str> = data
+                .state
+                .iter()
+                .filter(|entry| entry.key == FETCHED_AT_KEY)
+                .map(|entry| (entry.feed_id, entry.value.as_str()))
+                .collect();
+
+            let mut by_feed: HashMap<i64, Vec<usize>> = HashMap::new();
+            for (index, entry) in data.notified.iter().enumerate() {
+                by_feed.entry(entry.feed_id).or_default().push(index);
+            }
+
+            let mut removed = HashSet::new();
+            for (feed_id, mut indexes) in by_feed {
+                let Some(fetched_at) = fetched_at.get(&feed_id) else {
+                    continue;
+                };
+
+                // Newest first; later entries win ties, like a higher row ID
+                indexes.sort_by(|a, b| {
+                    data.notified[*b]
+                        .notified_at
+                        .cmp(&data.notified[*a].notified_at)
+                        .then(b.cmp(a))
+                });
+
+                for (position, index) in indexes.into_iter().enumerate() {
+                    let entry = &data.notified[index];
+                    let too_old = cutoff.as_ref().is_none_or(|cutoff| entry.notified_at < *cutoff);
+                    let too_many = policy
+                        .keep_per_feed
+                        .is_none_or(|keep| position >= keep as usize);
+                    let seen = entry.last_seen_at.as_deref().unwrap_or(&entry.notified_at);
+
+                    if too_old && too_many && seen < *fetched_at {
+                        removed.insert(index);
+                    }
+                }
+            }
+
+            let mut index = 0;
+            data.notified.retain(|_| {
+                index += 1;
+                !removed.contains(&(index - 1))
+            });
+
+            Ok(removed.len())
+        })
+    }
+
+    fn rekey(&self, feed_id: i64, old_prefix: &str, new_prefix: &str) -> FeederResult<usize> {
+        self.storage.write(|data| {
+            let mut taken: HashSet<String> =
+                data.notified.iter().map(|entry| entry.cache_key.clone()).collect();
+            let mut moved = 0;
+
+            for entry in data.notified.iter_mut().filter(|entry| entry.feed_id == feed_id) {
+                let Some(rest) = entry.cache_key.strip_prefix(old_prefix) else {
+                    continue;
+                };
+                let cache_key = format!("{}{}", new_prefix, rest);
+                if taken.insert(cache_key.clone()) {
+                    taken.remove(&entry.cache_key);
+                    entry.cache_key = cache_key;
+                    moved += 1;
+                }
+            }
+
+            Ok(moved)
+        })
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use crate::domain::{Article, HistoryEntry};
+use crate::errors::FeederResult;
+use crate::storage::history_snippet;
+use crate::storage::memory::store::{now, HistoryRecord, StoreData};
+use crate::storage::memory::MemoryStorage;
+use crate::storage::traits::ArticleHistoryRepository;
+
+pub struct MemoryArticleHistoryRepository {
+    storage: MemoryStorage,
+}
+
+impl MemoryArticleHistoryRepository {
+    pub fn new(storage: MemoryStorage) -> Self {
+        Self { storage }
+    }
+
+    /// Records matching `keep`, newest first, joined with their feed's title
+    fn entries(
+        data: &StoreData,
+        limit: usize,
+        keep: impl Fn(&HistoryRecord) -> bool,
+    ) -> Vec<HistoryEntry> {
+        let mut records: Vec<&HistoryRecord> = data.history.iter().rev().filter(|r| keep(r)).collect();
+        records.sort_by(|a, b| b.notified_at.cmp(&a.notified_at));
+
+        records
+            .into_iter()
+            .filter_map(|record| {
+                let feed = data.feed(record.feed_id)?;
+                Some(HistoryEntry {
+                    feed_id: record.feed_id,
+                    feed_title: feed.title.clone(),
+                    title: record.title.clone(),
+                    link: record.link.clone(),
+                    published: record.published.clone(),
+                    author: record.author.clone(),
+                    snippet: record.snippet.clone(),
+                    notified_at: record.notified_at.clone(),
+                })
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Whether `record` contains every word of `words` in its title, author or text,
+/// ignoring case. A trailing `*` is accepted for parity with the SQLite backend's
+/// prefix matches; substrings match anyway.
+fn matches(record: &HistoryRecord, words: &[String]) -> bool {
+    let text = [Some(&record.title), record.author.as_ref(), record.snippet.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|field| field.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    words.iter().all(|word| text.contains(word.as_str()))
+}
+
+/// Keep a notified article in the history, unless its cache key already is
+pub(super) fn record_entry(
+    data: &mut StoreData,
+    cache_key: &str,
+    feed_id: i64,
+    article: &Article,
+    notified_at: &str,
+) {
+    if data.history.iter().any(|record| record.cache_key == cache_key) {
+        return;
+    }
+
+    Arc::make_mut(&mut data.history).push(HistoryRecord {
+        cache_key: cache_key.to_string(),
+        feed_id,
+        title: article.title.clone(),
+        link: article.links.first().cloned(),
+        published: article.published.clone(),
+        author: article.author.clone(),
+        snippet: history_snippet(article),
+        notified_at: notified_at.to_string(),
+    });
+}
+
+impl ArticleHistoryRepository for MemoryArticleHistoryRepository {
+    fn record(&self, cache_key: &str, feed_id: i64, article: &Article) -> FeederResult<()> {
+        self.storage.write(|data| {
+            record_entry(data, cache_key, feed_id, article, &now());
+            Ok(())
+        })
+    }
+
+    fn recent(&self, feed_id: Option<i64>, limit: usize) -> FeederResult<Vec<HistoryEntry>> {
+        self.storage.read(|data| {
+            Self::entries(data, limit, |record| {
+                feed_id.is_none_or(|id| record.feed_id == id)
+            })
+        })
+    }
+
+    fn count(&self, feed_id: Option<i64>) -> FeederResult<usize> {
+        self.storage.read(|data| {
+            data.history
+                .iter()
+                .filter(|record| feed_id.is_none_or(|id| record.feed_id == id))
+                .count()
+        })
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        feed_id: Option<i64>,
+        since: Option<String>,
+        limit: usize,
+    ) -> FeederResult<Vec<HistoryEntry>> {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| word.strip_suffix('*').filter(|stem| !stem.is_empty()).unwrap_or(word))
+            .map(str::to_lowercase)
+            .collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.storage.read(|data| {
+            Self::entries(data, limit, |record| {
+                feed_id.is_none_or(|id| record.feed_id == id)
+                    && since.as_ref().is_none_or(|since| record.notified_at >= *since)
+                    && matches(record, &words)
+            })
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::domain::Feed;
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::memory::store::{now, StoreData};
+use crate::storage::memory::MemoryStorage;
+use crate::storage::traits::FeedRepository;
+
+pub struct MemoryFeedRepository {
+    storage: MemoryStorage,
+}
+
+impl MemoryFeedRepository {
+    pub fn new(storage: MemoryStorage) -> Self {
+        Self { storage }
+    }
+
+    fn insert(data: &mut StoreData, feed: &Feed) -> FeederResult<i64> {
+        if data.feeds.iter().any(|stored| stored.url == feed.url) {
+            return Err(FeederError::FeedAlreadyExists(feed.url.clone()));
+        }
+
+        let id = data.next_feed_id;
+        data.next_feed_id += 1;
+        data.feeds.push(Feed {
+            id: Some(id),
+            created_at: Some(now()),
+            ..feed.clone()
+        });
+
+        Ok(id)
+    }
+}
+
+impl FeedRepository for MemoryFeedRepository {
+    fn add(&self, feed: &Feed) -> FeederResult<i64> {
+        self.storage.write(|data| Self::insert(data, feed))
+    }
+
+    fn add_batch(&self, feeds: &[Feed]) -> FeederResult<Vec<i64>> {
+        self.storage
+            .write(|data| feeds.iter().map(|feed| Self::insert(data, feed)).collect())
+    }
+
+    fn update(&self, feed: &Feed) -> FeederResult<()> {
+        let id = feed
+            .id
+            .ok_or_else(|| FeederError::FeedNotFound("Feed has no ID".to_string()))?;
+
+        self.storage.write(|data| {
+            let stored = data
+                .feeds
+                .iter_mut()
+                .find(|stored| stored.id == Some(id))
+                .ok_or_else(|| FeederError::FeedNotFound(id.to_string()))?;
+
+            *stored = Feed {
+                id: stored.id,
+                created_at: stored.created_at.take(),
+                ..feed.clone()
+            };
+            Ok(())
+        })
+    }
+
+    fn remove(&self, id: i64) -> FeederResult<()> {
+        self.storage.write(|data| {
+            data.feeds.retain(|feed| feed.id != Some(id));
+            data.notified.retain(|entry| entry.feed_id != id);
+            data.state.retain(|entry| entry.feed_id != id);
+            if data.history.iter().any(|record| record.feed_id == id) {
+                Arc::make_mut(&mut data.history).retain(|record| record.feed_id != id);
+            }
+            Ok(())
+        })
+    }
+
+    fn get_all(&self) -> FeederResult<Vec<Feed>> {
+        self.storage.read(|data| {
+            // Newest first, like the SQLite backend
+            let mut feeds = data.feeds.clone();
+            feeds.reverse();
+            feeds.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            feeds
+        })
+    }
+
+    fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>> {
+        self.storage.read(|data| data.feed(id).cloned())
+    }
+
+    fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>> {
+        self.storage
+            .read(|data| data.feeds.iter().find(|feed| feed.url == url).cloned())
+    }
+
+    fn exists(&self, url: &str) -> FeederResult<bool> {
+        self.storage.read(|data| data.feeds.iter().any(|feed| feed.url == url))
+    }
+}
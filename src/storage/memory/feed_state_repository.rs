@@ -0,0 +1,43 @@
+use crate::errors::FeederResult;
+use crate::storage::memory::store::StateEntry;
+use crate::storage::memory::MemoryStorage;
+use crate::storage::traits::FeedStateRepository;
+
+pub struct MemoryFeedStateRepository {
+    storage: MemoryStorage,
+}
+
+impl MemoryFeedStateRepository {
+    pub fn new(storage: MemoryStorage) -> Self {
+        Self { storage }
+    }
+}
+
+impl FeedStateRepository for MemoryFeedStateRepository {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>> {
+        self.storage.read(|data| {
+            data.state
+                .iter()
+                .find(|entry| entry.feed_id == feed_id && entry.key == key)
+                .map(|entry| entry.value.clone())
+        })
+    }
+
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()> {
+        self.storage.write(|data| {
+            match data
+                .state
+                .iter_mut()
+                .find(|entry| entry.feed_id == feed_id && entry.key == key)
+            {
+                Some(entry) => entry.value = value.to_string(),
+                None => data.state.push(StateEntry {
+                    feed_id,
+                    key: key.to_string(),
+                    value: value.to_string(),
+                }),
+            }
+            Ok(())
+        })
+    }
+}
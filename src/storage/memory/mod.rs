@@ -0,0 +1,16 @@
+mod store;
+mod feed_repository;
+mod article_cache_repository;
+mod article_history_repository;
+mod feed_state_repository;
+mod run_lock;
+
+pub use store::{MemoryStorage, STORE_VERSION};
+pub(crate) use store::{HistoryRecord, Persistence, StoreData};
+#[cfg(feature = "postgres")]
+pub(crate) use store::{CacheEntry, CacheState, StateEntry};
+pub use feed_repository::MemoryFeedRepository;
+pub use article_cache_repository::MemoryArticleCacheRepository;
+pub use article_history_repository::MemoryArticleHistoryRepository;
+pub use feed_state_repository::MemoryFeedStateRepository;
+pub use run_lock::MemoryRunLock;
@@ -0,0 +1,59 @@
+use std::sync::atomic::Ordering;
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::memory::MemoryStorage;
+
+/// Run lock of a `MemoryStorage`, which only other users of the same storage in
+/// this process can see. Released on drop.
+pub struct MemoryRunLock {
+    storage: MemoryStorage,
+}
+
+impl MemoryStorage {
+    /// Take the run lock, failing if it is already held. Entries still claimed by
+    /// an earlier run are released, so those articles are tried again.
+    pub fn lock_run(&self) -> FeederResult<MemoryRunLock> {
+        if self
+            .locked()
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(FeederError::RunInProgress(
+                "the lock is held by this process".to_string(),
+            ));
+        }
+
+        let lock = MemoryRunLock {
+            storage: self.clone(),
+        };
+        self.write(|data| {
+            data.release_claims();
+            Ok(())
+        })?;
+
+        Ok(lock)
+    }
+}
+
+impl Drop for MemoryRunLock {
+    fn drop(&mut self) {
+        self.storage.locked().store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_lock_fails_until_first_released() {
+        let storage = MemoryStorage::new();
+
+        let lock = storage.lock_run().unwrap();
+        let err = storage.clone().lock_run().err().unwrap();
+        assert!(matches!(err, FeederError::RunInProgress(_)), "{}", err);
+
+        drop(lock);
+        assert!(storage.lock_run().is_ok());
+    }
+}
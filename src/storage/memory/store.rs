@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Feed;
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::file::DataFormat;
+use crate::storage::integrity::{ForeignKeyViolation, IntegrityReport};
+use crate::storage::memory::{
+    MemoryArticleCacheRepository, MemoryArticleHistoryRepository, MemoryFeedRepository,
+    MemoryFeedStateRepository,
+};
+use crate::storage::traits::{
+    ArticleCacheRepository, ArticleHistoryRepository, FeedRepository, FeedStateRepository,
+    RunGuard, Storage,
+};
+
+/// Version of the `StoreData` layout, bumped when it changes incompatibly
+pub const STORE_VERSION: i64 = 1;
+
+/// Everything a `MemoryStorage` holds. This is also the layout of the files
+/// `FileStorage` reads and writes, so field names are part of that format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct StoreData {
+    pub version: i64,
+    /// Feed IDs are never reused, like SQLite's AUTOINCREMENT
+    pub next_feed_id: i64,
+    pub feeds: Vec<Feed>,
+    pub notified: Vec<CacheEntry>,
+    pub state: Vec<StateEntry>,
+    /// Shared between copies of the data, so changing anything else doesn't copy
+    /// the history, which only grows
+    pub history: Arc<Vec<HistoryRecord>>,
+}
+
+/// A notified or claimed article, as in SQLite's `notified_articles`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    pub cache_key: String,
+    pub feed_id: i64,
+    #[serde(default)]
+    pub title: String,
+    pub notified_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_at: Option<String>,
+    #[serde(default)]
+    pub state: CacheState,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CacheState {
+    #[default]
+    Sent,
+    /// Reserved by a run that hasn't confirmed sending it yet
+    Claimed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StateEntry {
+    pub feed_id: i64,
+    pub key: String,
+    pub value: String,
+}
+
+/// A notified article kept for history and search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct HistoryRecord {
+    pub cache_key: String,
+    pub feed_id: i64,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    pub notified_at: String,
+}
+
+impl StoreData {
+    pub fn new() -> Self {
+        Self {
+            version: STORE_VERSION,
+            next_feed_id: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Fill in what a hand-edited file may leave out: IDs of added feeds and the
+    /// next free ID
+    pub fn normalize(&mut self) {
+        let highest = self.feeds.iter().filter_map(|feed| feed.id).max().unwrap_or(0);
+        self.next_feed_id = self.next_feed_id.max(highest + 1);
+
+        for feed in self.feeds.iter_mut().filter(|feed| feed.id.is_none()) {
+            feed.id = Some(self.next_feed_id);
+            self.next_feed_id += 1;
+        }
+    }
+
+    /// Read the backup at `path`, TOML or JSON by its extension, brought to the
+    /// current version. Also returns the version it was written with.
+    pub fn read_backup(path: &Path) -> FeederResult<(Self, i64)> {
+        if !path.is_file() {
+            return Err(FeederError::InvalidInput(format!("No backup at {}", path.display())));
+        }
+
+        let mut backup = DataFormat::from_path(path).read(path).map_err(|e| {
+            FeederError::InvalidInput(format!("Can't restore {}: {}", path.display(), e))
+        })?;
+        let version = backup.version;
+        if version < 1 {
+            return Err(FeederError::InvalidInput(format!(
+                "Can't restore {}: it is not a feeder store (no version)",
+                path.display()
+            )));
+        }
+        backup.normalize();
+        // Claims belonged to runs of the backed-up store, which are long gone
+        backup.release_claims();
+        backup.version = STORE_VERSION;
+
+        Ok((backup, version))
+    }
+
+    pub fn feed(&self, id: i64) -> Option<&Feed> {
+        self.feeds.iter().find(|feed| feed.id == Some(id))
+    }
+
+    /// Drop cache entries of articles claimed but never confirmed as sent
+    pub fn release_claims(&mut self) {
+        self.notified.retain(|entry| entry.state != CacheState::Claimed);
+    }
+
+    /// Look for duplicates and rows pointing at missing feeds
+    pub fn check(&self) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        let mut ids = HashSet::new();
+        let mut urls = HashSet::new();
+        for feed in &self.feeds {
+            if let Some(id) = feed.id {
+                if !ids.insert(id) {
+                    report.integrity_errors.push(format!("feed ID {} is used twice", id));
+                }
+            }
+            if !urls.insert(feed.url.as_str()) {
+                report.integrity_errors.push(format!("feed {} is listed twice", feed.url));
+            }
+        }
+
+        let mut keys = HashSet::new();
+        for entry in &self.notified {
+            if !keys.insert(entry.cache_key.as_str()) {
+                report
+                    .integrity_errors
+                    .push(format!("cache key {} is listed twice", entry.cache_key));
+            }
+        }
+
+        report.orphaned_notifications = self
+            .notified
+            .iter()
+            .filter(|entry| !ids.contains(&entry.feed_id))
+            .count();
+
+        let missing_feed = |table: &str, feed_id: i64| ForeignKeyViolation {
+            table: table.to_string(),
+            rowid: Some(feed_id),
+            parent: "feeds".to_string(),
+        };
+        report.foreign_key_violations.extend(
+            self.state
+                .iter()
+                .filter(|entry| !ids.contains(&entry.feed_id))
+                .map(|entry| missing_feed("state", entry.feed_id)),
+        );
+        report.foreign_key_violations.extend(
+            self.history
+                .iter()
+                .filter(|record| !ids.contains(&record.feed_id))
+                .map(|record| missing_feed("history", record.feed_id)),
+        );
+
+        report
+    }
+}
+
+/// Where a `MemoryStorage` keeps a copy of its data between processes
+pub(crate) trait Persistence: Send + Sync {
+    /// Keep other processes from changing the stored data until the returned
+    /// guard is dropped
+    fn lock(&self) -> FeederResult<Box<dyn Send>>;
+    /// The stored data if it changed since it was last loaded or saved. A quick
+    /// check can miss changes, so with `always` it is read anyway.
+    fn load_if_changed(&self, always: bool) -> FeederResult<Option<StoreData>>;
+    fn save(&self, data: &StoreData) -> FeederResult<()>;
+}
+
+/// Storage backend keeping everything in memory.
+///
+/// Clones share the same data, so repositories created from one storage see each
+/// other's changes. Useful in tests and when embedding feeder as a library; nothing
+/// is kept once the last clone is dropped. `FileStorage` builds on it to persist
+/// the data to a file.
+#[derive(Clone)]
+pub struct MemoryStorage {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    data: Mutex<StoreData>,
+    persistence: Option<Box<dyn Persistence>>,
+    /// Whether this process holds the run lock
+    locked: AtomicBool,
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::build(StoreData::new(), None)
+    }
+
+    /// A storage reading its data from `persistence` and saving every change back
+    pub(crate) fn persisted(persistence: Box<dyn Persistence>) -> FeederResult<Self> {
+        let data = persistence.load_if_changed(true)?.unwrap_or_else(StoreData::new);
+        Ok(Self::build(data, Some(persistence)))
+    }
+
+    fn build(data: StoreData, persistence: Option<Box<dyn Persistence>>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                data: Mutex::new(data),
+                persistence,
+                locked: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// The data, reloaded first if another process changed it. `changing` reads
+    /// persisted data again even if it looks unchanged, as changes must not be
+    /// based on an outdated copy.
+    fn data(&self, changing: bool) -> FeederResult<MutexGuard<'_, StoreData>> {
+        let mut data = self
+            .shared
+            .data
+            .lock()
+            .map_err(|_| FeederError::Storage("store lock poisoned".to_string()))?;
+
+        if let Some(persistence) = &self.shared.persistence {
+            if let Some(fresh) = persistence.load_if_changed(changing)? {
+                *data = fresh;
+            }
+        }
+
+        Ok(data)
+    }
+
+    pub(crate) fn read<T>(&self, f: impl FnOnce(&StoreData) -> T) -> FeederResult<T> {
+        Ok(f(&*self.data(false)?))
+    }
+
+    /// Change the data as one transaction: if `f` fails nothing it did is kept.
+    /// Persisted storage is locked against other processes from reading the data
+    /// to saving the result, which happens before returning.
+    pub(crate) fn write<T>(&self, f: impl FnOnce(&mut StoreData) -> FeederResult<T>) -> FeederResult<T> {
+        let _lock = match &self.shared.persistence {
+            Some(persistence) => Some(persistence.lock()?),
+            None => None,
+        };
+        let mut data = self.data(true)?;
+
+        let mut changed = data.clone();
+        let result = f(&mut changed)?;
+
+        if changed != *data {
+            if let Some(persistence) = &self.shared.persistence {
+                persistence.save(&changed)?;
+            }
+            *data = changed;
+        }
+
+        Ok(result)
+    }
+
+    pub(crate) fn locked(&self) -> &AtomicBool {
+        &self.shared.locked
+    }
+
+    /// Write a copy of the data to `path`, as TOML or JSON by its extension
+    pub(crate) fn export(&self, path: &Path) -> FeederResult<()> {
+        let data = self.read(StoreData::clone)?;
+        DataFormat::from_path(path).write(path, &data)
+    }
+
+    /// Replace the data with the copy at `path`, returning its version
+    pub(crate) fn import(&self, path: &Path) -> FeederResult<i64> {
+        let (backup, version) = StoreData::read_backup(path)?;
+
+        self.write(|data| {
+            *data = backup;
+            Ok(())
+        })?;
+
+        Ok(version)
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn feeds(&self) -> Box<dyn FeedRepository> {
+        Box::new(MemoryFeedRepository::new(self.clone()))
+    }
+
+    fn article_cache(&self) -> Box<dyn ArticleCacheRepository> {
+        Box::new(MemoryArticleCacheRepository::new(self.clone()))
+    }
+
+    fn feed_state(&self) -> Box<dyn FeedStateRepository> {
+        Box::new(MemoryFeedStateRepository::new(self.clone()))
+    }
+
+    fn history(&self) -> Box<dyn ArticleHistoryRepository> {
+        Box::new(MemoryArticleHistoryRepository::new(self.clone()))
+    }
+
+    fn lock_run(&self) -> FeederResult<RunGuard> {
+        Ok(Box::new(MemoryStorage::lock_run(self)?))
+    }
+
+    fn backup(&self, path: &Path) -> FeederResult<()> {
+        self.export(path)
+    }
+
+    fn restore(&self, path: &Path) -> FeederResult<i64> {
+        self.import(path)
+    }
+
+    fn check(&self) -> FeederResult<IntegrityReport> {
+        self.read(StoreData::check)
+    }
+
+    fn compact(&self) -> FeederResult<u64> {
+        Ok(0)
+    }
+}
+
+/// Current time in the `YYYY-MM-DD HH:MM:SS` UTC form SQLite's `datetime('now')` uses
+pub(crate) fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -1,5 +1,111 @@
 pub mod traits;
 pub mod sqlite;
+pub mod memory;
+pub mod file;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+mod integrity;
 
-pub use traits::{FeedRepository, ArticleCacheRepository};
-pub use sqlite::{SqliteStorage, SqliteFeedRepository, SqliteArticleCacheRepository};
+use std::fmt;
+
+use crate::domain::Article;
+use crate::errors::{FeederError, FeederResult};
+use crate::sinks::format::snippet;
+
+pub use traits::{FeedRepository, ArticleCacheRepository, ArticleHistoryRepository, FeedStateRepository, RunGuard, Storage};
+pub use integrity::{ForeignKeyViolation, IntegrityReport};
+pub use sqlite::{SqliteStorage, SqliteFeedRepository, SqliteArticleCacheRepository, SqliteArticleHistoryRepository, SqliteFeedStateRepository};
+pub use memory::MemoryStorage;
+pub use file::FileStorage;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStorage;
+
+/// Longest article text kept in the history
+const SNIPPET_CHARS: usize = 500;
+
+/// Where feeds, the notification cache and the article history are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// SQLite database file
+    #[default]
+    Sqlite,
+    /// Human-editable TOML or JSON file, for small setups
+    File,
+    /// Nothing is persisted; for tests and embedding
+    Memory,
+    /// PostgreSQL database shared by several feeder instances. Needs the
+    /// `postgres` cargo feature.
+    Postgres,
+}
+
+impl StorageBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::Sqlite => "sqlite",
+            StorageBackend::File => "file",
+            StorageBackend::Memory => "memory",
+            StorageBackend::Postgres => "postgres",
+        }
+    }
+
+    /// File used next to the executable when no path is configured. PostgreSQL
+    /// has no default: its URL must be given.
+    pub fn default_file_name(&self) -> Option<&'static str> {
+        match self {
+            StorageBackend::Sqlite => Some("feeder.db"),
+            StorageBackend::File => Some("feeder.toml"),
+            StorageBackend::Memory => Some(":memory:"),
+            StorageBackend::Postgres => None,
+        }
+    }
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = FeederError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            "file" | "toml" | "json" => Ok(StorageBackend::File),
+            "memory" => Ok(StorageBackend::Memory),
+            "postgres" | "postgresql" => Ok(StorageBackend::Postgres),
+            _ => Err(FeederError::Config(format!("Unknown storage backend: {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Open `backend`'s store at `path`, creating it if it doesn't exist yet. For
+/// PostgreSQL `path` is the database URL; the memory backend ignores it.
+pub fn open(backend: StorageBackend, path: &str) -> FeederResult<Box<dyn Storage>> {
+    Ok(match backend {
+        StorageBackend::Sqlite => Box::new(SqliteStorage::new(path)?),
+        StorageBackend::File => Box::new(FileStorage::open(path)?),
+        StorageBackend::Memory => Box::new(MemoryStorage::new()),
+        #[cfg(feature = "postgres")]
+        StorageBackend::Postgres => Box::new(PostgresStorage::connect(path)?),
+        #[cfg(not(feature = "postgres"))]
+        StorageBackend::Postgres => {
+            return Err(FeederError::Config(
+                "This build has no PostgreSQL support; rebuild with --features postgres".to_string(),
+            ))
+        }
+    })
+}
+
+/// Start of an article's text as kept in the history, whitespace collapsed. Sources
+/// that keep notifications to the title still give the entry's summary here.
+pub(crate) fn history_snippet(article: &Article) -> Option<String> {
+    article
+        .content
+        .as_deref()
+        .or(article.summary.as_deref())
+        .map(|content| content.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .map(|text| snippet(&text, SNIPPET_CHARS))
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use postgres::GenericClient;
+
+use crate::domain::{Article, RetentionPolicy};
+use crate::errors::FeederResult;
+use crate::storage::postgres::article_history_repository::insert_entry;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::traits::{ArticleCacheRepository, FETCHED_AT_KEY};
+
+pub struct PostgresArticleCacheRepository {
+    storage: PostgresStorage,
+}
+
+impl PostgresArticleCacheRepository {
+    pub fn new(storage: PostgresStorage) -> Self {
+        Self { storage }
+    }
+}
+
+fn mark_sent(
+    client: &mut impl GenericClient,
+    cache_key: &str,
+    feed_id: i64,
+    title: &str,
+) -> Result<(), postgres::Error> {
+    client.execute(
+        "INSERT INTO notified_articles (cache_key, feed_id, article_title, last_seen_at, state)
+         VALUES ($1, $2, $3, timezone('utc', now()), 'sent')
+         ON CONFLICT (cache_key) DO UPDATE SET state = 'sent', claimed_by = NULL",
+        &[&cache_key, &feed_id, &title],
+    )?;
+    Ok(())
+}
+
+impl ArticleCacheRepository for PostgresArticleCacheRepository {
+    fn is_notified(&self, cache_key: &str) -> FeederResult<bool> {
+        let row = self.storage.client()?.query_one(
+            "SELECT EXISTS(SELECT 1 FROM notified_articles WHERE cache_key = $1)",
+            &[&cache_key],
+        )?;
+        Ok(row.get(0))
+    }
+
+    fn mark_notified(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<()> {
+        mark_sent(&mut *self.storage.client()?, cache_key, feed_id, title)?;
+        Ok(())
+    }
+
+    fn mark_notified_batch(&self, feed_id: i64, entries: &[(String, &Article)]) -> FeederResult<()> {
+        let mut client = self.storage.client()?;
+        let mut tx = client.transaction()?;
+
+        for (cache_key, article) in entries {
+            mark_sent(&mut tx, cache_key, feed_id, &article.title)?;
+            insert_entry(&mut tx, cache_key, feed_id, article)?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The unique cache key makes the insert the claim: of several workers claiming
+    /// the same article at once, exactly one inserts the row
+    fn claim(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<bool> {
+        let worker = self.storage.worker()?;
+        let inserted = self.storage.client()?.execute(
+            "INSERT INTO notified_articles (cache_key, feed_id, article_title, last_seen_at, state, claimed_by)
+             VALUES ($1, $2, $3, timezone('utc', now()), 'claimed', $4)
+             ON CONFLICT (cache_key) DO NOTHING",
+            &[&cache_key, &feed_id, &title, &worker],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    fn release(&self, cache_key: &str) -> FeederResult<()> {
+        self.storage.client()?.execute(
+            "DELETE FROM notified_articles WHERE cache_key = $1 AND state = 'claimed'",
+            &[&cache_key],
+        )?;
+        Ok(())
+    }
+
+    fn get_unnotified(&self, cache_keys: &[String]) -> FeederResult<Vec<String>> {
+        if cache_keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = self.storage.client()?.query(
+            "SELECT cache_key FROM notified_articles WHERE cache_key = ANY($1)",
+            &[&cache_keys],
+        )?;
+        let notified: HashSet<String> = rows.iter().map(|row| row.get(0)).collect();
+
+        Ok(cache_keys
+            .iter()
+            .filter(|key| !notified.contains(*key))
+            .cloned()
+            .collect())
+    }
+
+    fn mark_seen(&self, cache_keys: &[String], seen_at: &str) -> FeederResult<()> {
+        if cache_keys.is_empty() {
+            return Ok(());
+        }
+
+        self.storage.client()?.execute(
+            "UPDATE notified_articles SET last_seen_at = $1::text::timestamp WHERE cache_key = ANY($2)",
+            &[&seen_at, &cache_keys],
+        )?;
+        Ok(())
+    }
+
+    fn prune(&self, policy: &RetentionPolicy) -> FeederResult<usize> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+
+        // Same rules as the SQLite backend: entries seen at or after their feed's last
+        // successful fetch are still in the feed, and feeds never fetched keep everything
+        let days = policy.days.map(|days| days.min(i32::MAX as u32) as i32);
+        let keep = policy.keep_per_feed.map(i64::from);
+        let removed = self.storage.client()?.execute(
+            "DELETE FROM notified_articles WHERE id IN (
+                SELECT n.id FROM (
+                    SELECT id, feed_id, notified_at, last_seen_at,
+                           ROW_NUMBER() OVER (PARTITION BY feed_id ORDER BY notified_at DESC, id DESC) AS position
+                    FROM notified_articles
+                ) n
+                JOIN feed_state s ON s.feed_id = n.feed_id AND s.key = $3
+                WHERE ($1::int IS NULL OR n.notified_at < timezone('utc', now()) - make_interval(days => $1::int))
+                  AND ($2::bigint IS NULL OR n.position > $2::bigint)
+                  AND COALESCE(n.last_seen_at, n.notified_at) < s.value::timestamp
+            )",
+            &[&days, &keep, &FETCHED_AT_KEY],
+        )?;
+
+        Ok(removed as usize)
+    }
+
+    fn rekey(&self, feed_id: i64, old_prefix: &str, new_prefix: &str) -> FeederResult<usize> {
+        let moved = self.storage.client()?.execute(
+            "UPDATE notified_articles n
+             SET cache_key = $3 || substr(n.cache_key, char_length($2) + 1)
+             WHERE n.feed_id = $1 AND left(n.cache_key, char_length($2)) = $2
+               AND NOT EXISTS (
+                   SELECT 1 FROM notified_articles o
+                   WHERE o.cache_key = $3 || substr(n.cache_key, char_length($2) + 1)
+               )",
+            &[&feed_id, &old_prefix, &new_prefix],
+        )?;
+        Ok(moved as usize)
+    }
+}
@@ -0,0 +1,143 @@
+use postgres::{GenericClient, Row};
+
+use crate::domain::{Article, HistoryEntry};
+use crate::errors::FeederResult;
+use crate::storage::history_snippet;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::traits::ArticleHistoryRepository;
+
+const ENTRY_COLUMNS: &str = "a.feed_id, f.title, a.title, a.link, a.published, a.author, a.snippet, \
+     to_char(a.notified_at, 'YYYY-MM-DD HH24:MI:SS')";
+
+pub struct PostgresArticleHistoryRepository {
+    storage: PostgresStorage,
+}
+
+impl PostgresArticleHistoryRepository {
+    pub fn new(storage: PostgresStorage) -> Self {
+        Self { storage }
+    }
+
+    fn row_to_entry(row: &Row) -> HistoryEntry {
+        HistoryEntry {
+            feed_id: row.get(0),
+            feed_title: row.get(1),
+            title: row.get(2),
+            link: row.get(3),
+            published: row.get(4),
+            author: row.get(5),
+            snippet: row.get(6),
+            notified_at: row.get(7),
+        }
+    }
+}
+
+/// Turn free text into a `tsquery` matching every word. Words are quoted so
+/// punctuation like `C++` or `!` isn't read as query syntax; a trailing `*` becomes
+/// a prefix match.
+fn ts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) if !stem.is_empty() => (stem, ":*"),
+                _ => (word, ""),
+            };
+            format!("'{}'{}", word.replace('\\', "\\\\").replace('\'', "''"), prefix)
+        })
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// Keep a notified article in the history, unless its cache key already is
+pub(super) fn insert_entry(
+    client: &mut impl GenericClient,
+    cache_key: &str,
+    feed_id: i64,
+    article: &Article,
+) -> Result<(), postgres::Error> {
+    client.execute(
+        "INSERT INTO articles (cache_key, feed_id, title, link, published, author, snippet)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (cache_key) DO NOTHING",
+        &[
+            &cache_key,
+            &feed_id,
+            &article.title,
+            &article.links.first(),
+            &article.published,
+            &article.author,
+            &history_snippet(article),
+        ],
+    )?;
+    Ok(())
+}
+
+impl ArticleHistoryRepository for PostgresArticleHistoryRepository {
+    fn record(&self, cache_key: &str, feed_id: i64, article: &Article) -> FeederResult<()> {
+        insert_entry(&mut *self.storage.client()?, cache_key, feed_id, article)?;
+        Ok(())
+    }
+
+    fn recent(&self, feed_id: Option<i64>, limit: usize) -> FeederResult<Vec<HistoryEntry>> {
+        let rows = self.storage.client()?.query(
+            &format!(
+                "SELECT {} FROM articles a JOIN feeds f ON f.id = a.feed_id
+                 WHERE $1::bigint IS NULL OR a.feed_id = $1
+                 ORDER BY a.notified_at DESC, a.id DESC LIMIT $2",
+                ENTRY_COLUMNS
+            ),
+            &[&feed_id, &(limit as i64)],
+        )?;
+        Ok(rows.iter().map(Self::row_to_entry).collect())
+    }
+
+    fn count(&self, feed_id: Option<i64>) -> FeederResult<usize> {
+        let row = self.storage.client()?.query_one(
+            "SELECT COUNT(*) FROM articles WHERE $1::bigint IS NULL OR feed_id = $1",
+            &[&feed_id],
+        )?;
+        Ok(row.get::<_, i64>(0) as usize)
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        feed_id: Option<i64>,
+        since: Option<String>,
+        limit: usize,
+    ) -> FeederResult<Vec<HistoryEntry>> {
+        let query = ts_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = self.storage.client()?.query(
+            &format!(
+                "SELECT {} FROM articles a
+                 JOIN feeds f ON f.id = a.feed_id,
+                 to_tsquery('simple', $1) query
+                 WHERE a.search @@ query
+                   AND ($2::bigint IS NULL OR a.feed_id = $2)
+                   AND ($3::text IS NULL OR a.notified_at >= $3::text::timestamp)
+                 ORDER BY ts_rank(a.search, query) DESC, a.notified_at DESC LIMIT $4",
+                ENTRY_COLUMNS
+            ),
+            &[&query, &feed_id, &since, &(limit as i64)],
+        )?;
+        Ok(rows.iter().map(Self::row_to_entry).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ts_query_quotes_words() {
+        assert_eq!(ts_query("rust  async*"), "'rust' & 'async':*");
+        assert_eq!(ts_query("it's C++"), "'it''s' & 'C++'");
+        assert_eq!(ts_query("*"), "'*'");
+        assert_eq!(ts_query("   "), "");
+    }
+}
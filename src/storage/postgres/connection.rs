@@ -0,0 +1,243 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use postgres::{Client, Config};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::postgres::{
+    PostgresArticleCacheRepository, PostgresArticleHistoryRepository, PostgresFeedRepository,
+    PostgresFeedStateRepository,
+};
+use crate::storage::traits::{
+    ArticleCacheRepository, ArticleHistoryRepository, FeedRepository, FeedStateRepository,
+    RunGuard, Storage,
+};
+
+/// Schema changes, in order. Timestamps are kept in UTC without a zone, like the
+/// `YYYY-MM-DD HH:MM:SS` strings of the other backends.
+const MIGRATIONS: &[&str] = &[
+    // 1: the SQLite schema as of its version 4, plus the workers sharing the
+    // database, the feeds each is fetching and the articles each has claimed
+    r#"
+    CREATE TABLE feeds (
+        id BIGSERIAL PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        feed_url TEXT NOT NULL,
+        title TEXT NOT NULL,
+        feed_type TEXT NOT NULL,
+        source_type TEXT NOT NULL,
+        created_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+        settings TEXT NOT NULL DEFAULT '{}',
+        leased_by TEXT
+    );
+
+    CREATE TABLE notified_articles (
+        id BIGSERIAL PRIMARY KEY,
+        cache_key TEXT NOT NULL UNIQUE,
+        feed_id BIGINT NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+        article_title TEXT,
+        notified_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+        last_seen_at TIMESTAMP,
+        state TEXT NOT NULL DEFAULT 'sent',
+        claimed_by TEXT
+    );
+
+    CREATE INDEX idx_notified_articles_feed ON notified_articles(feed_id, notified_at);
+
+    CREATE TABLE feed_state (
+        feed_id BIGINT NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        updated_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+        PRIMARY KEY (feed_id, key)
+    );
+
+    CREATE TABLE articles (
+        id BIGSERIAL PRIMARY KEY,
+        cache_key TEXT NOT NULL UNIQUE,
+        feed_id BIGINT NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+        title TEXT NOT NULL,
+        link TEXT,
+        published TEXT,
+        author TEXT,
+        snippet TEXT,
+        notified_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+        search TSVECTOR GENERATED ALWAYS AS (
+            to_tsvector('simple', title || ' ' || COALESCE(author, '') || ' ' || COALESCE(snippet, ''))
+        ) STORED
+    );
+
+    CREATE INDEX idx_articles_feed_notified ON articles(feed_id, notified_at);
+    CREATE INDEX idx_articles_notified ON articles(notified_at);
+    CREATE INDEX idx_articles_search ON articles USING GIN (search);
+
+    CREATE TABLE workers (
+        owner TEXT PRIMARY KEY,
+        pid BIGINT NOT NULL,
+        started_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now()),
+        heartbeat_at TIMESTAMP NOT NULL DEFAULT timezone('utc', now())
+    );
+    "#,
+];
+
+/// Schema version of a fully migrated database
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Advisory lock key serializing migrations between workers starting together
+const MIGRATION_LOCK: i64 = 0x6665_6564_6572;
+
+/// PostgreSQL database shared by any number of feeder processes.
+///
+/// Runs don't exclude each other: each registers as a worker and leases the feeds
+/// it fetches, so concurrent runs split the feeds between them, and articles are
+/// claimed before they are sent so none is notified twice.
+#[derive(Clone)]
+pub struct PostgresStorage {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    client: Mutex<Client>,
+    /// Owner name of the running worker, once `lock_run` registered it
+    worker: Mutex<Option<String>>,
+    /// Why the running worker can no longer count on its registration, once its
+    /// heartbeat stopped
+    lost: Mutex<Option<String>>,
+}
+
+impl PostgresStorage {
+    /// Connect to `url`, a `postgresql://` URL or `key=value` connection string, and
+    /// bring the schema up to date
+    pub fn connect(url: &str) -> FeederResult<Self> {
+        let config: Config = url
+            .parse()
+            .map_err(|e| FeederError::Config(format!("Invalid PostgreSQL URL: {}", e)))?;
+        Self::with_config(&config)
+    }
+
+    /// Connect with `config`. TLS is used when the server offers it, and required
+    /// with `sslmode=require`; certificates are checked against the same roots as
+    /// HTTPS requests.
+    pub fn with_config(config: &Config) -> FeederResult<Self> {
+        let mut client = config.connect(tls()?)?;
+        Self::migrate(&mut client)?;
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                client: Mutex::new(client),
+                worker: Mutex::new(None),
+                lost: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Run any migrations newer than the recorded schema version
+    fn migrate(client: &mut Client) -> FeederResult<()> {
+        let mut tx = client.transaction()?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK])?;
+        tx.batch_execute("CREATE TABLE IF NOT EXISTS schema_version (version BIGINT NOT NULL)")?;
+
+        let version: i64 = tx
+            .query_one("SELECT COALESCE(MAX(version), 0) FROM schema_version", &[])?
+            .get(0);
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+            tx.batch_execute(migration)?;
+            tx.execute("INSERT INTO schema_version (version) VALUES ($1)", &[&(i as i64 + 1)])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn client(&self) -> FeederResult<MutexGuard<'_, Client>> {
+        self.shared
+            .client
+            .lock()
+            .map_err(|_| FeederError::Storage("PostgreSQL connection poisoned".to_string()))
+    }
+
+    /// The running worker's owner name, if a run is in progress. Fails once the
+    /// worker's heartbeat stopped, as other workers may then take over its feeds
+    /// and claims, and whatever it goes on to send may be sent twice.
+    pub(super) fn worker(&self) -> FeederResult<Option<String>> {
+        if let Some(reason) = self.shared.lost.lock().ok().and_then(|lost| lost.clone()) {
+            return Err(FeederError::Storage(format!(
+                "This run lost its worker registration: {}",
+                reason
+            )));
+        }
+        Ok(self.shared.worker.lock().ok().and_then(|worker| worker.clone()))
+    }
+
+    pub(super) fn set_worker(&self, owner: Option<String>) {
+        if let Ok(mut worker) = self.shared.worker.lock() {
+            *worker = owner;
+        }
+        if let Ok(mut lost) = self.shared.lost.lock() {
+            *lost = None;
+        }
+    }
+
+    /// Stop the running worker from leasing feeds and claiming articles; `worker`
+    /// reports `reason` to whoever tries next
+    pub(super) fn lose_worker(&self, reason: String) {
+        if let Ok(mut lost) = self.shared.lost.lock() {
+            *lost = Some(reason);
+        }
+    }
+}
+
+fn tls() -> FeederResult<MakeRustlsConnect> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| FeederError::Config(format!("Can't set up TLS for PostgreSQL: {}", e)))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    Ok(MakeRustlsConnect::new(config))
+}
+
+impl Storage for PostgresStorage {
+    fn feeds(&self) -> Box<dyn FeedRepository> {
+        Box::new(PostgresFeedRepository::new(self.clone()))
+    }
+
+    fn article_cache(&self) -> Box<dyn ArticleCacheRepository> {
+        Box::new(PostgresArticleCacheRepository::new(self.clone()))
+    }
+
+    fn feed_state(&self) -> Box<dyn FeedStateRepository> {
+        Box::new(PostgresFeedStateRepository::new(self.clone()))
+    }
+
+    fn history(&self) -> Box<dyn ArticleHistoryRepository> {
+        Box::new(PostgresArticleHistoryRepository::new(self.clone()))
+    }
+
+    fn lock_run(&self) -> FeederResult<RunGuard> {
+        Ok(Box::new(PostgresStorage::lock_run(self)?))
+    }
+
+    fn backup(&self, path: &Path) -> FeederResult<()> {
+        PostgresStorage::backup(self, path)
+    }
+
+    fn restore(&self, path: &Path) -> FeederResult<i64> {
+        PostgresStorage::restore(self, path)
+    }
+
+    fn check(&self) -> FeederResult<IntegrityReport> {
+        PostgresStorage::check(self)
+    }
+
+    fn compact(&self) -> FeederResult<u64> {
+        PostgresStorage::compact(self)
+    }
+}
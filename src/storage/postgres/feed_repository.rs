@@ -0,0 +1,172 @@
+use postgres::{GenericClient, Row};
+
+use crate::domain::{Feed, FeedSettings, FeedType, SourceType};
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::postgres::worker::STALE_AFTER;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::traits::FeedRepository;
+
+pub(super) const FEED_COLUMNS: &str = "id, url, feed_url, title, feed_type, source_type, \
+     to_char(created_at, 'YYYY-MM-DD HH24:MI:SS'), settings";
+
+pub struct PostgresFeedRepository {
+    storage: PostgresStorage,
+}
+
+impl PostgresFeedRepository {
+    pub fn new(storage: PostgresStorage) -> Self {
+        Self { storage }
+    }
+
+    /// Map a row selected with `FEED_COLUMNS` to a feed
+    pub(super) fn feed_from_row(row: &Row) -> FeederResult<Feed> {
+        let feed_type: String = row.get(4);
+        let source_type: String = row.get(5);
+        let settings: String = row.get(7);
+
+        let settings = FeedSettings::from_json(&settings)
+            .map_err(|e| FeederError::Storage(format!("Invalid settings of feed: {}", e)))?;
+
+        Ok(Feed {
+            id: Some(row.get(0)),
+            url: row.get(1),
+            feed_url: row.get(2),
+            title: row.get(3),
+            feed_type: feed_type.parse().unwrap_or(FeedType::Rss),
+            source_type: source_type.parse().unwrap_or(SourceType::RssAtom),
+            created_at: row.get(6),
+            settings,
+        })
+    }
+
+    /// Insert a feed on `client`, which may be inside a transaction
+    fn insert(client: &mut impl GenericClient, feed: &Feed) -> FeederResult<i64> {
+        let row = client.query_opt(
+            "INSERT INTO feeds (url, feed_url, title, feed_type, source_type, settings)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (url) DO NOTHING RETURNING id",
+            &[
+                &feed.url,
+                &feed.feed_url,
+                &feed.title,
+                &feed.feed_type.as_str(),
+                &feed.source_type.as_str(),
+                &feed.settings.to_json(),
+            ],
+        )?;
+
+        row.map(|row| row.get(0))
+            .ok_or_else(|| FeederError::FeedAlreadyExists(feed.url.clone()))
+    }
+
+    fn get_one(&self, condition: &str, param: &(dyn postgres::types::ToSql + Sync)) -> FeederResult<Option<Feed>> {
+        let row = self.storage.client()?.query_opt(
+            &format!("SELECT {} FROM feeds WHERE {}", FEED_COLUMNS, condition),
+            &[param],
+        )?;
+        row.as_ref().map(Self::feed_from_row).transpose()
+    }
+}
+
+impl FeedRepository for PostgresFeedRepository {
+    fn add(&self, feed: &Feed) -> FeederResult<i64> {
+        Self::insert(&mut *self.storage.client()?, feed)
+    }
+
+    fn add_batch(&self, feeds: &[Feed]) -> FeederResult<Vec<i64>> {
+        let mut client = self.storage.client()?;
+        let mut tx = client.transaction()?;
+
+        let ids = feeds
+            .iter()
+            .map(|feed| Self::insert(&mut tx, feed))
+            .collect::<FeederResult<Vec<_>>>()?;
+
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    fn update(&self, feed: &Feed) -> FeederResult<()> {
+        let id = feed
+            .id
+            .ok_or_else(|| FeederError::FeedNotFound("Feed has no ID".to_string()))?;
+
+        let updated = self.storage.client()?.execute(
+            "UPDATE feeds SET url = $1, feed_url = $2, title = $3, feed_type = $4, source_type = $5, settings = $6
+             WHERE id = $7",
+            &[
+                &feed.url,
+                &feed.feed_url,
+                &feed.title,
+                &feed.feed_type.as_str(),
+                &feed.source_type.as_str(),
+                &feed.settings.to_json(),
+                &id,
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(FeederError::FeedNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn remove(&self, id: i64) -> FeederResult<()> {
+        self.storage
+            .client()?
+            .execute("DELETE FROM feeds WHERE id = $1", &[&id])?;
+        Ok(())
+    }
+
+    fn get_all(&self) -> FeederResult<Vec<Feed>> {
+        let rows = self.storage.client()?.query(
+            &format!("SELECT {} FROM feeds ORDER BY created_at DESC, id DESC", FEED_COLUMNS),
+            &[],
+        )?;
+        rows.iter().map(Self::feed_from_row).collect()
+    }
+
+    fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>> {
+        self.get_one("id = $1", &id)
+    }
+
+    fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>> {
+        self.get_one("url = $1", &url)
+    }
+
+    fn exists(&self, url: &str) -> FeederResult<bool> {
+        let row = self
+            .storage
+            .client()?
+            .query_one("SELECT EXISTS(SELECT 1 FROM feeds WHERE url = $1)", &[&url])?;
+        Ok(row.get(0))
+    }
+
+    /// Leases the feed to the running worker until its run ends. Feeds leased by
+    /// live workers are passed over; rows another worker is leasing at that moment
+    /// are skipped rather than waited for, so two workers never take the same feed.
+    fn lease_next(&self, taken: &[i64]) -> FeederResult<Option<Feed>> {
+        let worker = self.storage.worker()?;
+        let row = self.storage.client()?.query_opt(
+            &format!(
+                "UPDATE feeds SET leased_by = $2
+                 WHERE id = (
+                     SELECT id FROM feeds
+                     WHERE NOT (id = ANY($1))
+                       AND (leased_by IS NULL OR leased_by = $2 OR leased_by NOT IN (
+                           SELECT owner FROM workers
+                           WHERE heartbeat_at >= timezone('utc', now()) - make_interval(secs => $3)
+                       ))
+                     ORDER BY created_at DESC, id DESC
+                     LIMIT 1
+                     FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING {}",
+                FEED_COLUMNS
+            ),
+            &[&taken, &worker, &STALE_AFTER.as_secs_f64()],
+        )?;
+        row.as_ref().map(Self::feed_from_row).transpose()
+    }
+}
@@ -0,0 +1,32 @@
+use crate::errors::FeederResult;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::traits::FeedStateRepository;
+
+pub struct PostgresFeedStateRepository {
+    storage: PostgresStorage,
+}
+
+impl PostgresFeedStateRepository {
+    pub fn new(storage: PostgresStorage) -> Self {
+        Self { storage }
+    }
+}
+
+impl FeedStateRepository for PostgresFeedStateRepository {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>> {
+        let row = self.storage.client()?.query_opt(
+            "SELECT value FROM feed_state WHERE feed_id = $1 AND key = $2",
+            &[&feed_id, &key],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()> {
+        self.storage.client()?.execute(
+            "INSERT INTO feed_state (feed_id, key, value) VALUES ($1, $2, $3)
+             ON CONFLICT (feed_id, key) DO UPDATE SET value = excluded.value, updated_at = timezone('utc', now())",
+            &[&feed_id, &key, &value],
+        )?;
+        Ok(())
+    }
+}
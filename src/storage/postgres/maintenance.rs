@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use postgres::{IsolationLevel, Transaction};
+
+use crate::errors::FeederResult;
+use crate::storage::file::DataFormat;
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::memory::{CacheEntry, CacheState, HistoryRecord, StateEntry, StoreData};
+use crate::storage::postgres::feed_repository::FEED_COLUMNS;
+use crate::storage::postgres::{PostgresFeedRepository, PostgresStorage};
+
+/// Tables holding feeder's data, as opposed to the workers using it
+const DATA_TABLES: &[&str] = &["feeds", "notified_articles", "feed_state", "articles"];
+
+impl PostgresStorage {
+    /// Copy the database to `path` in the file backend's format, TOML or JSON by its
+    /// extension. The copy is read from one snapshot, so it is consistent even while
+    /// workers are running. For copies in PostgreSQL's own format, use `pg_dump`.
+    pub fn backup<P: AsRef<Path>>(&self, path: P) -> FeederResult<()> {
+        let path = path.as_ref();
+
+        let mut client = self.client()?;
+        let mut tx = client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()?;
+        let data = Self::read_data(&mut tx)?;
+        tx.commit()?;
+        drop(client);
+
+        DataFormat::from_path(path).write(path, &data)
+    }
+
+    /// Replace the database's contents with the backup at `path`, as written by
+    /// `backup` or by the file or memory backends. Returns the backup's version.
+    pub fn restore<P: AsRef<Path>>(&self, path: P) -> FeederResult<i64> {
+        let (backup, version) = StoreData::read_backup(path.as_ref())?;
+
+        let mut client = self.client()?;
+        let mut tx = client.transaction()?;
+        tx.batch_execute(&format!("TRUNCATE {} RESTART IDENTITY", DATA_TABLES.join(", ")))?;
+        Self::write_data(&mut tx, &backup)?;
+        tx.commit()?;
+
+        Ok(version)
+    }
+
+    /// PostgreSQL enforces the schema's foreign keys itself, so this only looks for
+    /// cache entries left behind by removed feeds
+    pub fn check(&self) -> FeederResult<IntegrityReport> {
+        let orphaned: i64 = self
+            .client()?
+            .query_one(
+                "SELECT COUNT(*) FROM notified_articles n
+                 WHERE NOT EXISTS (SELECT 1 FROM feeds f WHERE f.id = n.feed_id)",
+                &[],
+            )?
+            .get(0);
+
+        Ok(IntegrityReport {
+            orphaned_notifications: orphaned as usize,
+            ..Default::default()
+        })
+    }
+
+    /// Vacuum feeder's tables so space freed by pruning is reused
+    pub fn compact(&self) -> FeederResult<u64> {
+        let mut client = self.client()?;
+        let size_query = format!(
+            "SELECT ({})::bigint",
+            DATA_TABLES
+                .iter()
+                .map(|table| format!("pg_total_relation_size('{}')", table))
+                .collect::<Vec<_>>()
+                .join(" + ")
+        );
+
+        let before: i64 = client.query_one(&size_query, &[])?.get(0);
+        client.batch_execute(&format!("VACUUM ANALYZE {}", DATA_TABLES.join(", ")))?;
+        let after: i64 = client.query_one(&size_query, &[])?.get(0);
+
+        Ok(before.saturating_sub(after).max(0) as u64)
+    }
+
+    fn read_data(tx: &mut Transaction) -> FeederResult<StoreData> {
+        let mut data = StoreData::new();
+
+        data.feeds = tx
+            .query(&format!("SELECT {} FROM feeds ORDER BY id", FEED_COLUMNS), &[])?
+            .iter()
+            .map(PostgresFeedRepository::feed_from_row)
+            .collect::<FeederResult<_>>()?;
+        data.next_feed_id = tx
+            .query_one(
+                "SELECT CASE WHEN is_called THEN last_value + 1 ELSE last_value END FROM feeds_id_seq",
+                &[],
+            )?
+            .get(0);
+
+        data.notified = tx
+            .query(
+                "SELECT cache_key, feed_id, COALESCE(article_title, ''),
+                        to_char(notified_at, 'YYYY-MM-DD HH24:MI:SS'),
+                        to_char(last_seen_at, 'YYYY-MM-DD HH24:MI:SS'), state
+                 FROM notified_articles ORDER BY id",
+                &[],
+            )?
+            .iter()
+            .map(|row| CacheEntry {
+                cache_key: row.get(0),
+                feed_id: row.get(1),
+                title: row.get(2),
+                notified_at: row.get(3),
+                last_seen_at: row.get(4),
+                state: if row.get::<_, &str>(5) == "claimed" {
+                    CacheState::Claimed
+                } else {
+                    CacheState::Sent
+                },
+            })
+            .collect();
+
+        data.state = tx
+            .query("SELECT feed_id, key, value FROM feed_state ORDER BY feed_id, key", &[])?
+            .iter()
+            .map(|row| StateEntry {
+                feed_id: row.get(0),
+                key: row.get(1),
+                value: row.get(2),
+            })
+            .collect();
+
+        data.history = tx
+            .query(
+                "SELECT cache_key, feed_id, title, link, published, author, snippet,
+                        to_char(notified_at, 'YYYY-MM-DD HH24:MI:SS')
+                 FROM articles ORDER BY id",
+                &[],
+            )?
+            .iter()
+            .map(|row| HistoryRecord {
+                cache_key: row.get(0),
+                feed_id: row.get(1),
+                title: row.get(2),
+                link: row.get(3),
+                published: row.get(4),
+                author: row.get(5),
+                snippet: row.get(6),
+                notified_at: row.get(7),
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        Ok(data)
+    }
+
+    fn write_data(tx: &mut Transaction, data: &StoreData) -> FeederResult<()> {
+        let insert_feed = tx.prepare(
+            "INSERT INTO feeds (id, url, feed_url, title, feed_type, source_type, created_at, settings)
+             VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7::text::timestamp, timezone('utc', now())), $8)",
+        )?;
+        for feed in &data.feeds {
+            tx.execute(
+                &insert_feed,
+                &[
+                    &feed.id,
+                    &feed.url,
+                    &feed.feed_url,
+                    &feed.title,
+                    &feed.feed_type.as_str(),
+                    &feed.source_type.as_str(),
+                    &feed.created_at,
+                    &feed.settings.to_json(),
+                ],
+            )?;
+        }
+        tx.execute(
+            "SELECT setval('feeds_id_seq', GREATEST($1, (SELECT COALESCE(MAX(id), 0) + 1 FROM feeds)), false)",
+            &[&data.next_feed_id],
+        )?;
+
+        let insert_notified = tx.prepare(
+            "INSERT INTO notified_articles (cache_key, feed_id, article_title, notified_at, last_seen_at, state)
+             VALUES ($1, $2, $3, $4::text::timestamp, $5::text::timestamp, $6)",
+        )?;
+        for entry in &data.notified {
+            let state = match entry.state {
+                CacheState::Sent => "sent",
+                CacheState::Claimed => "claimed",
+            };
+            tx.execute(
+                &insert_notified,
+                &[
+                    &entry.cache_key,
+                    &entry.feed_id,
+                    &entry.title,
+                    &entry.notified_at,
+                    &entry.last_seen_at,
+                    &state,
+                ],
+            )?;
+        }
+
+        let insert_state =
+            tx.prepare("INSERT INTO feed_state (feed_id, key, value) VALUES ($1, $2, $3)")?;
+        for entry in &data.state {
+            tx.execute(&insert_state, &[&entry.feed_id, &entry.key, &entry.value])?;
+        }
+
+        let insert_history = tx.prepare(
+            "INSERT INTO articles (cache_key, feed_id, title, link, published, author, snippet, notified_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8::text::timestamp)",
+        )?;
+        for record in data.history.iter() {
+            tx.execute(
+                &insert_history,
+                &[
+                    &record.cache_key,
+                    &record.feed_id,
+                    &record.title,
+                    &record.link,
+                    &record.published,
+                    &record.author,
+                    &record.snippet,
+                    &record.notified_at,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+}
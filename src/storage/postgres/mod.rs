@@ -0,0 +1,15 @@
+mod connection;
+mod feed_repository;
+mod article_cache_repository;
+mod article_history_repository;
+mod feed_state_repository;
+mod maintenance;
+mod worker;
+
+pub use connection::{PostgresStorage, SCHEMA_VERSION};
+pub use feed_repository::PostgresFeedRepository;
+pub use article_cache_repository::PostgresArticleCacheRepository;
+pub use article_history_repository::PostgresArticleHistoryRepository;
+pub use feed_state_repository::PostgresFeedStateRepository;
+pub use worker::Worker;
+
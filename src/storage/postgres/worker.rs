@@ -0,0 +1,171 @@
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::errors::FeederResult;
+use crate::storage::postgres::PostgresStorage;
+
+/// A worker that hasn't refreshed its heartbeat for this long is treated as dead:
+/// its leases and claims are up for grabs
+pub(super) const STALE_AFTER: Duration = Duration::from_secs(120);
+const HEARTBEAT_EVERY: Duration = Duration::from_secs(20);
+/// A worker whose heartbeat has failed for this long stops leasing and claiming,
+/// well before other workers would treat it as dead
+const LOST_AFTER: Duration = Duration::from_secs(60);
+
+/// Registration of a running worker in `workers`, held for the length of a run.
+///
+/// Unlike the SQLite run lock it doesn't exclude other runs: the feeds this worker
+/// leases and the articles it claims are recorded under its name, which other
+/// workers respect while it keeps its heartbeat fresh. Dropping it gives up its
+/// leases and removes the registration.
+pub struct Worker {
+    storage: PostgresStorage,
+    owner: String,
+    stop: Option<Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl PostgresStorage {
+    /// Register this process as a worker for a run.
+    ///
+    /// Workers that died are cleaned up first: their leases are freed and the
+    /// articles they claimed but never sent are released, so those are tried again.
+    pub fn lock_run(&self) -> FeederResult<Worker> {
+        self.lock_run_with(HEARTBEAT_EVERY)
+    }
+
+    fn lock_run_with(&self, heartbeat_every: Duration) -> FeederResult<Worker> {
+        let owner = format!("{}-{:016x}", std::process::id(), fastrand::u64(..));
+
+        {
+            let mut client = self.client()?;
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "DELETE FROM workers
+                 WHERE heartbeat_at < timezone('utc', now()) - make_interval(secs => $1)",
+                &[&STALE_AFTER.as_secs_f64()],
+            )?;
+            tx.batch_execute(
+                "DELETE FROM notified_articles
+                 WHERE state = 'claimed'
+                   AND (claimed_by IS NULL OR claimed_by NOT IN (SELECT owner FROM workers));
+                 UPDATE feeds SET leased_by = NULL
+                 WHERE leased_by IS NOT NULL AND leased_by NOT IN (SELECT owner FROM workers);",
+            )?;
+            tx.execute(
+                "INSERT INTO workers (owner, pid) VALUES ($1, $2)",
+                &[&owner, &i64::from(std::process::id())],
+            )?;
+            tx.commit()?;
+        }
+        self.set_worker(Some(owner.clone()));
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let storage = self.clone();
+        let heartbeat_owner = owner.clone();
+        let heartbeat = std::thread::spawn(move || {
+            let mut last_beat = Instant::now();
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(heartbeat_every) {
+                match storage.heartbeat(&heartbeat_owner) {
+                    Ok(true) => last_beat = Instant::now(),
+                    Ok(false) => {
+                        storage.lose_worker("another worker removed it as dead".to_string());
+                        return;
+                    }
+                    Err(e) if last_beat.elapsed() >= LOST_AFTER => {
+                        storage.lose_worker(format!(
+                            "no heartbeat for {} seconds: {}",
+                            last_beat.elapsed().as_secs(),
+                            e
+                        ));
+                        return;
+                    }
+                    // Tried again on the next beat; only a lasting failure loses the worker
+                    Err(_) => {}
+                }
+            }
+        });
+
+        Ok(Worker {
+            storage: self.clone(),
+            owner,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        })
+    }
+
+    /// Refresh the heartbeat of worker `owner`; false if it is no longer registered
+    fn heartbeat(&self, owner: &str) -> FeederResult<bool> {
+        let updated = self.client()?.execute(
+            "UPDATE workers SET heartbeat_at = timezone('utc', now()) WHERE owner = $1",
+            &[&owner],
+        )?;
+        Ok(updated == 1)
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+
+        self.storage.set_worker(None);
+        if let Ok(mut client) = self.storage.client() {
+            let _ = client.execute("UPDATE feeds SET leased_by = NULL WHERE leased_by = $1", &[&self.owner]);
+            let _ = client.execute("DELETE FROM workers WHERE owner = $1", &[&self.owner]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Feed, FeedType, SourceType};
+    use crate::storage::traits::{ArticleCacheRepository, FeedRepository};
+    use crate::storage::Storage;
+
+    /// A storage in a new schema of the database at `FEEDER_TEST_POSTGRES_URL`,
+    /// like the backend tests in tests/storage_backends.rs
+    fn storage() -> PostgresStorage {
+        let url = std::env::var("FEEDER_TEST_POSTGRES_URL")
+            .expect("FEEDER_TEST_POSTGRES_URL must point at a scratch database");
+        let mut config: postgres::Config = url.parse().unwrap();
+
+        let schema = format!("feeder_test_{:016x}", fastrand::u64(..));
+        let mut admin = config.connect(postgres::NoTls).unwrap();
+        admin.batch_execute(&format!("CREATE SCHEMA {}", schema)).unwrap();
+
+        config.options(&format!("-c search_path={}", schema));
+        PostgresStorage::with_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_worker_removed_as_dead_stops_claiming() {
+        let storage = storage();
+        let feed_id = storage
+            .feeds()
+            .add(&Feed::new(
+                "https://a.example".to_string(),
+                "https://a.example/feed".to_string(),
+                "A".to_string(),
+                FeedType::Rss,
+                SourceType::RssAtom,
+            ))
+            .unwrap();
+        let cache = storage.article_cache();
+
+        let _worker = storage.lock_run_with(Duration::from_millis(50)).unwrap();
+        assert!(cache.claim("k1", feed_id, "One").unwrap());
+
+        // Another worker took it for dead and cleaned up after it
+        storage.client().unwrap().execute("DELETE FROM workers", &[]).unwrap();
+        std::thread::sleep(Duration::from_millis(500));
+
+        assert!(cache.claim("k2", feed_id, "Two").is_err());
+        assert!(storage.feeds().lease_next(&[]).is_err());
+    }
+}
@@ -1,7 +1,14 @@
+use rusqlite::Connection;
+
+use crate::domain::{Article, RetentionPolicy};
 use crate::errors::FeederResult;
-use crate::storage::traits::ArticleCacheRepository;
+use crate::storage::sqlite::article_history_repository::insert_entry;
+use crate::storage::traits::{ArticleCacheRepository, FETCHED_AT_KEY};
 use crate::storage::sqlite::SqliteStorage;
 
+/// Keys bound per `IN (...)` query, well below SQLite's variable limit
+const KEYS_PER_QUERY: usize = 500;
+
 pub struct SqliteArticleCacheRepository {
     storage: SqliteStorage,
 }
@@ -12,6 +19,24 @@ impl SqliteArticleCacheRepository {
     }
 }
 
+/// `?1, ?2, ...` for `count` parameters, starting after `offset` others
+fn placeholders(count: usize, offset: usize) -> String {
+    (1..=count)
+        .map(|i| format!("?{}", i + offset))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn mark_sent(conn: &Connection, cache_key: &str, feed_id: i64, title: &str) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO notified_articles (cache_key, feed_id, article_title, last_seen_at, state)
+         VALUES (?1, ?2, ?3, datetime('now'), 'sent')
+         ON CONFLICT(cache_key) DO UPDATE SET state = 'sent'",
+    )?
+    .execute((cache_key, feed_id, title))?;
+    Ok(())
+}
+
 impl ArticleCacheRepository for SqliteArticleCacheRepository {
     fn is_notified(&self, cache_key: &str) -> FeederResult<bool> {
         let conn = self.storage.connection()?;
@@ -24,10 +49,40 @@ impl ArticleCacheRepository for SqliteArticleCacheRepository {
 
     fn mark_notified(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<()> {
         let conn = self.storage.connection()?;
-        conn.execute(
-            "INSERT OR IGNORE INTO notified_articles (cache_key, feed_id, article_title) VALUES (?1, ?2, ?3)",
+        mark_sent(&conn, cache_key, feed_id, title)?;
+        Ok(())
+    }
+
+    fn mark_notified_batch(&self, feed_id: i64, entries: &[(String, &Article)]) -> FeederResult<()> {
+        let mut conn = self.storage.connection()?;
+        let tx = conn.transaction()?;
+
+        for (cache_key, article) in entries {
+            mark_sent(&tx, cache_key, feed_id, &article.title)?;
+            insert_entry(&tx, cache_key, feed_id, article)?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn claim(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<bool> {
+        self.storage.check_run_lock()?;
+        let conn = self.storage.connection()?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO notified_articles (cache_key, feed_id, article_title, last_seen_at, state)
+             VALUES (?1, ?2, ?3, datetime('now'), 'claimed')",
             (cache_key, feed_id, title),
         )?;
+        Ok(inserted == 1)
+    }
+
+    fn release(&self, cache_key: &str) -> FeederResult<()> {
+        let conn = self.storage.connection()?;
+        conn.execute(
+            "DELETE FROM notified_articles WHERE cache_key = ?1 AND state = 'claimed'",
+            [cache_key],
+        )?;
         Ok(())
     }
 
@@ -37,29 +92,80 @@ impl ArticleCacheRepository for SqliteArticleCacheRepository {
         }
 
         let conn = self.storage.connection()?;
+        let mut notified = std::collections::HashSet::new();
 
-        // Build placeholders for IN clause
-        let placeholders: Vec<String> = (0..cache_keys.len()).map(|i| format!("?{}", i + 1)).collect();
-        let query = format!(
-            "SELECT cache_key FROM notified_articles WHERE cache_key IN ({})",
-            placeholders.join(", ")
-        );
-
-        let mut stmt = conn.prepare(&query)?;
-
-        // Get notified keys
-        let notified: Vec<String> = stmt
-            .query_map(rusqlite::params_from_iter(cache_keys.iter()), |row| row.get(0))?
-            .filter_map(|r| r.ok())
-            .collect();
+        for chunk in cache_keys.chunks(KEYS_PER_QUERY) {
+            let query = format!(
+                "SELECT cache_key FROM notified_articles WHERE cache_key IN ({})",
+                placeholders(chunk.len(), 0)
+            );
+            let mut stmt = conn.prepare_cached(&query)?;
+            for key in stmt.query_map(rusqlite::params_from_iter(chunk.iter()), |row| row.get::<_, String>(0))? {
+                notified.insert(key?);
+            }
+        }
 
         // Return keys that are not in notified
         Ok(cache_keys
             .iter()
-            .filter(|k| !notified.contains(k))
+            .filter(|k| !notified.contains(*k))
             .cloned()
             .collect())
     }
+
+    fn mark_seen(&self, cache_keys: &[String], seen_at: &str) -> FeederResult<()> {
+        let conn = self.storage.connection()?;
+
+        for chunk in cache_keys.chunks(KEYS_PER_QUERY) {
+            let query = format!(
+                "UPDATE notified_articles SET last_seen_at = ?1 WHERE cache_key IN ({})",
+                placeholders(chunk.len(), 1)
+            );
+            let params = std::iter::once(seen_at).chain(chunk.iter().map(String::as_str));
+            conn.prepare_cached(&query)?
+                .execute(rusqlite::params_from_iter(params))?;
+        }
+
+        Ok(())
+    }
+
+    fn prune(&self, policy: &RetentionPolicy) -> FeederResult<usize> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+
+        // Entries seen at or after their feed's last successful fetch are still in the
+        // feed; removing them would notify them again. Feeds not fetched since this was
+        // tracked keep everything.
+        let conn = self.storage.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM notified_articles WHERE id IN (
+                SELECT n.id FROM (
+                    SELECT id, feed_id, notified_at, last_seen_at,
+                           ROW_NUMBER() OVER (PARTITION BY feed_id ORDER BY notified_at DESC, id DESC) AS position
+                    FROM notified_articles
+                ) n
+                JOIN feed_state s ON s.feed_id = n.feed_id AND s.key = ?3
+                WHERE (?1 IS NULL OR n.notified_at < datetime('now', printf('-%d days', ?1)))
+                  AND (?2 IS NULL OR n.position > ?2)
+                  AND COALESCE(n.last_seen_at, n.notified_at) < s.value
+            )",
+            (policy.days, policy.keep_per_feed, FETCHED_AT_KEY),
+        )?;
+
+        Ok(removed)
+    }
+
+    fn rekey(&self, feed_id: i64, old_prefix: &str, new_prefix: &str) -> FeederResult<usize> {
+        let conn = self.storage.connection()?;
+        let moved = conn.execute(
+            "UPDATE OR IGNORE notified_articles
+             SET cache_key = ?3 || substr(cache_key, length(?2) + 1)
+             WHERE feed_id = ?1 AND substr(cache_key, 1, length(?2)) = ?2",
+            (feed_id, old_prefix, new_prefix),
+        )?;
+        Ok(moved)
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +234,67 @@ mod tests {
         let unnotified = cache_repo.get_unnotified(&keys).unwrap();
         assert!(unnotified.is_empty());
     }
+
+    #[test]
+    fn test_mark_notified_batch() {
+        let (_, feed_repo, cache_repo) = setup();
+        let feed_id = add_feed(&feed_repo);
+
+        let one = Article::new("1".to_string(), "One".to_string());
+        let two = Article::new("2".to_string(), "Two".to_string());
+        let entries = vec![("key1".to_string(), &one), ("key2".to_string(), &two)];
+        cache_repo.mark_notified_batch(feed_id, &entries).unwrap();
+        assert!(cache_repo.is_notified("key1").unwrap() && cache_repo.is_notified("key2").unwrap());
+    }
+
+    #[test]
+    fn test_claim_is_exclusive_until_released() {
+        let (_, feed_repo, cache_repo) = setup();
+        let feed_id = add_feed(&feed_repo);
+
+        assert!(cache_repo.claim("key1", feed_id, "Article").unwrap());
+        // A second run sees the claim and must not send
+        assert!(!cache_repo.claim("key1", feed_id, "Article").unwrap());
+        assert!(cache_repo.get_unnotified(&["key1".to_string()]).unwrap().is_empty());
+
+        cache_repo.release("key1").unwrap();
+        assert!(cache_repo.claim("key1", feed_id, "Article").unwrap());
+
+        // Once sent, releasing leaves it alone
+        cache_repo.mark_notified("key1", feed_id, "Article").unwrap();
+        cache_repo.release("key1").unwrap();
+        assert!(cache_repo.is_notified("key1").unwrap());
+        assert!(!cache_repo.claim("key1", feed_id, "Article").unwrap());
+    }
+
+    fn add_feed(feed_repo: &SqliteFeedRepository) -> i64 {
+        feed_repo
+            .add(&Feed::new(
+                "https://example.com/feed".to_string(),
+                "https://example.com/feed".to_string(),
+                "Example Feed".to_string(),
+                FeedType::Rss,
+                SourceType::RssAtom,
+            ))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prune_skips_feeds_never_fetched() {
+        let (storage, feed_repo, cache_repo) = setup();
+        let feed_id = add_feed(&feed_repo);
+        cache_repo.mark_notified("old", feed_id, "Article").unwrap();
+        storage
+            .connection()
+            .unwrap()
+            .execute("UPDATE notified_articles SET notified_at = '2000-01-01 00:00:00'", [])
+            .unwrap();
+
+        let policy = RetentionPolicy {
+            days: Some(1),
+            keep_per_feed: Some(0),
+        };
+        assert_eq!(cache_repo.prune(&policy).unwrap(), 0);
+        assert_eq!(cache_repo.prune(&RetentionPolicy::default()).unwrap(), 0);
+    }
 }
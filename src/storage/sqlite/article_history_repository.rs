@@ -0,0 +1,265 @@
+use rusqlite::{Connection, Row};
+
+use crate::domain::{Article, HistoryEntry};
+use crate::errors::FeederResult;
+use crate::storage::history_snippet;
+use crate::storage::traits::ArticleHistoryRepository;
+use crate::storage::sqlite::SqliteStorage;
+
+const ENTRY_COLUMNS: &str =
+    "a.feed_id, f.title, a.title, a.link, a.published, a.author, a.snippet, a.notified_at";
+
+pub struct SqliteArticleHistoryRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteArticleHistoryRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+
+    fn row_to_entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+        Ok(HistoryEntry {
+            feed_id: row.get(0)?,
+            feed_title: row.get(1)?,
+            title: row.get(2)?,
+            link: row.get(3)?,
+            published: row.get(4)?,
+            author: row.get(5)?,
+            snippet: row.get(6)?,
+            notified_at: row.get(7)?,
+        })
+    }
+}
+
+/// Turn free text into an FTS5 query matching every word. Words are quoted so
+/// punctuation like `C++` or `-` isn't read as query syntax; a trailing `*` is kept
+/// for prefix matches.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) if !stem.is_empty() => (stem, "*"),
+                _ => (word, ""),
+            };
+            format!("\"{}\"{}", word.replace('"', "\"\""), prefix)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keep a notified article in the history, unless its cache key already is
+pub(super) fn insert_entry(
+    conn: &Connection,
+    cache_key: &str,
+    feed_id: i64,
+    article: &Article,
+) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT OR IGNORE INTO articles (cache_key, feed_id, title, link, published, author, snippet)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?
+    .execute((
+        cache_key,
+        feed_id,
+        &article.title,
+        article.links.first(),
+        &article.published,
+        &article.author,
+        history_snippet(article),
+    ))?;
+    Ok(())
+}
+
+impl ArticleHistoryRepository for SqliteArticleHistoryRepository {
+    fn record(&self, cache_key: &str, feed_id: i64, article: &Article) -> FeederResult<()> {
+        let conn = self.storage.connection()?;
+        insert_entry(&conn, cache_key, feed_id, article)?;
+        Ok(())
+    }
+
+    fn recent(&self, feed_id: Option<i64>, limit: usize) -> FeederResult<Vec<HistoryEntry>> {
+        let conn = self.storage.connection()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM articles a JOIN feeds f ON f.id = a.feed_id
+             WHERE ?1 IS NULL OR a.feed_id = ?1
+             ORDER BY a.notified_at DESC, a.id DESC LIMIT ?2",
+            ENTRY_COLUMNS
+        ))?;
+
+        let entries = stmt
+            .query_map((feed_id, limit as i64), Self::row_to_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn count(&self, feed_id: Option<i64>) -> FeederResult<usize> {
+        let conn = self.storage.connection()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM articles WHERE ?1 IS NULL OR feed_id = ?1",
+            [feed_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        feed_id: Option<i64>,
+        since: Option<String>,
+        limit: usize,
+    ) -> FeederResult<Vec<HistoryEntry>> {
+        let query = fts_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.storage.connection()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM articles_fts
+             JOIN articles a ON a.id = articles_fts.rowid
+             JOIN feeds f ON f.id = a.feed_id
+             WHERE articles_fts MATCH ?1
+               AND (?2 IS NULL OR a.feed_id = ?2)
+               AND (?3 IS NULL OR a.notified_at >= ?3)
+             ORDER BY rank, a.notified_at DESC LIMIT ?4",
+            ENTRY_COLUMNS
+        ))?;
+
+        let entries = stmt
+            .query_map((query, feed_id, since, limit as i64), Self::row_to_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Feed, FeedType, SourceType};
+    use crate::storage::sqlite::SqliteFeedRepository;
+    use crate::storage::traits::FeedRepository;
+
+    fn setup() -> (SqliteStorage, SqliteFeedRepository, SqliteArticleHistoryRepository, i64) {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let feed_repo = SqliteFeedRepository::new(storage.clone());
+        let history_repo = SqliteArticleHistoryRepository::new(storage.clone());
+
+        let feed = Feed::new(
+            "https://blog.example.com".to_string(),
+            "https://blog.example.com/feed".to_string(),
+            "Example Blog".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+        let feed_id = feed_repo.add(&feed).unwrap();
+
+        (storage, feed_repo, history_repo, feed_id)
+    }
+
+    fn article(id: &str, title: &str, content: &str) -> Article {
+        Article::new(id.to_string(), title.to_string())
+            .with_content(Some(content.to_string()))
+            .with_links(vec![format!("https://blog.example.com/{}", id)])
+            .with_author(Some("Ada".to_string()))
+    }
+
+    #[test]
+    fn test_record_and_list_recent() {
+        let (_, _, history_repo, feed_id) = setup();
+
+        history_repo
+            .record("Example Blog:1", feed_id, &article("1", "First", "Hello\n\n  world"))
+            .unwrap();
+        history_repo
+            .record("Example Blog:2", feed_id, &article("2", "Second", "More"))
+            .unwrap();
+        // Recording the same article again keeps one entry
+        history_repo
+            .record("Example Blog:2", feed_id, &article("2", "Second", "More"))
+            .unwrap();
+
+        let entries = history_repo.recent(None, 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Second");
+        assert_eq!(entries[1].feed_title, "Example Blog");
+        assert_eq!(entries[1].link.as_deref(), Some("https://blog.example.com/1"));
+        assert_eq!(entries[1].snippet.as_deref(), Some("Hello world"));
+
+        assert_eq!(history_repo.recent(Some(feed_id), 1).unwrap().len(), 1);
+        assert!(history_repo.recent(Some(feed_id + 1), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_matches_title_author_and_text() {
+        let (_, _, history_repo, feed_id) = setup();
+
+        history_repo
+            .record("k1", feed_id, &article("1", "Async traits in Rust", "Stabilized at last"))
+            .unwrap();
+        history_repo
+            .record("k2", feed_id, &article("2", "Gardening", "Tomatoes and C++ compilers"))
+            .unwrap();
+
+        let found = history_repo.search("rust", None, None, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title, "Async traits in Rust");
+
+        assert_eq!(history_repo.search("stabil*", None, None, 10).unwrap().len(), 1);
+        assert_eq!(history_repo.search("C++ tomatoes", None, None, 10).unwrap().len(), 1);
+        assert_eq!(history_repo.search("ada", Some(feed_id), None, 10).unwrap().len(), 2);
+        assert!(history_repo.search("rust gardening", None, None, 10).unwrap().is_empty());
+        assert!(history_repo.search("  ", None, None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summary_searchable_without_content() {
+        let (_, _, history_repo, feed_id) = setup();
+
+        let summarized = Article::new("1".to_string(), "Release notes".to_string())
+            .with_summary(Some("Faster   incremental builds".to_string()));
+        history_repo.record("k1", feed_id, &summarized).unwrap();
+
+        let found = history_repo.search("incremental", None, None, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].snippet.as_deref(), Some("Faster incremental builds"));
+    }
+
+    #[test]
+    fn test_search_since() {
+        let (storage, _, history_repo, feed_id) = setup();
+
+        history_repo.record("k1", feed_id, &article("1", "Old news", "")).unwrap();
+        history_repo.record("k2", feed_id, &article("2", "Fresh news", "")).unwrap();
+        storage
+            .connection()
+            .unwrap()
+            .execute("UPDATE articles SET notified_at = '2020-01-01 00:00:00' WHERE cache_key = 'k1'", [])
+            .unwrap();
+
+        let found = history_repo.search("news", None, Some("2021-01-01 00:00:00".to_string()), 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title, "Fresh news");
+        assert!(found[0].snippet.is_none());
+    }
+
+    #[test]
+    fn test_history_removed_with_feed() {
+        let (_, feed_repo, history_repo, feed_id) = setup();
+
+        history_repo.record("k1", feed_id, &article("1", "Gone soon", "")).unwrap();
+        feed_repo.remove(feed_id).unwrap();
+
+        assert!(history_repo.recent(None, 10).unwrap().is_empty());
+        assert!(history_repo.search("gone", None, None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fts_query_quotes_words() {
+        assert_eq!(fts_query("rust async"), "\"rust\" \"async\"");
+        assert_eq!(fts_query("say \"hi\" pre*"), "\"say\" \"\"\"hi\"\"\" \"pre\"*");
+        assert_eq!(fts_query("*"), "\"*\"");
+    }
+}
@@ -1,8 +1,18 @@
 use rusqlite::Connection;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::errors::{FeederError, FeederResult};
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::sqlite::{
+    SqliteArticleCacheRepository, SqliteArticleHistoryRepository, SqliteFeedRepository,
+    SqliteFeedStateRepository,
+};
+use crate::storage::traits::{
+    ArticleCacheRepository, ArticleHistoryRepository, FeedRepository, FeedStateRepository,
+    RunGuard, Storage,
+};
 
 const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS feeds (
@@ -29,30 +39,140 @@ CREATE TABLE IF NOT EXISTS notified_articles (
 CREATE INDEX IF NOT EXISTS idx_notified_articles_cache_key ON notified_articles(cache_key);
 "#;
 
+/// Schema changes applied on top of `SCHEMA`, in order. After running entry `i`
+/// the database's `user_version` is `i + 1`, so each migration runs exactly once.
+const MIGRATIONS: &[&str] = &[
+    // 1: per-feed settings and state persisted between runs
+    r#"
+    ALTER TABLE feeds ADD COLUMN settings TEXT NOT NULL DEFAULT '{}';
+
+    CREATE TABLE IF NOT EXISTS feed_state (
+        feed_id INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+        PRIMARY KEY (feed_id, key),
+        FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+    );
+    "#,
+    // 2: article history with a full-text index kept in sync by triggers
+    r#"
+    CREATE TABLE IF NOT EXISTS articles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        cache_key TEXT NOT NULL UNIQUE,
+        feed_id INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        link TEXT,
+        published TEXT,
+        author TEXT,
+        snippet TEXT,
+        notified_at TEXT NOT NULL DEFAULT (datetime('now')),
+        FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_articles_feed_notified ON articles(feed_id, notified_at);
+    CREATE INDEX IF NOT EXISTS idx_articles_notified ON articles(notified_at);
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS articles_fts USING fts5(
+        title, author, snippet,
+        content = 'articles', content_rowid = 'id'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS articles_fts_insert AFTER INSERT ON articles BEGIN
+        INSERT INTO articles_fts (rowid, title, author, snippet)
+        VALUES (new.id, new.title, new.author, new.snippet);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS articles_fts_delete AFTER DELETE ON articles BEGIN
+        INSERT INTO articles_fts (articles_fts, rowid, title, author, snippet)
+        VALUES ('delete', old.id, old.title, old.author, old.snippet);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS articles_fts_update AFTER UPDATE ON articles BEGIN
+        INSERT INTO articles_fts (articles_fts, rowid, title, author, snippet)
+        VALUES ('delete', old.id, old.title, old.author, old.snippet);
+        INSERT INTO articles_fts (rowid, title, author, snippet)
+        VALUES (new.id, new.title, new.author, new.snippet);
+    END;
+
+    -- Articles notified before history existed keep at least their title
+    INSERT OR IGNORE INTO articles (cache_key, feed_id, title, notified_at)
+    SELECT cache_key, feed_id, COALESCE(article_title, ''), notified_at FROM notified_articles;
+    "#,
+    // 3: when each cached entry was last seen in its feed, so pruning can keep live entries
+    r#"
+    ALTER TABLE notified_articles ADD COLUMN last_seen_at TEXT;
+    UPDATE notified_articles SET last_seen_at = notified_at;
+
+    CREATE INDEX IF NOT EXISTS idx_notified_articles_feed ON notified_articles(feed_id, notified_at);
+    "#,
+    // 4: exclusive run lock, and cache entries claimed by a run before they are sent
+    r#"
+    ALTER TABLE notified_articles ADD COLUMN state TEXT NOT NULL DEFAULT 'sent';
+
+    CREATE TABLE IF NOT EXISTS run_lock (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        owner TEXT NOT NULL,
+        pid INTEGER NOT NULL,
+        acquired_at TEXT NOT NULL DEFAULT (datetime('now')),
+        heartbeat_at TEXT NOT NULL DEFAULT (datetime('now'))
+    );
+    "#,
+];
+
+/// Schema version of a fully migrated database
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// How long a statement waits for another process's write lock before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Clone)]
 pub struct SqliteStorage {
     conn: Arc<Mutex<Connection>>,
+    /// Why this process can no longer count on the run lock it took, once its
+    /// heartbeat stopped
+    lost: Arc<Mutex<Option<String>>>,
 }
 
 impl SqliteStorage {
     pub fn new<P: AsRef<Path>>(path: P) -> FeederResult<Self> {
-        let conn = Connection::open(path)?;
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> FeederResult<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> FeederResult<Self> {
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-        conn.execute_batch(SCHEMA)?;
+        // Another process may be writing, e.g. a backup taken during a run
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Self::upgrade(&mut conn)?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
+            lost: Arc::new(Mutex::new(None)),
         })
     }
 
-    pub fn in_memory() -> FeederResult<Self> {
-        let conn = Connection::open_in_memory()?;
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    /// Create any missing tables, then migrate them to `SCHEMA_VERSION`
+    pub(super) fn upgrade(conn: &mut Connection) -> FeederResult<()> {
         conn.execute_batch(SCHEMA)?;
+        Self::migrate(conn)
+    }
 
-        Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
-        })
+    /// Run any migrations newer than the database's current `user_version`
+    fn migrate(conn: &mut Connection) -> FeederResult<()> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i as i64 + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(())
     }
 
     pub fn connection(&self) -> Result<std::sync::MutexGuard<'_, Connection>, FeederError> {
@@ -60,6 +180,64 @@ impl SqliteStorage {
             .lock()
             .map_err(|_| FeederError::Database(rusqlite::Error::InvalidQuery))
     }
+
+    /// Fails once the heartbeat of this process's run lock stopped, as another run
+    /// may then take the lock over, and whatever this one goes on to send may be
+    /// sent twice
+    pub(super) fn check_run_lock(&self) -> FeederResult<()> {
+        match self.lost.lock().ok().and_then(|lost| lost.clone()) {
+            Some(reason) => Err(FeederError::Storage(format!(
+                "This run lost its lock on the database: {}",
+                reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Record whether the run lock is lost, and why
+    pub(super) fn set_run_lock_lost(&self, reason: Option<String>) {
+        if let Ok(mut lost) = self.lost.lock() {
+            *lost = reason;
+        }
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn feeds(&self) -> Box<dyn FeedRepository> {
+        Box::new(SqliteFeedRepository::new(self.clone()))
+    }
+
+    fn article_cache(&self) -> Box<dyn ArticleCacheRepository> {
+        Box::new(SqliteArticleCacheRepository::new(self.clone()))
+    }
+
+    fn feed_state(&self) -> Box<dyn FeedStateRepository> {
+        Box::new(SqliteFeedStateRepository::new(self.clone()))
+    }
+
+    fn history(&self) -> Box<dyn ArticleHistoryRepository> {
+        Box::new(SqliteArticleHistoryRepository::new(self.clone()))
+    }
+
+    fn lock_run(&self) -> FeederResult<RunGuard> {
+        Ok(Box::new(SqliteStorage::lock_run(self)?))
+    }
+
+    fn backup(&self, path: &Path) -> FeederResult<()> {
+        SqliteStorage::backup(self, path)
+    }
+
+    fn restore(&self, path: &Path) -> FeederResult<i64> {
+        SqliteStorage::restore(self, path)
+    }
+
+    fn check(&self) -> FeederResult<IntegrityReport> {
+        SqliteStorage::check(self)
+    }
+
+    fn compact(&self) -> FeederResult<u64> {
+        SqliteStorage::compact(self)
+    }
 }
 
 #[cfg(test)]
@@ -72,11 +250,67 @@ mod tests {
         let conn = storage.connection().unwrap();
 
         // Verify tables exist
-        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='feeds'").unwrap();
-        let count: i32 = stmt.query_row([], |row| row.get(0)).unwrap_or(0);
-        drop(stmt);
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='feeds'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(count, 1);
+    }
 
-        // Just check we can query
-        assert!(true);
+    #[test]
+    fn test_migrations_bring_schema_to_current_version() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let conn = storage.connection().unwrap();
+
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrations_are_idempotent_on_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.db");
+
+        SqliteStorage::new(&path).unwrap();
+        let storage = SqliteStorage::new(&path).unwrap();
+        let conn = storage.connection().unwrap();
+
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_history_backfilled_from_notified_articles() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.db");
+
+        // A database as it was before the article history existed
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute_batch(
+            "INSERT INTO feeds (url, feed_url, title, feed_type, source_type)
+             VALUES ('https://a.example', 'https://a.example/feed', 'A', 'rss', 'rss_atom');
+             INSERT INTO notified_articles (cache_key, feed_id, article_title) VALUES ('A:1', 1, 'Old post');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let storage = SqliteStorage::new(&path).unwrap();
+        let conn = storage.connection().unwrap();
+        let title: String = conn
+            .query_row(
+                "SELECT a.title FROM articles_fts JOIN articles a ON a.id = articles_fts.rowid
+                 WHERE articles_fts MATCH 'old'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(title, "Old post");
     }
 }
@@ -1,8 +1,12 @@
-use crate::domain::{Feed, FeedType, SourceType};
+use rusqlite::{Connection, Row};
+
+use crate::domain::{Feed, FeedSettings, FeedType, SourceType};
 use crate::errors::{FeederError, FeederResult};
 use crate::storage::traits::FeedRepository;
 use crate::storage::sqlite::SqliteStorage;
 
+const FEED_COLUMNS: &str = "id, url, feed_url, title, feed_type, source_type, created_at, settings";
+
 pub struct SqliteFeedRepository {
     storage: SqliteStorage,
 }
@@ -11,12 +15,29 @@ impl SqliteFeedRepository {
     pub fn new(storage: SqliteStorage) -> Self {
         Self { storage }
     }
-}
 
-impl FeedRepository for SqliteFeedRepository {
-    fn add(&self, feed: &Feed) -> FeederResult<i64> {
-        let conn = self.storage.connection()?;
+    /// Map a row selected with `FEED_COLUMNS` to a feed
+    fn feed_from_row(row: &Row<'_>) -> rusqlite::Result<Feed> {
+        let feed_type_str: String = row.get(4)?;
+        let source_type_str: String = row.get(5)?;
+        let settings_json: String = row.get(7)?;
+
+        Ok(Feed {
+            id: Some(row.get(0)?),
+            url: row.get(1)?,
+            feed_url: row.get(2)?,
+            title: row.get(3)?,
+            feed_type: feed_type_str.parse().unwrap_or(FeedType::Rss),
+            source_type: source_type_str.parse().unwrap_or(SourceType::RssAtom),
+            created_at: row.get(6)?,
+            settings: FeedSettings::from_json(&settings_json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, Box::new(e))
+            })?,
+        })
+    }
 
+    /// Insert a feed on `conn`, which may be inside a transaction
+    fn insert(conn: &Connection, feed: &Feed) -> FeederResult<i64> {
         // Check if already exists (within the same connection to avoid deadlock)
         let mut stmt = conn.prepare("SELECT EXISTS(SELECT 1 FROM feeds WHERE url = ?1)")?;
         let exists: bool = stmt.query_row([&feed.url], |row| row.get(0))?;
@@ -27,103 +48,107 @@ impl FeedRepository for SqliteFeedRepository {
         }
 
         conn.execute(
-            "INSERT INTO feeds (url, feed_url, title, feed_type, source_type) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO feeds (url, feed_url, title, feed_type, source_type, settings) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             (
                 &feed.url,
                 &feed.feed_url,
                 &feed.title,
                 feed.feed_type.as_str(),
                 feed.source_type.as_str(),
+                feed.settings.to_json(),
             ),
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
-    fn remove(&self, id: i64) -> FeederResult<()> {
+    fn get_one(&self, query: &str, param: &dyn rusqlite::ToSql) -> FeederResult<Option<Feed>> {
         let conn = self.storage.connection()?;
-        conn.execute("DELETE FROM feeds WHERE id = ?1", [id])?;
-        Ok(())
+        let mut stmt = conn.prepare(query)?;
+
+        match stmt.query_row([param], Self::feed_from_row) {
+            Ok(f) => Ok(Some(f)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(FeederError::from(e)),
+        }
     }
+}
 
-    fn get_all(&self) -> FeederResult<Vec<Feed>> {
+impl FeedRepository for SqliteFeedRepository {
+    fn add(&self, feed: &Feed) -> FeederResult<i64> {
         let conn = self.storage.connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, url, feed_url, title, feed_type, source_type, created_at FROM feeds ORDER BY created_at DESC"
-        )?;
+        Self::insert(&conn, feed)
+    }
 
-        let feeds = stmt.query_map([], |row| {
-            let feed_type_str: String = row.get(4)?;
-            let source_type_str: String = row.get(5)?;
-
-            Ok(Feed {
-                id: Some(row.get(0)?),
-                url: row.get(1)?,
-                feed_url: row.get(2)?,
-                title: row.get(3)?,
-                feed_type: feed_type_str.parse().unwrap_or(FeedType::Rss),
-                source_type: source_type_str.parse().unwrap_or(SourceType::RssAtom),
-                created_at: row.get(6)?,
-            })
-        })?;
+    fn add_batch(&self, feeds: &[Feed]) -> FeederResult<Vec<i64>> {
+        let mut conn = self.storage.connection()?;
+        let tx = conn.transaction()?;
 
-        feeds.collect::<Result<Vec<_>, _>>().map_err(FeederError::from)
+        let ids = feeds
+            .iter()
+            .map(|feed| Self::insert(&tx, feed))
+            .collect::<FeederResult<Vec<_>>>()?;
+
+        tx.commit()?;
+        Ok(ids)
     }
 
-    fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>> {
+    fn update(&self, feed: &Feed) -> FeederResult<()> {
+        let id = feed
+            .id
+            .ok_or_else(|| FeederError::FeedNotFound("Feed has no ID".to_string()))?;
+
         let conn = self.storage.connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, url, feed_url, title, feed_type, source_type, created_at FROM feeds WHERE id = ?1"
+        let updated = conn.execute(
+            "UPDATE feeds SET url = ?1, feed_url = ?2, title = ?3, feed_type = ?4, source_type = ?5, settings = ?6 WHERE id = ?7",
+            (
+                &feed.url,
+                &feed.feed_url,
+                &feed.title,
+                feed.feed_type.as_str(),
+                feed.source_type.as_str(),
+                feed.settings.to_json(),
+                id,
+            ),
         )?;
 
-        let feed = stmt.query_row([id], |row| {
-            let feed_type_str: String = row.get(4)?;
-            let source_type_str: String = row.get(5)?;
-
-            Ok(Feed {
-                id: Some(row.get(0)?),
-                url: row.get(1)?,
-                feed_url: row.get(2)?,
-                title: row.get(3)?,
-                feed_type: feed_type_str.parse().unwrap_or(FeedType::Rss),
-                source_type: source_type_str.parse().unwrap_or(SourceType::RssAtom),
-                created_at: row.get(6)?,
-            })
-        });
-
-        match feed {
-            Ok(f) => Ok(Some(f)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(FeederError::from(e)),
+        if updated == 0 {
+            return Err(FeederError::FeedNotFound(id.to_string()));
         }
+
+        Ok(())
     }
 
-    fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>> {
+    fn remove(&self, id: i64) -> FeederResult<()> {
         let conn = self.storage.connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, url, feed_url, title, feed_type, source_type, created_at FROM feeds WHERE url = ?1"
-        )?;
+        conn.execute("DELETE FROM feeds WHERE id = ?1", [id])?;
+        Ok(())
+    }
 
-        let feed = stmt.query_row([url], |row| {
-            let feed_type_str: String = row.get(4)?;
-            let source_type_str: String = row.get(5)?;
-
-            Ok(Feed {
-                id: Some(row.get(0)?),
-                url: row.get(1)?,
-                feed_url: row.get(2)?,
-                title: row.get(3)?,
-                feed_type: feed_type_str.parse().unwrap_or(FeedType::Rss),
-                source_type: source_type_str.parse().unwrap_or(SourceType::RssAtom),
-                created_at: row.get(6)?,
-            })
-        });
+    fn get_all(&self) -> FeederResult<Vec<Feed>> {
+        let conn = self.storage.connection()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM feeds ORDER BY created_at DESC",
+            FEED_COLUMNS
+        ))?;
 
-        match feed {
-            Ok(f) => Ok(Some(f)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(FeederError::from(e)),
-        }
+        let feeds = stmt.query_map([], Self::feed_from_row)?;
+
+        feeds.collect::<Result<Vec<_>, _>>().map_err(FeederError::from)
+    }
+
+    fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>> {
+        self.get_one(
+            &format!("SELECT {} FROM feeds WHERE id = ?1", FEED_COLUMNS),
+            &id,
+        )
+    }
+
+    fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>> {
+        self.get_one(
+            &format!("SELECT {} FROM feeds WHERE url = ?1", FEED_COLUMNS),
+            &url,
+        )
     }
 
     fn exists(&self, url: &str) -> FeederResult<bool> {
@@ -223,6 +248,51 @@ mod tests {
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_settings_persisted() {
+        let repo = setup_repo();
+        let feed = Feed::new(
+            "https://status.example.com".to_string(),
+            "https://status.example.com".to_string(),
+            "Status".to_string(),
+            FeedType::Html,
+            SourceType::PageWatch,
+        )
+        .with_settings(FeedSettings {
+            selector: Some("#incidents".to_string()),
+            ..Default::default()
+        });
+
+        let id = repo.add(&feed).unwrap();
+        let retrieved = repo.get_by_id(id).unwrap().unwrap();
+
+        assert_eq!(retrieved.source_type, SourceType::PageWatch);
+        assert_eq!(retrieved.settings.selector.as_deref(), Some("#incidents"));
+    }
+
+    #[test]
+    fn test_update() {
+        let repo = setup_repo();
+        let feed = Feed::new(
+            "https://example.com/feed".to_string(),
+            "https://example.com/feed".to_string(),
+            "Example Feed".to_string(),
+            FeedType::Rss,
+            SourceType::RssAtom,
+        );
+
+        let id = repo.add(&feed).unwrap();
+        let mut stored = repo.get_by_id(id).unwrap().unwrap();
+        stored.settings.priority = Some(5);
+        repo.update(&stored).unwrap();
+
+        let retrieved = repo.get_by_id(id).unwrap().unwrap();
+        assert_eq!(retrieved.settings.priority, Some(5));
+
+        stored.id = Some(id + 100);
+        assert!(matches!(repo.update(&stored), Err(FeederError::FeedNotFound(_))));
+    }
+
     #[test]
     fn test_exists() {
         let repo = setup_repo();
@@ -238,4 +308,5 @@ mod tests {
         repo.add(&feed).unwrap();
         assert!(repo.exists("https://example.com/feed").unwrap());
     }
+
 }
@@ -0,0 +1,88 @@
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::traits::FeedStateRepository;
+use crate::storage::sqlite::SqliteStorage;
+
+pub struct SqliteFeedStateRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteFeedStateRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+}
+
+impl FeedStateRepository for SqliteFeedStateRepository {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>> {
+        let conn = self.storage.connection()?;
+        let mut stmt = conn.prepare("SELECT value FROM feed_state WHERE feed_id = ?1 AND key = ?2")?;
+
+        match stmt.query_row((feed_id, key), |row| row.get(0)) {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(FeederError::from(e)),
+        }
+    }
+
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()> {
+        let conn = self.storage.connection()?;
+        conn.execute(
+            "INSERT INTO feed_state (feed_id, key, value) VALUES (?1, ?2, ?3)
+             ON CONFLICT(feed_id, key) DO UPDATE SET value = excluded.value, updated_at = datetime('now')",
+            (feed_id, key, value),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Feed, FeedType, SourceType};
+    use crate::storage::sqlite::SqliteFeedRepository;
+    use crate::storage::traits::FeedRepository;
+
+    fn setup() -> (SqliteFeedRepository, SqliteFeedStateRepository, i64) {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let feed_repo = SqliteFeedRepository::new(storage.clone());
+        let state_repo = SqliteFeedStateRepository::new(storage);
+
+        let feed = Feed::new(
+            "https://status.example.com".to_string(),
+            "https://status.example.com".to_string(),
+            "Status".to_string(),
+            FeedType::Html,
+            SourceType::PageWatch,
+        );
+        let feed_id = feed_repo.add(&feed).unwrap();
+
+        (feed_repo, state_repo, feed_id)
+    }
+
+    #[test]
+    fn test_get_missing_key() {
+        let (_, state_repo, feed_id) = setup();
+        assert!(state_repo.get(feed_id, "snapshot").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_and_overwrite() {
+        let (_, state_repo, feed_id) = setup();
+
+        state_repo.set(feed_id, "snapshot", "first").unwrap();
+        assert_eq!(state_repo.get(feed_id, "snapshot").unwrap().as_deref(), Some("first"));
+
+        state_repo.set(feed_id, "snapshot", "second").unwrap();
+        assert_eq!(state_repo.get(feed_id, "snapshot").unwrap().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_state_removed_with_feed() {
+        let (feed_repo, state_repo, feed_id) = setup();
+
+        state_repo.set(feed_id, "snapshot", "content").unwrap();
+        feed_repo.remove(feed_id).unwrap();
+
+        assert!(state_repo.get(feed_id, "snapshot").unwrap().is_none());
+    }
+}
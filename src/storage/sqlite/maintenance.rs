@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::integrity::{ForeignKeyViolation, IntegrityReport};
+use crate::storage::sqlite::{SqliteStorage, SCHEMA_VERSION};
+
+/// Pages copied per backup step; the source is unlocked between steps so a
+/// running `feeder run` isn't blocked for the whole copy
+const BACKUP_PAGES_PER_STEP: i32 = 256;
+const BACKUP_PAUSE: Duration = Duration::from_millis(10);
+
+/// Tables every feeder database has, whatever its version
+const REQUIRED_TABLES: &[&str] = &["feeds", "notified_articles"];
+
+impl SqliteStorage {
+    /// Copy the database to `path` with SQLite's online backup API, which is safe
+    /// while another process is using it. The copy is written next to `path` and
+    /// renamed into place, so an existing backup is only replaced by a complete one.
+    pub fn backup<P: AsRef<Path>>(&self, path: P) -> FeederResult<()> {
+        let path = path.as_ref();
+        let partial = partial_path(path);
+
+        let result = (|| -> FeederResult<()> {
+            let mut destination = Connection::open(&partial)?;
+            let conn = self.connection()?;
+            Backup::new(&conn, &mut destination)?.run_to_completion(
+                BACKUP_PAGES_PER_STEP,
+                BACKUP_PAUSE,
+                None,
+            )?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => Ok(std::fs::rename(&partial, path)?),
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace the database's contents with the backup at `path`, then bring it to
+    /// the current schema. Returns the backup's schema version.
+    ///
+    /// The backup must be a sound feeder database no newer than this build.
+    pub fn restore<P: AsRef<Path>>(&self, path: P) -> FeederResult<i64> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(FeederError::InvalidInput(format!("No backup at {}", path.display())));
+        }
+
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version = validate_backup(&source).map_err(|reason| {
+            FeederError::InvalidInput(format!("Can't restore {}: {}", path.display(), reason))
+        })?;
+
+        let mut conn = self.connection()?;
+        Backup::new(&source, &mut conn)?.run_to_completion(
+            BACKUP_PAGES_PER_STEP,
+            BACKUP_PAUSE,
+            None,
+        )?;
+        Self::upgrade(&mut conn)?;
+
+        // The lock and claims belonged to runs of the backed-up database; kept, they'd
+        // block the next run until the lock went stale
+        conn.execute_batch(
+            "DELETE FROM run_lock;
+             DELETE FROM notified_articles WHERE state = 'claimed';",
+        )?;
+
+        Ok(version)
+    }
+
+    /// Run SQLite's integrity and foreign key checks and look for cache entries
+    /// left behind by removed feeds
+    pub fn check(&self) -> FeederResult<IntegrityReport> {
+        let conn = self.connection()?;
+
+        let integrity_errors = conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|message| message != "ok")
+            .collect();
+
+        let foreign_key_violations = conn
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |row| {
+                Ok(ForeignKeyViolation {
+                    table: row.get(0)?,
+                    rowid: row.get(1)?,
+                    parent: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let orphaned_notifications: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM notified_articles n
+             WHERE NOT EXISTS (SELECT 1 FROM feeds f WHERE f.id = n.feed_id)",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(IntegrityReport {
+            integrity_errors,
+            foreign_key_violations,
+            orphaned_notifications: orphaned_notifications as usize,
+        })
+    }
+
+    /// Refresh query planner statistics and rebuild the database file, returning
+    /// how many bytes were freed
+    pub fn compact(&self) -> FeederResult<u64> {
+        let conn = self.connection()?;
+
+        let before = database_size(&conn)?;
+        conn.execute_batch("ANALYZE; VACUUM;")?;
+        let after = database_size(&conn)?;
+
+        Ok(before.saturating_sub(after))
+    }
+}
+
+fn database_size(conn: &Connection) -> rusqlite::Result<u64> {
+    conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// `feeder.db` -> `feeder.db.partial`
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+/// Schema version of a backup, or why it can't be restored
+fn validate_backup(source: &Connection) -> Result<i64, String> {
+    let version: i64 = source
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "it has schema version {}, newer than the {} this version of feeder supports",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    for table in REQUIRED_TABLES {
+        let exists: bool = source
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+                [table],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !exists {
+            return Err(format!("it is not a feeder database (no {} table)", table));
+        }
+    }
+
+    let status: String = source
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if status != "ok" {
+        return Err(format!("it is damaged ({})", status));
+    }
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Feed, FeedType, SourceType};
+    use crate::storage::sqlite::{SqliteArticleCacheRepository, SqliteFeedRepository};
+    use crate::storage::traits::{ArticleCacheRepository, FeedRepository};
+
+    fn storage_with_feed(path: &Path) -> SqliteStorage {
+        let storage = SqliteStorage::new(path).unwrap();
+        let feed_id = SqliteFeedRepository::new(storage.clone())
+            .add(&Feed::new(
+                "https://example.com".to_string(),
+                "https://example.com/feed".to_string(),
+                "Example".to_string(),
+                FeedType::Rss,
+                SourceType::RssAtom,
+            ))
+            .unwrap();
+        SqliteArticleCacheRepository::new(storage.clone())
+            .mark_notified("Example:1", feed_id, "First")
+            .unwrap();
+        storage
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let backup_path = dir.path().join("backup.db");
+
+        let original = storage_with_feed(&dir.path().join("feeder.db"));
+        original.backup(&backup_path).unwrap();
+        assert!(!partial_path(&backup_path).exists());
+
+        let restored = SqliteStorage::new(dir.path().join("new.db")).unwrap();
+        assert_eq!(restored.restore(&backup_path).unwrap(), SCHEMA_VERSION);
+
+        let feeds = SqliteFeedRepository::new(restored.clone()).get_all().unwrap();
+        assert_eq!(feeds.len(), 1);
+        assert!(SqliteArticleCacheRepository::new(restored)
+            .is_notified("Example:1")
+            .unwrap());
+    }
+
+    #[test]
+    fn test_restore_migrates_older_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let backup_path = dir.path().join("old.db");
+
+        let conn = Connection::open(&backup_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE feeds (id INTEGER PRIMARY KEY AUTOINCREMENT, url TEXT NOT NULL UNIQUE,
+                 feed_url TEXT NOT NULL, title TEXT NOT NULL, feed_type TEXT NOT NULL,
+                 source_type TEXT NOT NULL, created_at TEXT NOT NULL DEFAULT (datetime('now')));
+             CREATE TABLE notified_articles (id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 cache_key TEXT NOT NULL UNIQUE, feed_id INTEGER NOT NULL, article_title TEXT,
+                 notified_at TEXT NOT NULL DEFAULT (datetime('now')),
+                 FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE);
+             INSERT INTO feeds (url, feed_url, title, feed_type, source_type)
+             VALUES ('https://a.example', 'https://a.example/feed', 'A', 'rss', 'rss_atom');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let storage = SqliteStorage::in_memory().unwrap();
+        assert_eq!(storage.restore(&backup_path).unwrap(), 0);
+
+        let feeds = SqliteFeedRepository::new(storage.clone()).get_all().unwrap();
+        assert_eq!(feeds[0].title, "A");
+        let version: i64 = storage
+            .connection()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_restore_rejects_newer_and_foreign_databases() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = storage_with_feed(&dir.path().join("feeder.db"));
+
+        let newer = dir.path().join("newer.db");
+        storage.backup(&newer).unwrap();
+        Connection::open(&newer)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        let err = storage.restore(&newer).unwrap_err().to_string();
+        assert!(err.contains("newer"), "{}", err);
+
+        let other = dir.path().join("other.db");
+        Connection::open(&other)
+            .unwrap()
+            .execute_batch("CREATE TABLE notes (body TEXT);")
+            .unwrap();
+        let err = storage.restore(&other).unwrap_err().to_string();
+        assert!(err.contains("not a feeder database"), "{}", err);
+
+        assert!(storage.restore(dir.path().join("missing.db")).is_err());
+
+        // The live database is untouched by failed restores
+        assert_eq!(SqliteFeedRepository::new(storage).get_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_check_reports_orphaned_notifications() {
+        let storage = storage_with_feed(Path::new(":memory:"));
+        assert!(storage.check().unwrap().is_ok());
+
+        {
+            let conn = storage.connection().unwrap();
+            conn.execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO notified_articles (cache_key, feed_id) VALUES ('Gone:1', 42);
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+        }
+
+        let report = storage.check().unwrap();
+        assert!(!report.is_ok());
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(report.orphaned_notifications, 1);
+        assert_eq!(report.foreign_key_violations[0].table, "notified_articles");
+        assert_eq!(report.foreign_key_violations[0].parent, "feeds");
+    }
+
+    #[test]
+    fn test_compact_reports_freed_space() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("feeder.db")).unwrap();
+
+        {
+            let conn = storage.connection().unwrap();
+            conn.execute_batch(
+                "CREATE TABLE filler (data BLOB);
+                 WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+                 INSERT INTO filler SELECT zeroblob(4096) FROM n;
+                 DROP TABLE filler;",
+            )
+            .unwrap();
+        }
+
+        assert!(storage.compact().unwrap() >= 200 * 4096);
+        assert_eq!(storage.compact().unwrap(), 0);
+    }
+}
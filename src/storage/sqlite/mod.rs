@@ -1,7 +1,14 @@
 mod connection;
 mod feed_repository;
 mod article_cache_repository;
+mod article_history_repository;
+mod feed_state_repository;
+mod maintenance;
+mod run_lock;
 
-pub use connection::SqliteStorage;
+pub use connection::{SqliteStorage, SCHEMA_VERSION};
 pub use feed_repository::SqliteFeedRepository;
 pub use article_cache_repository::SqliteArticleCacheRepository;
+pub use article_history_repository::SqliteArticleHistoryRepository;
+pub use feed_state_repository::SqliteFeedStateRepository;
+pub use run_lock::RunLock;
@@ -0,0 +1,258 @@
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use rusqlite::{OptionalExtension, TransactionBehavior};
+
+use crate::errors::{FeederError, FeederResult};
+use crate::storage::sqlite::SqliteStorage;
+
+/// A lock whose holder hasn't refreshed it for this long is treated as abandoned
+const STALE_AFTER: Duration = Duration::from_secs(120);
+const HEARTBEAT_EVERY: Duration = Duration::from_secs(20);
+/// A run whose heartbeat has failed for this long stops claiming articles, well
+/// before another run would take the lock over
+const LOST_AFTER: Duration = Duration::from_secs(60);
+
+/// Exclusive lock on the database held for the length of a run.
+///
+/// Stored as a row in `run_lock`, so it works wherever the database does. A
+/// background thread refreshes it while held; a lock left by a process that died
+/// goes stale after two minutes and is taken over. A run whose heartbeat stopped
+/// can no longer claim articles. Released on drop.
+pub struct RunLock {
+    storage: SqliteStorage,
+    owner: String,
+    stop: Option<Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl SqliteStorage {
+    /// Take the run lock, failing if another live process holds it.
+    ///
+    /// Cache entries still claimed by an earlier run that died before sending them
+    /// are released, so those articles are tried again.
+    pub fn lock_run(&self) -> FeederResult<RunLock> {
+        self.lock_run_with(STALE_AFTER, HEARTBEAT_EVERY)
+    }
+
+    fn lock_run_with(&self, stale_after: Duration, heartbeat_every: Duration) -> FeederResult<RunLock> {
+        let owner = format!("{}-{:016x}", std::process::id(), fastrand::u64(..));
+
+        {
+            let mut conn = self.connection()?;
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+            let holder: Option<(i64, String, bool)> = tx
+                .query_row(
+                    "SELECT pid, acquired_at, heartbeat_at >= datetime('now', printf('-%d seconds', ?1))
+                     FROM run_lock WHERE id = 1",
+                    [stale_after.as_secs() as i64],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()?;
+
+            if let Some((pid, since, true)) = holder {
+                return Err(FeederError::RunInProgress(format!(
+                    "process {} holds the lock since {} UTC",
+                    pid, since
+                )));
+            }
+
+            tx.execute(
+                "INSERT OR REPLACE INTO run_lock (id, owner, pid) VALUES (1, ?1, ?2)",
+                (&owner, std::process::id()),
+            )?;
+            tx.execute("DELETE FROM notified_articles WHERE state = 'claimed'", [])?;
+            tx.commit()?;
+        }
+
+        self.set_run_lock_lost(None);
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let storage = self.clone();
+        let heartbeat_owner = owner.clone();
+        let heartbeat = std::thread::spawn(move || {
+            let mut last_beat = Instant::now();
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(heartbeat_every) {
+                match storage.heartbeat(&heartbeat_owner) {
+                    Ok(true) => last_beat = Instant::now(),
+                    Ok(false) => {
+                        storage.set_run_lock_lost(Some("another run took it over".to_string()));
+                        return;
+                    }
+                    Err(e) if last_beat.elapsed() >= LOST_AFTER => {
+                        storage.set_run_lock_lost(Some(format!(
+                            "no heartbeat for {} seconds: {}",
+                            last_beat.elapsed().as_secs(),
+                            e
+                        )));
+                        return;
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+
+        Ok(RunLock {
+            storage: self.clone(),
+            owner,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        })
+    }
+
+    /// Refresh the heartbeat of lock `owner`; false if another run took the lock over
+    fn heartbeat(&self, owner: &str) -> FeederResult<bool> {
+        let updated = self.connection()?.execute(
+            "UPDATE run_lock SET heartbeat_at = datetime('now') WHERE id = 1 AND owner = ?1",
+            [owner],
+        )?;
+        Ok(updated == 1)
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+
+        if let Ok(conn) = self.storage.connection() {
+            let _ = conn.execute("DELETE FROM run_lock WHERE id = 1 AND owner = ?1", [&self.owner]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sqlite::SqliteArticleCacheRepository;
+    use crate::storage::traits::ArticleCacheRepository;
+
+    fn db_path() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("feeder.db");
+        (dir, path)
+    }
+
+    #[test]
+    fn test_second_lock_fails_until_first_released() {
+        let (_dir, path) = db_path();
+        // Two processes open the database separately
+        let first = SqliteStorage::new(&path).unwrap();
+        let second = SqliteStorage::new(&path).unwrap();
+
+        let lock = first.lock_run().unwrap();
+        let err = second.lock_run().err().unwrap();
+        assert!(matches!(err, FeederError::RunInProgress(_)), "{}", err);
+
+        drop(lock);
+        assert!(second.lock_run().is_ok());
+    }
+
+    #[test]
+    fn test_stale_lock_taken_over() {
+        let (_dir, path) = db_path();
+        let first = SqliteStorage::new(&path).unwrap();
+        let second = SqliteStorage::new(&path).unwrap();
+
+        // A process that died: its lock is never refreshed or released
+        let abandoned = first.lock_run().unwrap();
+        first
+            .connection()
+            .unwrap()
+            .execute("UPDATE run_lock SET heartbeat_at = datetime('now', '-1 hour')", [])
+            .unwrap();
+        std::mem::forget(abandoned);
+
+        assert!(second.lock_run().is_ok());
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_lock_fresh() {
+        let (_dir, path) = db_path();
+        let storage = SqliteStorage::new(&path).unwrap();
+
+        let _lock = storage
+            .lock_run_with(STALE_AFTER, Duration::from_millis(10))
+            .unwrap();
+        storage
+            .connection()
+            .unwrap()
+            .execute("UPDATE run_lock SET heartbeat_at = datetime('now', '-1 hour')", [])
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let fresh: bool = storage
+            .connection()
+            .unwrap()
+            .query_row(
+                "SELECT heartbeat_at >= datetime('now', '-1 minute') FROM run_lock",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(fresh);
+    }
+
+    #[test]
+    fn test_lock_taken_over_stops_claims() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        storage
+            .connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO feeds (url, feed_url, title, feed_type, source_type)
+                 VALUES ('https://a.example', 'https://a.example/feed', 'A', 'rss', 'rss_atom')",
+                [],
+            )
+            .unwrap();
+        let cache = SqliteArticleCacheRepository::new(storage.clone());
+
+        let _lock = storage
+            .lock_run_with(STALE_AFTER, Duration::from_millis(10))
+            .unwrap();
+        assert!(cache.claim("A:1", 1, "One").unwrap());
+
+        // Another run took the lock over after this one looked dead
+        storage
+            .connection()
+            .unwrap()
+            .execute("UPDATE run_lock SET owner = 'other'", [])
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let err = cache.claim("A:2", 1, "Two").unwrap_err();
+        assert!(err.to_string().contains("another run took it over"), "{}", err);
+    }
+
+    #[test]
+    fn test_claims_of_dead_run_released() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        {
+            let conn = storage.connection().unwrap();
+            conn.execute_batch(
+                "INSERT INTO feeds (url, feed_url, title, feed_type, source_type)
+                 VALUES ('https://a.example', 'https://a.example/feed', 'A', 'rss', 'rss_atom');
+                 INSERT INTO notified_articles (cache_key, feed_id, state) VALUES ('A:1', 1, 'claimed');
+                 INSERT INTO notified_articles (cache_key, feed_id, state) VALUES ('A:2', 1, 'sent');",
+            )
+            .unwrap();
+        }
+
+        let _lock = storage.lock_run().unwrap();
+
+        let keys: Vec<String> = storage
+            .connection()
+            .unwrap()
+            .prepare("SELECT cache_key FROM notified_articles")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(keys, vec!["A:2".to_string()]);
+    }
+}
@@ -1,19 +1,239 @@
-use crate::domain::Feed;
+use std::path::Path;
+
+use crate::domain::{Article, Feed, HistoryEntry, RetentionPolicy};
 use crate::errors::FeederResult;
+use crate::storage::integrity::IntegrityReport;
 
 #[cfg_attr(test, mockall::automock)]
 pub trait FeedRepository: Send + Sync {
     fn add(&self, feed: &Feed) -> FeederResult<i64>;
+    /// Add several feeds in one transaction: all are added, or none if one fails
+    fn add_batch(&self, feeds: &[Feed]) -> FeederResult<Vec<i64>>;
+    /// Overwrite a stored feed's fields, matched by `feed.id`
+    fn update(&self, feed: &Feed) -> FeederResult<()>;
     fn remove(&self, id: i64) -> FeederResult<()>;
     fn get_all(&self) -> FeederResult<Vec<Feed>>;
     fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>>;
     fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>>;
     fn exists(&self, url: &str) -> FeederResult<bool>;
+
+    /// Take the next feed for this run to fetch, skipping `taken` (the IDs already
+    /// handed out) and feeds another worker is fetching. Backends where runs hold
+    /// the store to themselves hand out every feed, in `get_all` order.
+    fn lease_next(&self, taken: &[i64]) -> FeederResult<Option<Feed>> {
+        Ok(self
+            .get_all()?
+            .into_iter()
+            .find(|feed| feed.id.is_some_and(|id| !taken.contains(&id))))
+    }
 }
 
 #[cfg_attr(test, mockall::automock)]
 pub trait ArticleCacheRepository: Send + Sync {
     fn is_notified(&self, cache_key: &str) -> FeederResult<bool>;
+    /// Record an article as sent, confirming a claim if there is one
     fn mark_notified(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<()>;
+    /// Record several articles of a feed, keyed by cache key, as sent and keep them
+    /// in the article history, all in one transaction
+    // Named for mockall, which can't mock the elided lifetime
+    #[allow(clippy::needless_lifetimes)]
+    fn mark_notified_batch<'a>(
+        &self,
+        feed_id: i64,
+        entries: &[(String, &'a Article)],
+    ) -> FeederResult<()>;
+    /// Reserve an article before sending it. Returns false when it is already sent
+    /// or claimed, in which case it must not be sent.
+    fn claim(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<bool>;
+    /// Give up a claim on an article that couldn't be sent, so a later run retries it
+    fn release(&self, cache_key: &str) -> FeederResult<()>;
     fn get_unnotified(&self, cache_keys: &[String]) -> FeederResult<Vec<String>>;
+    /// Record that the cached entries among `cache_keys` were still in their feed at
+    /// `seen_at` (`YYYY-MM-DD HH:MM:SS`, UTC)
+    fn mark_seen(&self, cache_keys: &[String], seen_at: &str) -> FeederResult<()>;
+    /// Remove entries outside `policy`, keeping any seen in their feed's last
+    /// successful fetch. Returns how many were removed.
+    fn prune(&self, policy: &RetentionPolicy) -> FeederResult<usize>;
+    /// Move a feed's entries keyed under `old_prefix` to `new_prefix`, as when the
+    /// feed is renamed. Entries whose new key is already taken stay as they are.
+    /// Returns how many moved.
+    fn rekey(&self, feed_id: i64, old_prefix: &str, new_prefix: &str) -> FeederResult<usize>;
+}
+
+/// `FeedStateRepository` key holding when a feed was last fetched successfully
+pub const FETCHED_AT_KEY: &str = "fetched_at";
+
+/// `FeedStateRepository` key counting a feed's failed fetches since its last success
+pub const ERROR_COUNT_KEY: &str = "error_count";
+
+/// `FeedStateRepository` key holding why a feed's most recent failed fetch failed, empty
+/// once a fetch succeeds again
+pub const LAST_ERROR_KEY: &str = "last_error";
+
+/// `FeedStateRepository` key holding, as JSON, the feed's notifications that sinks
+/// still have to be sent: ones a sink failed to take, and ones waiting for a digest
+pub const PENDING_NOTIFICATIONS_KEY: &str = "pending_notifications";
+
+/// Small key/value store for state a source keeps about a feed between runs
+#[cfg_attr(test, mockall::automock)]
+pub trait FeedStateRepository: Send + Sync {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>>;
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()>;
+}
+
+/// Notified articles, kept with their metadata and indexed for full-text search
+#[cfg_attr(test, mockall::automock)]
+pub trait ArticleHistoryRepository: Send + Sync {
+    fn record(&self, cache_key: &str, feed_id: i64, article: &Article) -> FeederResult<()>;
+    /// Most recently notified articles first, optionally of one feed only
+    fn recent(&self, feed_id: Option<i64>, limit: usize) -> FeederResult<Vec<HistoryEntry>>;
+    /// How many articles were notified, optionally of one feed only
+    fn count(&self, feed_id: Option<i64>) -> FeederResult<usize>;
+    /// Articles matching every word of `query`, best matches first. `since` is a
+    /// `YYYY-MM-DD HH:MM:SS` lower bound on when the article was notified.
+    fn search(
+        &self,
+        query: &str,
+        feed_id: Option<i64>,
+        since: Option<String>,
+        limit: usize,
+    ) -> FeederResult<Vec<HistoryEntry>>;
+}
+
+/// Held for the length of a run; dropping it releases the lock
+pub type RunGuard = Box<dyn Send>;
+
+/// A storage backend: hands out repositories that share one store, and the
+/// maintenance operations behind `feeder run` and `feeder db`
+pub trait Storage: Send + Sync {
+    fn feeds(&self) -> Box<dyn FeedRepository>;
+    fn article_cache(&self) -> Box<dyn ArticleCacheRepository>;
+    fn feed_state(&self) -> Box<dyn FeedStateRepository>;
+    fn history(&self) -> Box<dyn ArticleHistoryRepository>;
+
+    /// Take the run lock, failing with `RunInProgress` while another live process
+    /// holds it. Articles claimed by a run that died are released. Backends shared
+    /// by several workers let their runs proceed side by side instead, splitting the
+    /// feeds between them through `FeedRepository::lease_next`.
+    fn lock_run(&self) -> FeederResult<RunGuard>;
+    /// Copy the store to `path`, safely while it is in use
+    fn backup(&self, path: &Path) -> FeederResult<()>;
+    /// Replace the store's contents with the backup at `path`, returning the
+    /// backup's format version
+    fn restore(&self, path: &Path) -> FeederResult<i64>;
+    fn check(&self) -> FeederResult<IntegrityReport>;
+    /// Reclaim space freed by pruning, returning how many bytes were freed
+    fn compact(&self) -> FeederResult<u64>;
+}
+
+// Boxed repositories, as handed out by `Storage`, work wherever a repository does
+
+impl<T: FeedRepository + ?Sized> FeedRepository for Box<T> {
+    fn add(&self, feed: &Feed) -> FeederResult<i64> {
+        (**self).add(feed)
+    }
+
+    fn add_batch(&self, feeds: &[Feed]) -> FeederResult<Vec<i64>> {
+        (**self).add_batch(feeds)
+    }
+
+    fn update(&self, feed: &Feed) -> FeederResult<()> {
+        (**self).update(feed)
+    }
+
+    fn remove(&self, id: i64) -> FeederResult<()> {
+        (**self).remove(id)
+    }
+
+    fn get_all(&self) -> FeederResult<Vec<Feed>> {
+        (**self).get_all()
+    }
+
+    fn get_by_id(&self, id: i64) -> FeederResult<Option<Feed>> {
+        (**self).get_by_id(id)
+    }
+
+    fn get_by_url(&self, url: &str) -> FeederResult<Option<Feed>> {
+        (**self).get_by_url(url)
+    }
+
+    fn exists(&self, url: &str) -> FeederResult<bool> {
+        (**self).exists(url)
+    }
+
+    fn lease_next(&self, taken: &[i64]) -> FeederResult<Option<Feed>> {
+        (**self).lease_next(taken)
+    }
+}
+
+impl<T: ArticleCacheRepository + ?Sized> ArticleCacheRepository for Box<T> {
+    fn is_notified(&self, cache_key: &str) -> FeederResult<bool> {
+        (**self).is_notified(cache_key)
+    }
+
+    fn mark_notified(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<()> {
+        (**self).mark_notified(cache_key, feed_id, title)
+    }
+
+    fn mark_notified_batch(&self, feed_id: i64, entries: &[(String, &Article)]) -> FeederResult<()> {
+        (**self).mark_notified_batch(feed_id, entries)
+    }
+
+    fn claim(&self, cache_key: &str, feed_id: i64, title: &str) -> FeederResult<bool> {
+        (**self).claim(cache_key, feed_id, title)
+    }
+
+    fn release(&self, cache_key: &str) -> FeederResult<()> {
+        (**self).release(cache_key)
+    }
+
+    fn get_unnotified(&self, cache_keys: &[String]) -> FeederResult<Vec<String>> {
+        (**self).get_unnotified(cache_keys)
+    }
+
+    fn mark_seen(&self, cache_keys: &[String], seen_at: &str) -> FeederResult<()> {
+        (**self).mark_seen(cache_keys, seen_at)
+    }
+
+    fn prune(&self, policy: &RetentionPolicy) -> FeederResult<usize> {
+        (**self).prune(policy)
+    }
+
+    fn rekey(&self, feed_id: i64, old_prefix: &str, new_prefix: &str) -> FeederResult<usize> {
+        (**self).rekey(feed_id, old_prefix, new_prefix)
+    }
+}
+
+impl<T: FeedStateRepository + ?Sized> FeedStateRepository for Box<T> {
+    fn get(&self, feed_id: i64, key: &str) -> FeederResult<Option<String>> {
+        (**self).get(feed_id, key)
+    }
+
+    fn set(&self, feed_id: i64, key: &str, value: &str) -> FeederResult<()> {
+        (**self).set(feed_id, key, value)
+    }
+}
+
+impl<T: ArticleHistoryRepository + ?Sized> ArticleHistoryRepository for Box<T> {
+    fn record(&self, cache_key: &str, feed_id: i64, article: &Article) -> FeederResult<()> {
+        (**self).record(cache_key, feed_id, article)
+    }
+
+    fn recent(&self, feed_id: Option<i64>, limit: usize) -> FeederResult<Vec<HistoryEntry>> {
+        (**self).recent(feed_id, limit)
+    }
+
+    fn count(&self, feed_id: Option<i64>) -> FeederResult<usize> {
+        (**self).count(feed_id)
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        feed_id: Option<i64>,
+        since: Option<String>,
+        limit: usize,
+    ) -> FeederResult<Vec<HistoryEntry>> {
+        (**self).search(query, feed_id, since, limit)
+    }
 }
@@ -1,11 +1,24 @@
+use std::path::Path;
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use tempfile::TempDir;
 
+#[allow(deprecated)]
 fn feeder_cmd() -> Command {
     Command::cargo_bin("feeder").unwrap()
 }
 
+/// `feeder` keeping its data in the file store at `store_path`
+fn feeder_with_store(store_path: &Path) -> Command {
+    let mut cmd = feeder_cmd();
+    cmd.env("FEEDER_STORAGE", "file")
+        .env("FEEDER_DB_PATH", store_path)
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token");
+    cmd
+}
+
 #[test]
 fn test_help_shows_skip_notify_flag() {
     feeder_cmd()
@@ -72,6 +85,363 @@ fn test_run_with_both_flags_skip_notify_takes_precedence() {
         .stdout(predicate::str::contains("skip-notify mode"));
 }
 
+#[test]
+fn test_add_help_shows_discovery_flags() {
+    feeder_cmd()
+        .arg("add")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--pick"))
+        .stdout(predicate::str::contains("--watch"));
+}
+
+#[test]
+fn test_edit_rejects_out_of_range_priority() {
+    feeder_cmd()
+        .args(["edit", "1", "--priority", "9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--priority"));
+}
+
+#[test]
+fn test_add_rejects_two_kinds_of_credentials() {
+    feeder_cmd()
+        .args(["add", "https://example.com/feed", "--bearer", "t", "--cookie", "c=1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_history_and_search_on_empty_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("history.db");
+
+    feeder_cmd()
+        .arg("history")
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .env("NOTEBROOK_CHANNEL", "test-channel")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No articles notified yet"));
+
+    feeder_cmd()
+        .args(["search", "rust", "--since", "7d", "--json"])
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .env("NOTEBROOK_CHANNEL", "test-channel")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("[]\n"));
+}
+
+#[test]
+fn test_refresh_on_empty_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("refresh.db");
+
+    feeder_cmd()
+        .arg("refresh")
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No feeds configured"));
+
+    feeder_cmd()
+        .args(["refresh", "Missing Feed"])
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Missing Feed"));
+}
+
+#[test]
+fn test_search_rejects_bad_since() {
+    feeder_cmd()
+        .args(["search", "rust", "--since", "last week"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected YYYY-MM-DD"));
+}
+
+#[test]
+fn test_db_prune_needs_a_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("prune.db");
+
+    feeder_cmd()
+        .args(["db", "prune"])
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .env_remove("FEEDER_RETENTION_DAYS")
+        .env_remove("FEEDER_RETENTION_KEEP")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No retention limit"));
+
+    feeder_cmd()
+        .args(["db", "prune", "--days", "30"])
+        .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 0 cache entries").and(predicate::str::contains("Reclaimed")));
+}
+
+#[test]
+fn test_db_backup_check_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("feeder.db");
+    let backup_path = temp_dir.path().join("backup.db");
+
+    let db = |args: &[&str]| {
+        let mut cmd = feeder_cmd();
+        cmd.arg("db")
+            .args(args)
+            .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+            .env("NOTEBROOK_URL", "http://localhost:8080")
+            .env("NOTEBROOK_TOKEN", "test-token");
+        cmd
+    };
+
+    db(&["backup", backup_path.to_str().unwrap()]).assert().success();
+    assert!(backup_path.exists());
+
+    db(&["check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Integrity: ok").and(predicate::str::contains("Orphaned cache entries: 0")));
+
+    db(&["restore", backup_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("schema version"));
+
+    db(&["restore", temp_dir.path().join("missing.db").to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No backup at"));
+}
+
+#[test]
+fn test_run_refuses_while_another_run_holds_the_lock() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("locked.db");
+
+    let run = || {
+        let mut cmd = feeder_cmd();
+        cmd.args(["run", "--dry-run"])
+            .env("FEEDER_DB_PATH", db_path.to_str().unwrap())
+            .env("NOTEBROOK_URL", "http://localhost:8080")
+            .env("NOTEBROOK_TOKEN", "test-token");
+        cmd
+    };
+
+    // Creates the database, and releases the lock on exit
+    run().assert().success();
+
+    rusqlite::Connection::open(&db_path)
+        .unwrap()
+        .execute("INSERT INTO run_lock (id, owner, pid) VALUES (1, 'other', 4242)", [])
+        .unwrap();
+
+    run()
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Another feeder run is in progress: process 4242"));
+}
+
+#[test]
+fn test_file_storage_reads_hand_written_feeds() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("feeder.toml");
+    std::fs::write(
+        &store_path,
+        r#"
+[[feeds]]
+url = "https://blog.example.com"
+feed_url = "https://blog.example.com/feed.xml"
+title = "Example Blog"
+feed_type = "rss"
+source_type = "rssatom"
+"#,
+    )
+    .unwrap();
+
+    feeder_with_store(&store_path)
+        .args(["edit", "Example Blog", "--priority", "4"])
+        .assert()
+        .success();
+    feeder_with_store(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Example Blog"));
+
+    let content = std::fs::read_to_string(&store_path).unwrap();
+    assert!(content.contains("priority = 4"));
+}
+
+#[test]
+fn test_edit_title_overrides_feed_title() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("feeder.json");
+    std::fs::write(
+        &store_path,
+        r#"{"feeds": [{"url": "https://blog.example.com", "feed_url": "https://blog.example.com/feed.xml",
+            "title": "Example Blog - Latest", "feed_type": "rss", "source_type": "rssatom"}]}"#,
+    )
+    .unwrap();
+
+    feeder_with_store(&store_path)
+        .args(["edit", "Example Blog - Latest", "--title", "Example"])
+        .assert()
+        .success();
+    feeder_with_store(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  Example [rss_atom]"))
+        .stdout(predicate::str::contains("Original title: Example Blog - Latest"));
+
+    // The custom title finds the feed too, and can be dropped again
+    feeder_with_store(&store_path)
+        .args(["edit", "example", "--no-title"])
+        .assert()
+        .success();
+    feeder_with_store(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Original title").not());
+
+    feeder_with_store(&store_path)
+        .args(["edit", "1", "--title", " "])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--no-title"));
+}
+
+#[test]
+fn test_show_feed_details() {
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("feeder.toml");
+    std::fs::write(
+        &store_path,
+        r#"
+[[feeds]]
+url = "https://blog.example.com"
+feed_url = "https://blog.example.com/feed.xml"
+title = "Example Blog"
+feed_type = "rss"
+source_type = "rssatom"
+"#,
+    )
+    .unwrap();
+
+    feeder_with_store(&store_path)
+        .env("NOTEBROOK_CHANNEL", "blogs")
+        .args(["show", "Example Blog"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Feed: https://blog.example.com/feed.xml"))
+        .stdout(predicate::str::contains("Status: never fetched"))
+        .stdout(predicate::str::contains("Notebrook channel: blogs"))
+        .stdout(predicate::str::contains("None notified yet."));
+
+    let output = feeder_with_store(&store_path)
+        .env("NOTEBROOK_CHANNEL", "blogs")
+        .args(["show", "1", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let details: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(details["title"], "Example Blog");
+    assert_eq!(details["status"], "never_fetched");
+    assert_eq!(details["notified_count"], 0);
+    assert_eq!(details["settings"]["notebrook_channel"], "blogs");
+
+    feeder_with_store(&store_path)
+        .env("NOTEBROOK_CHANNEL", "blogs")
+        .args(["show", "Missing"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_postgres_storage_needs_url() {
+    feeder_cmd()
+        .arg("list")
+        .env("FEEDER_STORAGE", "postgres")
+        .env_remove("FEEDER_DB_PATH")
+        .env("NOTEBROOK_URL", "http://localhost:8080")
+        .env("NOTEBROOK_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("FEEDER_DB_PATH"));
+}
+
+#[test]
+fn test_run_sends_pending_digest_without_new_articles() {
+    use sha2::{Digest, Sha256};
+
+    let mut server = mockito::Server::new();
+    let _feed = server
+        .mock("GET", "/feed.xml")
+        .with_body(r#"<rss version="2.0"><channel><title>Blog</title></channel></rss>"#)
+        .create();
+    let digest = server
+        .mock("POST", "/hook")
+        .match_body(mockito::Matcher::Regex("Left from last run".to_string()))
+        .with_status(200)
+        .create();
+
+    // A digest the last run couldn't send, kept for the webhook's sink ID
+    let hook = format!("{}/hook", server.url());
+    let sink = format!("webhook:{}", &format!("{:x}", Sha256::digest(hook.as_bytes()))[..16]);
+    let pending = serde_json::json!([{
+        "sink": sink,
+        "notification": {
+            "feed_title": "Blog", "article_title": "Left from last run", "text": "",
+            "links": [], "published": null, "image": null
+        }
+    }]);
+    let temp_dir = TempDir::new().unwrap();
+    let store_path = temp_dir.path().join("feeder.json");
+    let store = serde_json::json!({
+        "feeds": [{"id": 1, "url": server.url(), "feed_url": format!("{}/feed.xml", server.url()),
+            "title": "Blog", "feed_type": "rss", "source_type": "rssatom"}],
+        "state": [{"feed_id": 1, "key": "pending_notifications", "value": pending.to_string()}],
+    });
+    std::fs::write(&store_path, store.to_string()).unwrap();
+
+    feeder_with_store(&store_path)
+        .env("FEEDER_WEBHOOK_URL", &hook)
+        .env("FEEDER_WEBHOOK_MODE", "digest")
+        .arg("run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No new articles to notify."))
+        .stdout(predicate::str::contains("Sending digest of 1 articles"));
+
+    digest.assert();
+    let content = std::fs::read_to_string(&store_path).unwrap();
+    assert!(!content.contains("Left from last run"));
+}
+
 mod skip_notify_integration {
     use super::*;
 
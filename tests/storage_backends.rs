@@ -0,0 +1,557 @@
+//! The same repository behaviour checked against every storage backend.
+//!
+//! With the `postgres` feature, PostgreSQL is checked too, in a fresh schema of
+//! the database at `FEEDER_TEST_POSTGRES_URL`. `scripts/test-postgres.sh` starts a
+//! throwaway server and runs the tests against it.
+
+use feeder::domain::{Article, Feed, FeedType, RetentionPolicy, SourceType};
+use feeder::errors::FeederError;
+use feeder::storage::traits::FETCHED_AT_KEY;
+use feeder::storage::{FileStorage, MemoryStorage, SqliteStorage, Storage};
+use tempfile::TempDir;
+
+#[cfg(feature = "postgres")]
+use feeder::storage::PostgresStorage;
+
+struct Backend {
+    name: &'static str,
+    storage: Box<dyn Storage>,
+    /// Extension of backup files this backend reads and writes
+    backup_extension: &'static str,
+    /// Whether a run excludes all others, rather than sharing the feeds out
+    exclusive_runs: bool,
+    dir: TempDir,
+}
+
+fn backends() -> Vec<Backend> {
+    let sqlite_dir = TempDir::new().unwrap();
+    let toml_dir = TempDir::new().unwrap();
+    let json_dir = TempDir::new().unwrap();
+
+    vec![
+        Backend {
+            name: "sqlite",
+            storage: Box::new(SqliteStorage::new(sqlite_dir.path().join("feeder.db")).unwrap()),
+            backup_extension: "db",
+            exclusive_runs: true,
+            dir: sqlite_dir,
+        },
+        Backend {
+            name: "memory",
+            storage: Box::new(MemoryStorage::new()),
+            backup_extension: "json",
+            exclusive_runs: true,
+            dir: TempDir::new().unwrap(),
+        },
+        Backend {
+            name: "toml file",
+            storage: Box::new(FileStorage::open(toml_dir.path().join("feeder.toml")).unwrap()),
+            backup_extension: "toml",
+            exclusive_runs: true,
+            dir: toml_dir,
+        },
+        Backend {
+            name: "json file",
+            storage: Box::new(FileStorage::open(json_dir.path().join("feeder.json")).unwrap()),
+            backup_extension: "json",
+            exclusive_runs: true,
+            dir: json_dir,
+        },
+        #[cfg(feature = "postgres")]
+        Backend {
+            name: "postgres",
+            storage: Box::new(postgres_storage()),
+            backup_extension: "json",
+            exclusive_runs: false,
+            dir: TempDir::new().unwrap(),
+        },
+    ]
+}
+
+/// A PostgreSQL storage in a schema of its own, so tests don't see each other's data
+#[cfg(feature = "postgres")]
+fn postgres_storage() -> PostgresStorage {
+    PostgresStorage::with_config(&postgres_config()).unwrap()
+}
+
+/// Connection settings for a new, empty schema in the test database
+#[cfg(feature = "postgres")]
+fn postgres_config() -> postgres::Config {
+    let url = std::env::var("FEEDER_TEST_POSTGRES_URL").expect(
+        "FEEDER_TEST_POSTGRES_URL must point at a scratch database; \
+         scripts/test-postgres.sh starts one",
+    );
+    let mut config: postgres::Config = url.parse().unwrap();
+
+    let schema = format!("feeder_test_{:016x}", fastrand::u64(..));
+    let mut admin = config.connect(postgres::NoTls).unwrap();
+    admin.batch_execute(&format!("CREATE SCHEMA {}", schema)).unwrap();
+
+    config.options(&format!("-c search_path={}", schema));
+    config
+}
+
+fn feed(url: &str) -> Feed {
+    Feed::new(
+        url.to_string(),
+        format!("{}/feed", url),
+        url.trim_start_matches("https://").to_string(),
+        FeedType::Rss,
+        SourceType::RssAtom,
+    )
+}
+
+fn article(id: &str, title: &str) -> Article {
+    Article::new(id.to_string(), title.to_string())
+        .with_content(Some(format!("Text of {}", title)))
+        .with_links(vec![format!("https://a.example/{}", id)])
+}
+
+/// Set when each cache entry was notified and last seen, `hours_ago[key]` hours ago.
+/// SQLite is changed in place; the others through a backup edited and restored.
+fn backdate(backend: &Backend, hours_ago: &[(String, i64)]) {
+    if backend.name == "sqlite" {
+        let conn = rusqlite::Connection::open(backend.dir.path().join("feeder.db")).unwrap();
+        for (key, hours) in hours_ago {
+            conn.execute(
+                "UPDATE notified_articles SET notified_at = datetime('now', printf('-%d hours', ?1)),
+                 last_seen_at = datetime('now', printf('-%d hours', ?1)) WHERE cache_key = ?2",
+                (hours, key),
+            )
+            .unwrap();
+        }
+        return;
+    }
+
+    let path = backend.dir.path().join("backdate.json");
+    backend.storage.backup(&path).unwrap();
+    let mut data: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    for entry in data["notified"].as_array_mut().unwrap() {
+        let Some((_, hours)) = hours_ago.iter().find(|(key, _)| entry["cache_key"] == key.as_str()) else {
+            continue;
+        };
+        let at = (chrono::Utc::now() - chrono::Duration::hours(*hours))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        entry["notified_at"] = at.clone().into();
+        entry["last_seen_at"] = at.into();
+    }
+    std::fs::write(&path, serde_json::to_string(&data).unwrap()).unwrap();
+    backend.storage.restore(&path).unwrap();
+}
+
+/// Cache `count` entries notified a day apart, `keyN` being N and a half days old, then
+/// record a fetch at which only `live` were still in the feed
+fn notified_history(backend: &Backend, feed_id: i64, count: usize, live: &[&str]) {
+    let cache = backend.storage.article_cache();
+    let mut hours_ago = Vec::new();
+    for i in 0..count {
+        let key = format!("key{}", i);
+        cache.mark_notified(&key, feed_id, "Article").unwrap();
+        hours_ago.push((key, i as i64 * 24 + 12));
+    }
+    backdate(backend, &hours_ago);
+
+    let now = "2999-01-01 00:00:00";
+    let live: Vec<String> = live.iter().map(|k| k.to_string()).collect();
+    cache.mark_seen(&live, now).unwrap();
+    backend.storage.feed_state().set(feed_id, FETCHED_AT_KEY, now).unwrap();
+}
+
+#[test]
+fn test_feeds() {
+    for backend in backends() {
+        let feeds = backend.storage.feeds();
+        let name = backend.name;
+
+        let id = feeds.add(&feed("https://a.example")).unwrap();
+        assert!(matches!(
+            feeds.add(&feed("https://a.example")),
+            Err(FeederError::FeedAlreadyExists(_))
+        ), "{}", name);
+
+        let mut stored = feeds.get_by_id(id).unwrap().unwrap();
+        assert!(stored.created_at.is_some(), "{}", name);
+        stored.settings.priority = Some(5);
+        feeds.update(&stored).unwrap();
+        assert_eq!(feeds.get_by_url("https://a.example").unwrap().unwrap().settings.priority, Some(5), "{}", name);
+
+        stored.id = Some(id + 100);
+        assert!(matches!(feeds.update(&stored), Err(FeederError::FeedNotFound(_))), "{}", name);
+
+        // A failing batch adds nothing
+        let result = feeds.add_batch(&[feed("https://b.example"), feed("https://a.example")]);
+        assert!(result.is_err(), "{}", name);
+        assert!(!feeds.exists("https://b.example").unwrap(), "{}", name);
+
+        let ids = feeds.add_batch(&[feed("https://b.example"), feed("https://c.example")]).unwrap();
+        assert_eq!(ids.len(), 2, "{}", name);
+        assert!(ids.iter().all(|new_id| *new_id > id), "{}", name);
+        assert_eq!(feeds.get_all().unwrap().len(), 3, "{}", name);
+
+        feeds.remove(id).unwrap();
+        assert!(feeds.get_by_id(id).unwrap().is_none(), "{}", name);
+    }
+}
+
+#[test]
+fn test_article_cache() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        cache.mark_notified("k1", feed_id, "One").unwrap();
+        assert!(cache.is_notified("k1").unwrap(), "{}", name);
+
+        assert!(cache.claim("k2", feed_id, "Two").unwrap(), "{}", name);
+        assert!(!cache.claim("k2", feed_id, "Two").unwrap(), "{}", name);
+        assert!(!cache.claim("k1", feed_id, "One").unwrap(), "{}", name);
+
+        let keys = vec!["k1".to_string(), "k2".to_string(), "k3".to_string()];
+        assert_eq!(cache.get_unnotified(&keys).unwrap(), vec!["k3".to_string()], "{}", name);
+
+        // Releasing only drops claims, never sent entries
+        cache.release("k2").unwrap();
+        cache.release("k1").unwrap();
+        assert!(!cache.is_notified("k2").unwrap(), "{}", name);
+        assert!(cache.is_notified("k1").unwrap(), "{}", name);
+
+        // Confirming a claim keeps it through the next lock
+        assert!(cache.claim("k2", feed_id, "Two").unwrap(), "{}", name);
+        cache.mark_notified("k2", feed_id, "Two").unwrap();
+        assert!(cache.claim("k3", feed_id, "Three").unwrap(), "{}", name);
+        drop(backend.storage.lock_run().unwrap());
+        assert!(cache.is_notified("k2").unwrap(), "{}", name);
+        assert!(!cache.is_notified("k3").unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_prune_keeps_entries_still_in_feed() {
+    for backend in backends() {
+        let feeds = backend.storage.feeds();
+        let fetched = feeds.add(&feed("https://a.example")).unwrap();
+        let never_fetched = feeds.add(&feed("https://b.example")).unwrap();
+        let cache = backend.storage.article_cache();
+        let state = backend.storage.feed_state();
+        let name = backend.name;
+
+        cache.mark_notified("a1", fetched, "Gone from feed").unwrap();
+        cache.mark_notified("a2", fetched, "Still in feed").unwrap();
+        cache.mark_notified("b1", never_fetched, "Kept").unwrap();
+
+        cache.mark_seen(&["a2".to_string()], "2999-01-01 00:00:00").unwrap();
+        state.set(fetched, FETCHED_AT_KEY, "2999-01-01 00:00:00").unwrap();
+
+        let policy = RetentionPolicy {
+            days: None,
+            keep_per_feed: Some(0),
+        };
+        assert_eq!(cache.prune(&policy).unwrap(), 1, "{}", name);
+        assert!(!cache.is_notified("a1").unwrap(), "{}", name);
+        assert!(cache.is_notified("a2").unwrap(), "{}", name);
+        assert!(cache.is_notified("b1").unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_get_unnotified_with_more_keys_than_one_query_binds() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        let cache = backend.storage.article_cache();
+
+        let keys: Vec<String> = (0..1001).map(|i| format!("key{}", i)).collect();
+        cache.mark_notified("key0", feed_id, "First").unwrap();
+        cache.mark_notified("key1000", feed_id, "Last").unwrap();
+
+        assert_eq!(cache.get_unnotified(&keys).unwrap().len(), keys.len() - 2, "{}", backend.name);
+    }
+}
+
+#[test]
+fn test_mark_notified_batch_rolls_back_on_failure() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        let (one, two) = (article("1", "One"), article("2", "Two"));
+        cache
+            .mark_notified_batch(feed_id, &[("k1".to_string(), &one), ("k2".to_string(), &two)])
+            .unwrap();
+        assert!(cache.is_notified("k1").unwrap() && cache.is_notified("k2").unwrap(), "{}", name);
+
+        // A batch that fails stores nothing
+        let three = article("3", "Three");
+        assert!(cache.mark_notified_batch(feed_id + 1, &[("k3".to_string(), &three)]).is_err(), "{}", name);
+        assert!(!cache.is_notified("k3").unwrap(), "{}", name);
+        assert_eq!(backend.storage.history().count(None).unwrap(), 2, "{}", name);
+    }
+}
+
+#[test]
+fn test_prune_by_age_keeps_entries_still_in_feed() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        notified_history(&backend, feed_id, 10, &["key9"]);
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        let policy = RetentionPolicy {
+            days: Some(5),
+            keep_per_feed: None,
+        };
+        // key5..key8 are old and gone from the feed
+        assert_eq!(cache.prune(&policy).unwrap(), 4, "{}", name);
+        assert!(cache.is_notified("key9").unwrap(), "{}", name);
+        assert!(!cache.is_notified("key8").unwrap(), "{}", name);
+        assert!(cache.is_notified("key4").unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_prune_keeps_newest_per_feed() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        notified_history(&backend, feed_id, 6, &[]);
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        let policy = RetentionPolicy {
+            days: None,
+            keep_per_feed: Some(2),
+        };
+        assert_eq!(cache.prune(&policy).unwrap(), 4, "{}", name);
+        assert!(cache.is_notified("key1").unwrap(), "{}", name);
+        assert!(!cache.is_notified("key2").unwrap(), "{}", name);
+    }
+
+    // With both limits an entry is kept while it meets either
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        notified_history(&backend, feed_id, 6, &[]);
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        let policy = RetentionPolicy {
+            days: Some(3),
+            keep_per_feed: Some(2),
+        };
+        assert_eq!(cache.prune(&policy).unwrap(), 3, "{}", name);
+        assert!(cache.is_notified("key2").unwrap(), "{}", name);
+        assert!(!cache.is_notified("key3").unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_rekey_moves_only_the_feeds_entries() {
+    for backend in backends() {
+        let feeds = backend.storage.feeds();
+        let renamed = feeds.add(&feed("https://a.example")).unwrap();
+        let other = feeds.add(&feed("https://b.example")).unwrap();
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+
+        cache.mark_notified("Old:1", renamed, "One").unwrap();
+        cache.mark_notified("Old:2", renamed, "Two").unwrap();
+        cache.mark_notified("New:2", other, "Taken").unwrap();
+        cache.mark_notified("Old:3", other, "Other feed").unwrap();
+
+        assert_eq!(cache.rekey(renamed, "Old:", "New:").unwrap(), 1, "{}", name);
+        assert!(cache.is_notified("New:1").unwrap(), "{}", name);
+        assert!(!cache.is_notified("Old:1").unwrap(), "{}", name);
+        assert!(cache.is_notified("Old:2").unwrap(), "{}", name);
+        assert!(cache.is_notified("Old:3").unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_feed_state() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        let state = backend.storage.feed_state();
+        let name = backend.name;
+
+        assert!(state.get(feed_id, "etag").unwrap().is_none(), "{}", name);
+        state.set(feed_id, "etag", "\"v1\"").unwrap();
+        state.set(feed_id, "etag", "\"v2\"").unwrap();
+        assert_eq!(state.get(feed_id, "etag").unwrap().as_deref(), Some("\"v2\""), "{}", name);
+        assert!(state.get(feed_id + 1, "etag").unwrap().is_none(), "{}", name);
+    }
+}
+
+#[test]
+fn test_history() {
+    for backend in backends() {
+        let feeds = backend.storage.feeds();
+        let feed_id = feeds.add(&feed("https://a.example")).unwrap();
+        let history = backend.storage.history();
+        let name = backend.name;
+
+        let rust = article("1", "Async traits in Rust");
+        let garden = article("2", "Gardening tips");
+        backend
+            .storage
+            .article_cache()
+            .mark_notified_batch(feed_id, &[("k1".to_string(), &rust), ("k2".to_string(), &garden)])
+            .unwrap();
+        history.record("k2", feed_id, &garden).unwrap();
+
+        let recent = history.recent(None, 10).unwrap();
+        assert_eq!(recent.len(), 2, "{}", name);
+        assert_eq!(recent[0].feed_title, "a.example", "{}", name);
+        assert_eq!(history.recent(Some(feed_id), 1).unwrap().len(), 1, "{}", name);
+        assert_eq!(history.count(Some(feed_id)).unwrap(), 2, "{}", name);
+        assert_eq!(history.count(Some(feed_id + 1)).unwrap(), 0, "{}", name);
+
+        let found = history.search("rust", None, None, 10).unwrap();
+        assert_eq!(found.len(), 1, "{}", name);
+        assert_eq!(found[0].link.as_deref(), Some("https://a.example/1"), "{}", name);
+        assert_eq!(history.search("garden*", Some(feed_id), None, 10).unwrap().len(), 1, "{}", name);
+        assert!(history.search("rust gardening", None, None, 10).unwrap().is_empty(), "{}", name);
+        assert!(history
+            .search("rust", None, Some("2999-01-01 00:00:00".to_string()), 10)
+            .unwrap()
+            .is_empty(), "{}", name);
+
+        feeds.remove(feed_id).unwrap();
+        assert!(history.recent(None, 10).unwrap().is_empty(), "{}", name);
+        assert_eq!(history.count(None).unwrap(), 0, "{}", name);
+    }
+}
+
+#[test]
+fn test_run_lock() {
+    for backend in backends() {
+        let lock = backend.storage.lock_run().unwrap();
+        let second = backend.storage.lock_run();
+        if backend.exclusive_runs {
+            let err = second.err().unwrap();
+            assert!(matches!(err, FeederError::RunInProgress(_)), "{}: {}", backend.name, err);
+        } else {
+            assert!(second.is_ok(), "{}", backend.name);
+        }
+
+        drop(lock);
+        assert!(backend.storage.lock_run().is_ok(), "{}", backend.name);
+    }
+}
+
+#[test]
+fn test_backup_and_restore() {
+    for backend in backends() {
+        let feeds = backend.storage.feeds();
+        let name = backend.name;
+        let path = backend
+            .dir
+            .path()
+            .join(format!("backup.{}", backend.backup_extension));
+
+        feeds.add(&feed("https://a.example")).unwrap();
+        backend.storage.backup(&path).unwrap();
+
+        feeds.add(&feed("https://b.example")).unwrap();
+        assert!(backend.storage.restore(&path).unwrap() > 0, "{}", name);
+
+        let urls: Vec<String> = feeds.get_all().unwrap().into_iter().map(|feed| feed.url).collect();
+        assert_eq!(urls, vec!["https://a.example".to_string()], "{}", name);
+        assert!(backend.storage.check().unwrap().is_ok(), "{}", name);
+
+        let missing = backend.dir.path().join("missing");
+        assert!(backend.storage.restore(&missing).is_err(), "{}", name);
+    }
+}
+
+#[test]
+fn test_restore_drops_claims_and_run_lock() {
+    for backend in backends() {
+        let feed_id = backend.storage.feeds().add(&feed("https://a.example")).unwrap();
+        let cache = backend.storage.article_cache();
+        let name = backend.name;
+        let path = backend
+            .dir
+            .path()
+            .join(format!("backup.{}", backend.backup_extension));
+
+        // Backed up in the middle of a run
+        let lock = backend.storage.lock_run().unwrap();
+        assert!(cache.claim("k1", feed_id, "One").unwrap(), "{}", name);
+        backend.storage.backup(&path).unwrap();
+        drop(lock);
+
+        backend.storage.restore(&path).unwrap();
+
+        let keys = vec!["k1".to_string()];
+        assert_eq!(cache.get_unnotified(&keys).unwrap(), keys, "{}", name);
+        assert!(backend.storage.lock_run().is_ok(), "{}", name);
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres_workers_split_feeds() {
+    // Two processes sharing the database, running at the same time
+    let config = postgres_config();
+    let first = PostgresStorage::with_config(&config).unwrap();
+    let second = PostgresStorage::with_config(&config).unwrap();
+    let (first_feeds, second_feeds) = (first.feeds(), second.feeds());
+    first_feeds
+        .add_batch(&[feed("https://a.example"), feed("https://b.example"), feed("https://c.example")])
+        .unwrap();
+
+    let first_run = first.lock_run().unwrap();
+    let _second_run = second.lock_run().unwrap();
+
+    let a = first_feeds.lease_next(&[]).unwrap().unwrap().id.unwrap();
+    let b = second_feeds.lease_next(&[]).unwrap().unwrap().id.unwrap();
+    assert_ne!(a, b);
+
+    // Each feed is handed out once
+    let c = first_feeds.lease_next(&[a]).unwrap().unwrap().id.unwrap();
+    assert!(c != a && c != b);
+    assert!(second_feeds.lease_next(&[b]).unwrap().is_none());
+    assert!(first_feeds.lease_next(&[a, c]).unwrap().is_none());
+
+    // An article claimed by one worker is left alone by the other
+    assert!(first.article_cache().claim("k1", a, "One").unwrap());
+    assert!(!second.article_cache().claim("k1", a, "One").unwrap());
+
+    // The feeds of a finished run are free again, and its claims too once its
+    // worker is gone
+    drop(first_run);
+    assert!(second_feeds.lease_next(&[b]).unwrap().is_some());
+    assert!(second.article_cache().is_notified("k1").unwrap());
+    let third = PostgresStorage::with_config(&config).unwrap();
+    drop(third.lock_run().unwrap());
+    assert!(!second.article_cache().is_notified("k1").unwrap());
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres_dead_worker_leases_released() {
+    let config = postgres_config();
+    let dead = PostgresStorage::with_config(&config).unwrap();
+    let alive = PostgresStorage::with_config(&config).unwrap();
+    let feed_id = dead.feeds().add(&feed("https://a.example")).unwrap();
+
+    // A process that died mid-run: its registration is never refreshed or removed
+    let run = dead.lock_run().unwrap();
+    assert_eq!(dead.feeds().lease_next(&[]).unwrap().unwrap().id, Some(feed_id));
+    assert!(dead.article_cache().claim("k1", feed_id, "One").unwrap());
+    std::mem::forget(run);
+
+    let _run = alive.lock_run().unwrap();
+    assert!(alive.feeds().lease_next(&[]).unwrap().is_none());
+    assert!(alive.article_cache().is_notified("k1").unwrap());
+
+    dead.client()
+        .unwrap()
+        .execute("UPDATE workers SET heartbeat_at = heartbeat_at - interval '1 hour'", &[])
+        .unwrap();
+    assert_eq!(alive.feeds().lease_next(&[]).unwrap().unwrap().id, Some(feed_id));
+    let restarted = PostgresStorage::with_config(&config).unwrap();
+    drop(restarted.lock_run().unwrap());
+    assert!(!alive.article_cache().is_notified("k1").unwrap());
+}